@@ -0,0 +1,41 @@
+//! Per-task client cost: cloning the client, and assembling the headers of an invoke.
+//! `cargo bench --bench client_clone`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::collections::HashMap;
+use std::hint::black_box;
+use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient, FunctionsError};
+
+fn client() -> FunctionsClient {
+    let headers: HashMap<String, String> = (0..8).map(|i| (format!("x-default-{}", i), format!("value-{}", i))).collect();
+    FunctionsClient::builder("https://project-ref.supabase.co/functions/v1")
+        .headers(headers)
+        .header("Authorization", "Bearer token")
+        .header("apikey", "key")
+        .build()
+        .unwrap()
+}
+
+fn client_clone(c: &mut Criterion) {
+    let client = client();
+    c.bench_function("clone", |b| b.iter(|| black_box(&client).clone()));
+
+    // The signer runs right after the headers are assembled; failing there keeps the
+    // network out of the measurement.
+    let mut assembling = client.clone();
+    assembling.set_request_signer(|_| Err(FunctionsError::BuildError("stop".into())));
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let options = FunctionInvokeOptions {
+        headers: Some(HashMap::from([("x-request-id".to_string(), "abc".to_string())])),
+        ..Default::default()
+    };
+    c.bench_function("clone_and_assemble_headers", |b| {
+        b.iter(|| {
+            let client = assembling.clone();
+            runtime.block_on(client.invoke("function-name", Some(options.clone()))).unwrap_err()
+        })
+    });
+}
+
+criterion_group!(benches, client_clone);
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+//! Compares the JSON backends on a ~5 MB response body:
+//! `cargo bench --features simd-json --bench json_backends`.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use serde_json::{json, Value};
+use std::hint::black_box;
+
+fn fixture() -> Vec<u8> {
+    let rows: Vec<Value> = (0..14_000)
+        .map(|i| {
+            json!({
+                "id": i,
+                "name": format!("customer {}", i),
+                "email": format!("customer{}@example.com", i),
+                "active": i % 3 != 0,
+                "balance": i as f64 * 1.25,
+                "tags": ["alpha", "beta", "gamma"],
+                "address": {"street": "1 Main Street", "city": "Lagos", "zip": format!("{:05}", i)},
+                "history": (0..5).map(|n| json!({"at": 1_700_000_000 + n, "amount": n * i})).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    serde_json::to_vec(&rows).unwrap()
+}
+
+fn json_backends(c: &mut Criterion) {
+    let body = fixture();
+    let mut group = c.benchmark_group("parse_response");
+    group.throughput(Throughput::Bytes(body.len() as u64));
+    group.sample_size(20);
+    group.bench_function("serde_json", |b| {
+        b.iter(|| serde_json::from_slice::<Value>(black_box(&body)).unwrap())
+    });
+    // Includes the copy the client makes, since simd-json parses in place.
+    group.bench_function("simd_json", |b| {
+        b.iter(|| simd_json::serde::from_slice::<Value>(&mut black_box(&body).to_vec()).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, json_backends);
+criterion_main!(benches);
@@ -0,0 +1,103 @@
+use crate::errors::{preview, FunctionsError, HEADER_PREVIEW_LEN};
+use reqwest::header::HeaderValue;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+
+/// Supplies the bearer token for each invocation.
+///
+/// When an invocation is rejected with a 401, the client calls `invalidate` and
+/// `token` once more, then replays the request a single time with the new token.
+pub trait TokenProvider: fmt::Debug + Send + Sync {
+    fn token(&self) -> Pin<Box<dyn Future<Output = Result<String, FunctionsError>> + Send + '_>>;
+
+    /// Called after a 401 so cached tokens can be dropped before the next `token` call.
+    fn invalidate(&self) {}
+}
+
+/// What `set_auth`, `set_api_key` and `clone_with_auth` take: `secrecy::SecretString` with the
+/// `secrecy` feature, so Debug never prints the value, and `String` without it.
+#[cfg(feature = "secrecy")]
+pub type Secret = secrecy::SecretString;
+/// What `set_auth`, `set_api_key` and `clone_with_auth` take: `secrecy::SecretString` with the
+/// `secrecy` feature, so Debug never prints the value, and `String` without it.
+#[cfg(not(feature = "secrecy"))]
+pub type Secret = String;
+
+#[cfg(feature = "secrecy")]
+pub(crate) fn secret(value: String) -> Secret {
+    Secret::from(value)
+}
+
+#[cfg(not(feature = "secrecy"))]
+pub(crate) fn secret(value: String) -> Secret {
+    value
+}
+
+#[cfg(feature = "secrecy")]
+pub(crate) fn expose(secret: &Secret) -> &str {
+    secrecy::ExposeSecret::expose_secret(secret)
+}
+
+#[cfg(not(feature = "secrecy"))]
+pub(crate) fn expose(secret: &Secret) -> &str {
+    secret
+}
+
+/// One of the client's credentials, `Authorization` or `apikey`, replaceable through `&self`. Readers take a snapshot, so
+/// an invoke keeps the value it started with. A clone starts from the current value and is
+/// updated on its own.
+#[derive(Debug, Default)]
+pub(crate) struct AuthCell(RwLock<Option<Arc<Secret>>>);
+
+impl AuthCell {
+    pub(crate) fn new(value: Option<Secret>) -> Self {
+        Self(RwLock::new(value.map(Arc::new)))
+    }
+
+    pub(crate) fn get(&self) -> Option<Arc<Secret>> {
+        self.0.read().unwrap().clone()
+    }
+
+    pub(crate) fn set(&self, value: Secret) {
+        *self.0.write().unwrap() = Some(Arc::new(value));
+    }
+}
+
+impl Clone for AuthCell {
+    fn clone(&self) -> Self {
+        Self(RwLock::new(self.get()))
+    }
+}
+
+// Marked sensitive, and a bad value is reported without echoing it.
+pub(crate) fn credential_header(name: &str, value: &str) -> Result<HeaderValue, FunctionsError> {
+    let mut header = HeaderValue::from_str(value).map_err(|_| FunctionsError::InvalidHeader {
+        name: preview(name, HEADER_PREVIEW_LEN),
+        reason: "invalid header value".into(),
+    })?;
+    header.set_sensitive(true);
+    Ok(header)
+}
+
+/// `token` as an `Authorization` value. Surrounding whitespace and a leading `Bearer ` are
+/// dropped, so a pasted header or a line from a `.env` file works, and a token a header can't
+/// carry is rejected without echoing it.
+pub(crate) fn bearer(token: &str) -> Result<Secret, FunctionsError> {
+    let token = token.trim();
+    let token = match token.get(..6) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("bearer") && token[6..].starts_with(char::is_whitespace) => token[6..].trim_start(),
+        _ => token,
+    };
+    if token.is_empty() || token.eq_ignore_ascii_case("bearer") {
+        return Err(FunctionsError::InvalidHeader { name: "Authorization".into(), reason: "empty token".into() });
+    }
+    let value = format!("Bearer {}", token);
+    credential_header("Authorization", &value)?;
+    #[cfg(feature = "tracing")]
+    if token.split('.').filter(|part| !part.is_empty()).count() != 3 {
+        tracing::warn!("the token passed to set_auth does not look like a JWT");
+    }
+    Ok(secret(value))
+}
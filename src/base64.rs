@@ -0,0 +1,91 @@
+use crate::errors::FunctionsError;
+use crate::models::ResponseData;
+use bytes::Bytes;
+use serde_json::Value;
+
+impl ResponseData {
+    /// Decodes a base64 body: `Text`, or a `Json` string. Whitespace and line breaks are
+    /// skipped, the standard and URL-safe alphabets are both accepted, and padding is optional.
+    /// Invalid input fails with a `DeserializeError` naming the byte offset of the problem.
+    pub fn decode_base64(&self) -> Result<Bytes, FunctionsError> {
+        let encoded = match self {
+            ResponseData::Text(text) => text.as_str(),
+            ResponseData::Json(Value::String(text)) => text.as_str(),
+            other => return Err(FunctionsError::DeserializeError(format!("expected base64 text, got {:?}", other))),
+        };
+        decode(encoded).map(Bytes::from).map_err(FunctionsError::DeserializeError)
+    }
+
+    /// Decodes the base64 string in the `field` of a `Json` object, as
+    /// [`decode_base64`](Self::decode_base64) would. Offsets count from the start of the field's value.
+    pub fn decode_base64_field(&self, field: &str) -> Result<Bytes, FunctionsError> {
+        match self {
+            ResponseData::Json(Value::Object(object)) => match object.get(field) {
+                Some(Value::String(text)) => decode(text)
+                    .map(Bytes::from)
+                    .map_err(|e| FunctionsError::DeserializeError(format!("field `{}`: {}", field, e))),
+                Some(other) => Err(FunctionsError::DeserializeError(format!("field `{}` is not a string: {}", field, other))),
+                None => Err(FunctionsError::DeserializeError(format!("no field `{}` in the JSON object", field))),
+            },
+            other => Err(FunctionsError::DeserializeError(format!("expected a JSON object, got {:?}", other))),
+        }
+    }
+}
+
+fn decode(encoded: &str) -> Result<Vec<u8>, String> {
+    let invalid = |offset: usize| {
+        let found = encoded[offset..].chars().next().unwrap_or_default();
+        format!("invalid base64 at offset {}: unexpected {:?}", offset, found)
+    };
+    let mut decoded = Vec::with_capacity(encoded.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut filled = 0;
+    let mut padding = 0;
+    for (offset, byte) in encoded.bytes().enumerate() {
+        if byte.is_ascii_whitespace() {
+            continue;
+        }
+        if byte == b'=' {
+            // Padding can only complete a group of two or three characters.
+            if filled < 2 || filled + padding == 4 {
+                return Err(invalid(offset));
+            }
+            padding += 1;
+            continue;
+        }
+        let value = match sextet(byte) {
+            Some(value) if padding == 0 => value,
+            _ => return Err(invalid(offset)),
+        };
+        buffer = buffer << 6 | value;
+        filled += 1;
+        if filled == 4 {
+            decoded.extend_from_slice(&buffer.to_be_bytes()[1..]);
+            buffer = 0;
+            filled = 0;
+        }
+    }
+    match (filled, padding) {
+        (0, 0) | (2, 0) | (2, 2) | (3, 0) | (3, 1) => {}
+        (1, _) => return Err(format!("invalid base64 at offset {}: input ends mid-byte", encoded.len())),
+        _ => return Err(format!("invalid base64 at offset {}: incomplete padding", encoded.len())),
+    }
+    match filled {
+        2 => decoded.push((buffer >> 4) as u8),
+        3 => decoded.extend_from_slice(&((buffer >> 2) as u16).to_be_bytes()),
+        _ => {}
+    }
+    Ok(decoded)
+}
+
+fn sextet(byte: u8) -> Option<u32> {
+    let value = match byte {
+        b'A'..=b'Z' => byte - b'A',
+        b'a'..=b'z' => byte - b'a' + 26,
+        b'0'..=b'9' => byte - b'0' + 52,
+        b'+' | b'-' => 62,
+        b'/' | b'_' => 63,
+        _ => return None,
+    };
+    Some(value.into())
+}
@@ -0,0 +1,246 @@
+use crate::models::{FunctionInvokeOptions, FunctionRegion, HttpMethod, InvokeBody, ResponseData, ResponseMetadata};
+use futures_util::lock::{Mutex as AsyncMutex, OwnedMutexGuard};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Opt-in in-memory cache for successful responses, shared by clones of the client.
+///
+/// Only `GET` responses are cached unless `cache_post` is set. Entries are keyed by the
+/// function name, method, region, body, per-invoke headers and the client's credentials.
+/// Expired entries with an ETag are revalidated with `If-None-Match` instead of refetched.
+#[derive(Debug, Clone)]
+pub struct CachePolicy {
+    pub ttl: Duration,
+    /// The oldest entry is evicted when a new one would exceed this.
+    pub max_entries: usize,
+    pub cache_post: bool,
+    /// Concurrent misses for the same key wait for a single request instead of each sending one.
+    pub coalesce: bool,
+}
+
+impl CachePolicy {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, max_entries: 1000, cache_post: false, coalesce: true }
+    }
+
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    pub fn cache_post(mut self, cache_post: bool) -> Self {
+        self.cache_post = cache_post;
+        self
+    }
+
+    pub fn coalesce(mut self, coalesce: bool) -> Self {
+        self.coalesce = coalesce;
+        self
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    function_name: String,
+    // The rest of the request, credentials and body included, compared in full rather than as
+    // a digest so two requests never share an entry through a hash collision.
+    material: Arc<[u8]>,
+}
+
+// The material holds credentials, and the client's Debug output includes the cache.
+impl fmt::Debug for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CacheKey").field("function_name", &self.function_name).finish_non_exhaustive()
+    }
+}
+
+/// Keeps every byte written to it instead of mixing them into a digest. `Hash` impls write a
+/// prefix-free encoding, so equal bytes mean equal values.
+#[derive(Default)]
+struct KeyWriter(Vec<u8>);
+
+impl Hasher for KeyWriter {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    // Only the bytes are used.
+    fn finish(&self) -> u64 {
+        0
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    data: ResponseData,
+    metadata: ResponseMetadata,
+    expires_at: Instant,
+}
+
+#[derive(Debug)]
+pub(crate) struct ResponseCache {
+    policy: CachePolicy,
+    entries: Mutex<HashMap<CacheKey, Entry>>,
+    in_flight: Mutex<HashMap<CacheKey, Arc<AsyncMutex<()>>>>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(policy: CachePolicy) -> Self {
+        Self { policy, entries: Mutex::new(HashMap::new()), in_flight: Mutex::new(HashMap::new()) }
+    }
+
+    /// `None` when responses to `method` are not cached.
+    pub(crate) fn key(
+        &self,
+        function_name: &str,
+        method: &HttpMethod,
+        region: FunctionRegion,
+        options: &FunctionInvokeOptions,
+        credentials: &[Option<&str>],
+    ) -> Option<CacheKey> {
+        match method {
+            HttpMethod::Get => {}
+            HttpMethod::Post if self.policy.cache_post => {}
+            _ => return None,
+        }
+        Some(request_key(function_name, method, region, options, credentials))
+    }
+
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<(ResponseData, ResponseMetadata)> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some((entry.data.clone(), entry.metadata.clone())),
+            // Expired entries with an ETag stay around to be revalidated.
+            Some(entry) if entry.metadata.etag().is_none() => {
+                entries.remove(key);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// The ETag of an expired entry, to revalidate it with `If-None-Match`.
+    pub(crate) fn stale_etag(&self, key: &CacheKey) -> Option<String> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(key).and_then(|entry| entry.metadata.etag()).map(str::to_string)
+    }
+
+    /// Restarts the TTL of an entry the server reported as not modified.
+    pub(crate) fn refresh(&self, key: &CacheKey) -> Option<(ResponseData, ResponseMetadata)> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get_mut(key)?;
+        entry.expires_at = Instant::now() + self.policy.ttl;
+        Some((entry.data.clone(), entry.metadata.clone()))
+    }
+
+    pub(crate) fn insert(&self, key: CacheKey, data: ResponseData, metadata: ResponseMetadata) {
+        if self.policy.max_entries == 0 {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if !entries.contains_key(&key) && entries.len() >= self.policy.max_entries {
+            entries.retain(|_, entry| entry.expires_at > now);
+        }
+        while !entries.contains_key(&key) && entries.len() >= self.policy.max_entries {
+            // Every entry shares the TTL, so the earliest expiry is the oldest insert.
+            let oldest = entries.iter().min_by_key(|(_, entry)| entry.expires_at).map(|(key, _)| key.clone());
+            match oldest {
+                Some(oldest) => entries.remove(&oldest),
+                None => break,
+            };
+        }
+        entries.insert(key, Entry { data, metadata, expires_at: now + self.policy.ttl });
+    }
+
+    pub(crate) fn invalidate(&self, function_name: &str) {
+        self.entries.lock().unwrap().retain(|key, _| key.function_name != function_name);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Waits until no other caller is fetching `key`; `None` when coalescing is off.
+    pub(crate) async fn join_flight<'a>(&'a self, key: &CacheKey) -> Option<Flight<'a>> {
+        if !self.policy.coalesce {
+            return None;
+        }
+        let lock = self.in_flight.lock().unwrap().entry(key.clone()).or_default().clone();
+        let guard = lock.clone().lock_owned().await;
+        Some(Flight { cache: self, key: key.clone(), lock, _guard: guard })
+    }
+}
+
+/// Identifies a request by function name, method, region, body, per-invoke headers and
+/// credentials, for the cache and for coalescing.
+pub(crate) fn request_key(
+    function_name: &str,
+    method: &HttpMethod,
+    region: FunctionRegion,
+    options: &FunctionInvokeOptions,
+    credentials: &[Option<&str>],
+) -> CacheKey {
+    let mut hasher = KeyWriter::default();
+    method.as_str().hash(&mut hasher);
+    region.hash(&mut hasher);
+    credentials.hash(&mut hasher);
+    options.base_url.hash(&mut hasher);
+    options.path.hash(&mut hasher);
+    options.query.hash(&mut hasher);
+    options.query_lists.hash(&mut hasher);
+    options.array_style.hash(&mut hasher);
+    options.response_type.hash(&mut hasher);
+    options.detect_content_type.unwrap_or(false).hash(&mut hasher);
+    options.keep_raw_body.unwrap_or(false).hash(&mut hasher);
+    options.header_mode.hash(&mut hasher);
+    if let Some(ref headers) = options.headers {
+        let mut headers: Vec<_> = headers.iter().map(|(name, value)| (name.to_ascii_lowercase(), value)).collect();
+        headers.sort();
+        headers.hash(&mut hasher);
+    }
+    if let Some(ref body) = options.body {
+        hash_body(body, &mut hasher);
+    }
+    CacheKey { function_name: function_name.to_string(), material: hasher.0.into() }
+}
+
+/// Held while fetching a key; waiters re-check the cache once it is dropped.
+pub(crate) struct Flight<'a> {
+    cache: &'a ResponseCache,
+    key: CacheKey,
+    lock: Arc<AsyncMutex<()>>,
+    _guard: OwnedMutexGuard<()>,
+}
+
+impl Drop for Flight<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self.cache.in_flight.lock().unwrap();
+        // The map, `lock` and the guard are the only references when nobody else is waiting.
+        if Arc::strong_count(&self.lock) == 3 {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+fn hash_body(body: &InvokeBody, hasher: &mut KeyWriter) {
+    std::mem::discriminant(body).hash(hasher);
+    match body {
+        InvokeBody::File(bytes) | InvokeBody::Blob(bytes) | InvokeBody::ArrayBuffer(bytes) => bytes.hash(hasher),
+        InvokeBody::String(text) => text.hash(hasher),
+        #[cfg(feature = "prost")]
+        InvokeBody::Protobuf(bytes) => bytes.hash(hasher),
+        #[cfg(feature = "cbor")]
+        InvokeBody::Cbor(bytes) => bytes.hash(hasher),
+        #[cfg(feature = "multipart")]
+        InvokeBody::FormData(fields) => fields.hash(hasher),
+        InvokeBody::Json(fields) => {
+            let mut fields: Vec<_> = fields.iter().map(|(name, value)| (name, value.to_string())).collect();
+            fields.sort();
+            fields.hash(hasher);
+        }
+    }
+}
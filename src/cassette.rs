@@ -0,0 +1,258 @@
+use crate::errors::FunctionsError;
+use crate::models;
+use reqwest::{Client, Response, ResponseBuilderExt};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Records invocations to a JSON cassette file, or replays them from one without touching the
+/// network, for integration tests that can't reach a real project.
+///
+/// A replayed request matches a recorded one with the same method and path (query included),
+/// the same body unless `match_body(false)`, and the same values for the `match_headers`.
+/// Each recording is used once, in order; when all matching ones are used the first is reused.
+/// Credential headers are never written to the cassette.
+pub struct Cassette {
+    path: PathBuf,
+    mode: Mode,
+    match_body: bool,
+    match_headers: Vec<String>,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Record,
+    Replay,
+}
+
+#[derive(Default)]
+struct State {
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<StoredBody>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: StoredBody,
+}
+
+/// UTF-8 bodies are stored as strings so cassettes stay readable, anything else as a byte array.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredBody {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl StoredBody {
+    fn new(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => StoredBody::Text(text.to_string()),
+            Err(_) => StoredBody::Binary(bytes.to_vec()),
+        }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        match self {
+            StoredBody::Text(text) => text.into_bytes(),
+            StoredBody::Binary(bytes) => bytes,
+        }
+    }
+}
+
+impl fmt::Display for StoredBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoredBody::Text(text) => write!(f, "{:?}", text),
+            StoredBody::Binary(bytes) => write!(f, "{} binary bytes", bytes.len()),
+        }
+    }
+}
+
+impl Cassette {
+    /// Sends requests over the network and writes each exchange to `path`, replacing the file.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self::new(path.into(), Mode::Record, State::default())
+    }
+
+    /// Answers requests from the cassette at `path`. Fails if it can't be read or parsed.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, FunctionsError> {
+        let path = path.into();
+        let contents = std::fs::read(&path)
+            .map_err(|e| FunctionsError::BuildError(format!("couldn't read cassette {}: {}", path.display(), e)))?;
+        let file: CassetteFile = serde_json::from_slice(&contents)
+            .map_err(|e| FunctionsError::DeserializeError(format!("cassette {}: {}", path.display(), e)))?;
+        let used = vec![false; file.interactions.len()];
+        Ok(Self::new(path, Mode::Replay, State { interactions: file.interactions, used }))
+    }
+
+    fn new(path: PathBuf, mode: Mode, state: State) -> Self {
+        Cassette { path, mode, match_body: true, match_headers: Vec::new(), state: Mutex::new(state) }
+    }
+
+    /// Whether replayed requests must have the recorded body. On by default.
+    pub fn match_body(mut self, match_body: bool) -> Self {
+        self.match_body = match_body;
+        self
+    }
+
+    /// Headers whose values must also match when replaying. A header missing on both sides matches.
+    pub fn match_headers<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.match_headers = names.into_iter().map(|name| name.into().to_ascii_lowercase()).collect();
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) async fn execute(&self, client: &Client, request: reqwest::Request) -> Result<Response, FunctionsError> {
+        let url = request.url().clone();
+        let recorded = recorded_request(&request);
+        if self.mode == Mode::Replay {
+            let response = self.find(&recorded)?;
+            return build_response(url, response);
+        }
+
+        let response = client.execute(request).await.map_err(FunctionsError::from_reqwest)?;
+        let status = response.status().as_u16();
+        let headers = header_pairs(response.headers(), false);
+        let body = response.bytes().await.map_err(FunctionsError::from_reqwest)?;
+        let response = RecordedResponse { status, headers, body: StoredBody::new(&body) };
+
+        let mut state = self.state.lock().unwrap();
+        state.interactions.push(Interaction { request: recorded, response: response.clone() });
+        let file = CassetteFile { interactions: state.interactions.clone() };
+        let json = serde_json::to_vec_pretty(&file).map_err(|e| FunctionsError::BuildError(e.to_string()))?;
+        std::fs::write(&self.path, json)
+            .map_err(|e| FunctionsError::BuildError(format!("couldn't write cassette {}: {}", self.path.display(), e)))?;
+        build_response(url, response)
+    }
+
+    fn find(&self, request: &RecordedRequest) -> Result<RecordedResponse, FunctionsError> {
+        let mut state = self.state.lock().unwrap();
+        let differences: Vec<Vec<String>> =
+            state.interactions.iter().map(|interaction| self.differences(&interaction.request, request)).collect();
+        let matching = || differences.iter().enumerate().filter(|(_, differences)| differences.is_empty()).map(|(i, _)| i);
+        let index = matching().find(|&i| !state.used[i]).or_else(|| matching().next());
+        if let Some(index) = index {
+            state.used[index] = true;
+            return Ok(state.interactions[index].response.clone());
+        }
+
+        let mut message = format!("no interaction in cassette {} matches {} {}", self.path.display(), request.method, request.path);
+        if let Some((index, closest)) = differences.iter().enumerate().min_by_key(|(_, differences)| differences.len()) {
+            message.push_str(&format!("; the closest, #{}, differs in:", index + 1));
+            for difference in closest {
+                message.push_str("\n  ");
+                message.push_str(difference);
+            }
+        }
+        Err(FunctionsError::FetchError(message))
+    }
+
+    // Each difference reads `field: - recorded + request`.
+    fn differences(&self, recorded: &RecordedRequest, request: &RecordedRequest) -> Vec<String> {
+        let mut differences = Vec::new();
+        let mut compare = |field: &str, recorded: String, request: String| {
+            if recorded != request {
+                differences.push(format!("{}:\n    - {}\n    + {}", field, recorded, request));
+            }
+        };
+        compare("method", recorded.method.clone(), request.method.clone());
+        compare("path", recorded.path.clone(), request.path.clone());
+        if self.match_body {
+            compare("body", describe(recorded.body.as_ref()), describe(request.body.as_ref()));
+        }
+        for name in &self.match_headers {
+            compare(&format!("header {}", name), header_value(recorded, name), header_value(request, name));
+        }
+        differences
+    }
+}
+
+impl fmt::Debug for Cassette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cassette")
+            .field("path", &self.path)
+            .field("mode", &self.mode)
+            .field("match_body", &self.match_body)
+            .field("match_headers", &self.match_headers)
+            .finish()
+    }
+}
+
+fn recorded_request(request: &reqwest::Request) -> RecordedRequest {
+    let url = request.url();
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    RecordedRequest {
+        method: request.method().to_string(),
+        path,
+        headers: header_pairs(request.headers(), true),
+        // Multipart bodies are streamed, and their boundary changes every time anyway.
+        body: request.body().and_then(|body| body.as_bytes()).map(StoredBody::new),
+    }
+}
+
+fn header_pairs(headers: &reqwest::header::HeaderMap, skip_secrets: bool) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter(|(name, value)| !(skip_secrets && models::is_secret(name, value)))
+        .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+        .collect()
+}
+
+fn header_value(request: &RecordedRequest, name: &str) -> String {
+    let values: Vec<_> = request.headers.iter().filter(|(n, _)| n == name).map(|(_, value)| format!("{:?}", value)).collect();
+    if values.is_empty() {
+        "(none)".to_string()
+    } else {
+        values.join(", ")
+    }
+}
+
+fn describe(body: Option<&StoredBody>) -> String {
+    body.map_or_else(|| "(none)".to_string(), StoredBody::to_string)
+}
+
+fn build_response(url: reqwest::Url, recorded: RecordedResponse) -> Result<Response, FunctionsError> {
+    let mut builder = http::Response::builder().status(recorded.status).url(url);
+    for (name, value) in &recorded.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    let response = builder
+        .body(recorded.body.into_bytes())
+        .map_err(|e| FunctionsError::DeserializeError(format!("invalid recorded response: {}", e)))?;
+    Ok(Response::from(response))
+}
@@ -0,0 +1,49 @@
+use crate::errors::FunctionsError;
+use crate::models::{FunctionsResponse, InvokeBody, ResponseData};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub(crate) const MEDIA_TYPE: &str = "application/cbor";
+
+/// `application/cbor` responses are read as `Bytes`.
+pub(crate) fn is_cbor(media_type: &str) -> bool {
+    media_type == MEDIA_TYPE
+}
+
+impl InvokeBody {
+    /// Serializes `value` as an `application/cbor` body, for the `body` of
+    /// [`FunctionInvokeOptions`](crate::FunctionInvokeOptions). Unlike a
+    /// [`Json`](InvokeBody::Json) body, the value is encoded here, so a value CBOR can't represent
+    /// fails before any request is made.
+    pub fn cbor<T: Serialize>(value: &T) -> Result<Self, FunctionsError> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(value, &mut bytes).map_err(|e| FunctionsError::BuildError(format!("invalid CBOR body: {}", e)))?;
+        Ok(InvokeBody::Cbor(bytes))
+    }
+}
+
+impl FunctionsResponse {
+    /// Flattens the response like [`into_result`](Self::into_result), then deserializes the body
+    /// into `T` in the format its `Content-Type` names: CBOR or JSON. Any other body fails with a
+    /// `DeserializeError`.
+    ///
+    /// This lives on the response rather than on [`ResponseData`] because a CBOR body is only
+    /// `Bytes` there; the format comes from the response metadata.
+    pub fn deserialize_into<T: DeserializeOwned>(self) -> Result<T, FunctionsError> {
+        let media_type = self.metadata().and_then(|metadata| metadata.media_type());
+        let data = self.into_result()?;
+        match (media_type.as_deref(), &data) {
+            (Some(MEDIA_TYPE), ResponseData::Bytes(bytes)) => {
+                ciborium::from_reader(bytes.as_ref()).map_err(|e| FunctionsError::DeserializeError(format!("invalid CBOR: {}", e)))
+            }
+            (Some(MEDIA_TYPE), other) => Err(FunctionsError::DeserializeError(format!("expected a CBOR document, got {:?}", other))),
+            (_, ResponseData::Json(value)) => {
+                T::deserialize(value).map_err(|e| FunctionsError::DeserializeError(format!("invalid JSON: {}", e)))
+            }
+            (media_type, _) => Err(FunctionsError::DeserializeError(format!(
+                "can't deserialize a {} body",
+                media_type.unwrap_or("unlabelled")
+            ))),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::cache::CacheKey;
+use crate::errors::FunctionsError;
+use crate::models::FunctionsResponse;
+use futures_util::future::{BoxFuture, FutureExt, Shared, WeakShared};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+type Invocation = BoxFuture<'static, Result<FunctionsResponse, FunctionsError>>;
+
+/// The coalesced invocations in flight, shared by clones of the client. Entries are weak, so
+/// an invocation all of whose callers went away is no longer joined.
+#[derive(Default)]
+pub(crate) struct Coalescer {
+    in_flight: Mutex<InFlight>,
+}
+
+#[derive(Default)]
+struct InFlight {
+    next_id: u64,
+    leaders: HashMap<CacheKey, (u64, WeakShared<Invocation>)>,
+}
+
+impl fmt::Debug for Coalescer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Coalescer").field("in_flight", &self.in_flight.lock().unwrap().leaders.len()).finish()
+    }
+}
+
+impl Coalescer {
+    /// Joins the invocation in flight for `key`, or starts one with `start` for later callers
+    /// to join. Every caller gets a clone of its outcome.
+    pub(crate) fn join<F>(self: &Arc<Self>, key: CacheKey, start: impl FnOnce() -> F) -> Shared<Invocation>
+    where
+        F: Future<Output = Result<FunctionsResponse, FunctionsError>> + Send + 'static,
+    {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if let Some(leader) = in_flight.leaders.get(&key).and_then(|(_, leader)| leader.upgrade()) {
+            return leader;
+        }
+
+        in_flight.next_id += 1;
+        let id = in_flight.next_id;
+        let invocation = start();
+        let leave = Leave { coalescer: self.clone(), key: key.clone(), id };
+        let leader = async move {
+            let _leave = leave;
+            invocation.await
+        }
+        .boxed()
+        .shared();
+        let weak = leader.downgrade().expect("a shared future that was never polled has no output");
+        in_flight.leaders.insert(key, (id, weak));
+        leader
+    }
+}
+
+/// Removes the leader's entry when it finishes, panics or is dropped by every caller, so the
+/// next call sends a fresh request.
+struct Leave {
+    coalescer: Arc<Coalescer>,
+    key: CacheKey,
+    id: u64,
+}
+
+impl Drop for Leave {
+    fn drop(&mut self) {
+        let mut in_flight = self.coalescer.in_flight.lock().unwrap();
+        // A newer leader may already have replaced an entry whose callers all went away.
+        if in_flight.leaders.get(&self.key).is_some_and(|(id, _)| *id == self.id) {
+            in_flight.leaders.remove(&self.key);
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use crate::errors::FunctionsError;
+use crate::models::ResponseData;
+use bytes::Bytes;
+use csv::ByteRecord;
+use csv_core::ReadRecordResult;
+use futures_util::stream::{self, Stream};
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
+
+/// How CSV bodies are read: the field delimiter and whether the first record names the columns.
+///
+/// With headers, records deserialize into structs by column name; without, by position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    delimiter: u8,
+    has_headers: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions { delimiter: b',', has_headers: true }
+    }
+}
+
+impl CsvOptions {
+    /// Comma-delimited with a header row.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn has_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+}
+
+impl ResponseData {
+    /// Deserializes every record of a comma-delimited CSV response with a header row. Works on
+    /// `Text` and `Bytes`; quoted fields may contain delimiters and newlines.
+    pub fn csv_records<T: DeserializeOwned>(&self) -> Result<Vec<T>, FunctionsError> {
+        self.csv_records_with(CsvOptions::default())
+    }
+
+    /// [`csv_records`](Self::csv_records) with another delimiter or without a header row.
+    pub fn csv_records_with<T: DeserializeOwned>(&self, options: CsvOptions) -> Result<Vec<T>, FunctionsError> {
+        let body = match self {
+            ResponseData::Text(text) => text.as_bytes(),
+            ResponseData::Bytes(bytes) => bytes,
+            other => return Err(FunctionsError::DeserializeError(format!("expected a CSV document, got {:?}", other))),
+        };
+        csv::ReaderBuilder::new()
+            .delimiter(options.delimiter)
+            .has_headers(options.has_headers)
+            .from_reader(body)
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .map_err(|e| FunctionsError::DeserializeError(format!("invalid CSV: {}", e)))
+    }
+}
+
+/// Yields the records of a CSV body as they complete, holding at most one partial record in memory.
+pub(crate) fn records<T: DeserializeOwned>(
+    response: Response,
+    options: CsvOptions,
+) -> impl Stream<Item = Result<T, FunctionsError>> {
+    let state = RecordsState {
+        response: Some(response),
+        reader: csv_core::ReaderBuilder::new().delimiter(options.delimiter).build(),
+        chunk: Bytes::new(),
+        consumed: 0,
+        fields: vec![0; 1024],
+        written: 0,
+        ends: vec![0; 32],
+        ended: 0,
+        has_headers: options.has_headers,
+        headers: None,
+        width: None,
+        index: 0,
+        done: false,
+        _record: PhantomData,
+    };
+    stream::unfold(state, next_record)
+}
+
+struct RecordsState<T> {
+    /// `None` once the body has been read to the end.
+    response: Option<Response>,
+    reader: csv_core::Reader,
+    chunk: Bytes,
+    consumed: usize,
+    // The fields of the record being parsed, and where each of them ends.
+    fields: Vec<u8>,
+    written: usize,
+    ends: Vec<usize>,
+    ended: usize,
+    has_headers: bool,
+    headers: Option<ByteRecord>,
+    /// The number of fields every record must have, set by the first one.
+    width: Option<usize>,
+    index: usize,
+    done: bool,
+    _record: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> RecordsState<T> {
+    fn take_record(&mut self) -> ByteRecord {
+        let mut record = ByteRecord::with_capacity(self.written, self.ended);
+        let mut start = 0;
+        for &end in &self.ends[..self.ended] {
+            record.push_field(&self.fields[start..end]);
+            start = end;
+        }
+        self.written = 0;
+        self.ended = 0;
+        record
+    }
+
+    fn deserialize(&mut self, record: ByteRecord) -> Result<T, FunctionsError> {
+        let index = self.index;
+        self.index += 1;
+        let width = *self.width.get_or_insert(record.len());
+        if record.len() != width {
+            return Err(FunctionsError::DeserializeError(format!(
+                "CSV record {}: found {} fields, expected {}",
+                index,
+                record.len(),
+                width
+            )));
+        }
+        record
+            .deserialize(self.headers.as_ref())
+            .map_err(|e| FunctionsError::DeserializeError(format!("CSV record {}: {}", index, e)))
+    }
+}
+
+async fn next_record<T: DeserializeOwned>(
+    mut state: RecordsState<T>,
+) -> Option<(Result<T, FunctionsError>, RecordsState<T>)> {
+    while !state.done {
+        // An empty input tells the parser the body has ended, so only pass one once it has.
+        if state.consumed == state.chunk.len() {
+            if let Some(response) = state.response.as_mut() {
+                match response.chunk().await {
+                    Ok(Some(chunk)) => {
+                        state.chunk = chunk;
+                        state.consumed = 0;
+                        continue;
+                    }
+                    Ok(None) => state.response = None,
+                    Err(e) => {
+                        state.done = true;
+                        return Some((Err(FunctionsError::from_reqwest(e)), state));
+                    }
+                }
+            }
+        }
+
+        let (result, read, written, ended) = state.reader.read_record(
+            &state.chunk[state.consumed..],
+            &mut state.fields[state.written..],
+            &mut state.ends[state.ended..],
+        );
+        state.consumed += read;
+        state.written += written;
+        state.ended += ended;
+        match result {
+            ReadRecordResult::InputEmpty => {}
+            ReadRecordResult::OutputFull => state.fields.resize(state.fields.len() * 2, 0),
+            ReadRecordResult::OutputEndsFull => state.ends.resize(state.ends.len() * 2, 0),
+            ReadRecordResult::Record => {
+                let record = state.take_record();
+                if state.has_headers && state.headers.is_none() {
+                    state.width = Some(record.len());
+                    state.headers = Some(record);
+                    continue;
+                }
+                let result = state.deserialize(record);
+                state.done = result.is_err();
+                return Some((result, state));
+            }
+            ReadRecordResult::End => state.done = true,
+        }
+    }
+    None
+}
@@ -0,0 +1,60 @@
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::fmt;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+
+/// The system resolver, with IPv4 addresses moved ahead of IPv6 so they are tried first when
+/// `prefer_ipv4` is set. Every client resolves through it so a failed lookup surfaces as a
+/// [`ResolveError`] rather than as text in hyper's error.
+#[derive(Debug)]
+pub(crate) struct SystemResolver {
+    pub(crate) prefer_ipv4: bool,
+}
+
+impl Resolve for SystemResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        let ipv4_first = self.prefer_ipv4;
+        Box::pin(async move {
+            let mut addrs = lookup(host).await.map_err(ResolveError)?;
+            if ipv4_first {
+                prefer_ipv4(&mut addrs);
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// A host name that didn't resolve, found in a connect error's source chain by its type.
+#[derive(Debug)]
+pub(crate) struct ResolveError(io::Error);
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for ResolveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+pub(crate) fn prefer_ipv4(addrs: &mut [SocketAddr]) {
+    addrs.sort_by_key(SocketAddr::is_ipv6);
+}
+
+fn lookup_blocking(host: &str) -> io::Result<Vec<SocketAddr>> {
+    (host, 0).to_socket_addrs().map(Iterator::collect)
+}
+
+#[cfg(feature = "tokio")]
+async fn lookup(host: String) -> io::Result<Vec<SocketAddr>> {
+    tokio::task::spawn_blocking(move || lookup_blocking(&host)).await.map_err(io::Error::other)?
+}
+
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+async fn lookup(host: String) -> io::Result<Vec<SocketAddr>> {
+    async_std::task::spawn_blocking(move || lookup_blocking(&host)).await
+}
@@ -1,44 +1,683 @@
+use crate::dns;
+use crate::idle::Stalled;
+use crate::models::{RateLimitInfo, ResponseMetadata};
+use crate::retry::AttemptSummary;
+use bytes::Bytes;
+use reqwest::header::HeaderMap;
+use reqwest::{StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
 use std::fmt;
+use std::time::Duration;
 
-#[derive(Debug)]
+pub(crate) const HEADER_PREVIEW_LEN: usize = 32;
+pub(crate) const HTTP_ERROR_BODY_SNIPPET_LEN: usize = 256;
+pub(crate) const FUNCTION_NAME_PREVIEW_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectErrorKind {
+    DnsResolution,
+    ConnectionRefused,
+    ConnectionReset,
+    TlsHandshake,
+    /// Reported for a `Timeout` of kind `TimeoutKind::Connect`.
+    Timeout,
+    Other,
+}
+
+/// Which limit a `FunctionsError::Timeout` ran into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutKind {
+    /// The builder's `connect_timeout`, while opening the connection.
+    Connect,
+    /// The per-attempt timeout from the invoke options or the client.
+    Attempt,
+    /// The retry policy's `max_elapsed`, which cut the last attempt short.
+    Deadline,
+}
+
+impl fmt::Display for TimeoutKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TimeoutKind::Connect => "connect timeout",
+            TimeoutKind::Attempt => "attempt timeout",
+            TimeoutKind::Deadline => "deadline",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FunctionsError {
     FetchError(String),
-    HttpError(String),
-    RelayError(String),
+    ConnectError { kind: ConnectErrorKind, message: String },
+    HttpError { function_name: String, status: u16, body: Bytes, metadata: Box<ResponseMetadata> },
+    RelayError(FunctionsRelayError),
+    InvalidHeader { name: String, reason: String },
+    InvalidUrl(String),
+    InvalidMethod(String),
+    /// The function name can't be used in a URL: it's empty, has a control character, or has
+    /// a `/` on a client without `allow_sub_paths`. Checked before anything is sent.
+    InvalidFunctionName { name: String, reason: String },
+    BuildError(String),
+    DeserializeError(String),
+    /// A response labelled as JSON didn't parse. `body` holds its start, up to the client's
+    /// `invalid_json_body_limit`, for logging.
+    InvalidJson { error: String, body: Bytes },
+    /// A time limit ran out. `elapsed` is measured from the start of the attempt, or of the
+    /// whole invoke for a `Deadline`. `limit` is `None` when it isn't known, such as for
+    /// errors read mid-stream.
+    Timeout { kind: TimeoutKind, limit: Option<Duration>, elapsed: Duration, attempt: u32, max_attempts: u32 },
+    /// `history` has every failed attempt in order, the last one being `last_error`.
+    RetriesExhausted { attempts: u32, elapsed: Duration, last_error: Box<FunctionsError>, history: Vec<AttemptSummary> },
+    /// `invoke_blocking` was called from inside an async runtime, where blocking would stall it.
+    BlockingInAsyncContext,
+    /// `into_result` was called on a `NotModified` response, which has no body.
+    NotModified(Box<ResponseMetadata>),
+    /// `shutdown` was called on the client or one of its clones, so nothing new is sent.
+    ShuttingDown,
+    /// No body bytes arrived for the idle timeout. `received` counts the body bytes read
+    /// before the stream stalled.
+    StalledStream { idle: Duration, received: u64 },
+    /// `poll_until` ran out of time before a poll satisfied its predicate. `last` is the value
+    /// the last completed poll returned, if any.
+    PollTimeout { elapsed: Duration, polls: u32, last: Option<serde_json::Value> },
+    /// `invoke_paginated` fetched `max_pages` pages and the last one still had a cursor.
+    PageLimit { max_pages: u32 },
+    /// A body kept with `keep_raw_body` was over the client's
+    /// [`max_response_size`](crate::FunctionsClientBuilder::max_response_size).
+    ResponseTooLarge { limit: u64 },
+}
+
+impl FunctionsError {
+    pub fn connect_kind(&self) -> Option<ConnectErrorKind> {
+        match self {
+            FunctionsError::ConnectError { kind, .. } => Some(*kind),
+            FunctionsError::Timeout { kind: TimeoutKind::Connect, .. } => Some(ConnectErrorKind::Timeout),
+            FunctionsError::RetriesExhausted { last_error, .. } => last_error.connect_kind(),
+            _ => None,
+        }
+    }
+
+    /// The rate limit headers of the response behind an `HttpError`, such as a 429, see
+    /// [`ResponseMetadata::rate_limit`].
+    pub fn rate_limit(&self) -> Option<RateLimitInfo> {
+        match self {
+            FunctionsError::HttpError { metadata, .. } => metadata.rate_limit(),
+            FunctionsError::RetriesExhausted { last_error, .. } => last_error.rate_limit(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_reqwest(error: reqwest::Error) -> Self {
+        let mut source: Option<&(dyn std::error::Error + 'static)> = Some(&error);
+        while let Some(err) = source {
+            if let Some(&Stalled { idle, received }) = err.downcast_ref::<Stalled>() {
+                return FunctionsError::StalledStream { idle, received };
+            }
+            source = err.source();
+        }
+        if error.is_timeout() {
+            let kind = if error.is_connect() { TimeoutKind::Connect } else { TimeoutKind::Attempt };
+            return FunctionsError::Timeout { kind, limit: None, elapsed: Duration::ZERO, attempt: 1, max_attempts: 1 };
+        }
+        match classify_connect_error(&error) {
+            Some(kind) => FunctionsError::ConnectError { kind, message: error_chain(&error) },
+            None => FunctionsError::FetchError(error.to_string()),
+        }
+    }
+
+    pub(crate) fn invalid_header_name(name: &str) -> Self {
+        FunctionsError::InvalidHeader {
+            name: preview(name, HEADER_PREVIEW_LEN),
+            reason: "invalid header name".into(),
+        }
+    }
+
+    pub(crate) fn invalid_header_value(name: &str, value: &str) -> Self {
+        FunctionsError::InvalidHeader {
+            name: preview(name, HEADER_PREVIEW_LEN),
+            reason: format!("invalid header value \"{}\"", preview(value, HEADER_PREVIEW_LEN)),
+        }
+    }
 }
 
 impl fmt::Display for FunctionsError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             FunctionsError::FetchError(msg) => write!(f, "FetchError: {}", msg),
-            FunctionsError::HttpError(msg) => write!(f, "HttpError: {}", msg),
+            FunctionsError::ConnectError { kind, message } => write!(f, "ConnectError ({:?}): {}", kind, message),
+            FunctionsError::HttpError { function_name, status, body, .. } => {
+                write!(f, "HttpError: {} returned {}", function_name, status)?;
+                if let Some(reason) = StatusCode::from_u16(*status).ok().and_then(|s| s.canonical_reason()) {
+                    write!(f, " {}", reason)?;
+                }
+                if !body.is_empty() {
+                    write!(f, ": {}", body_snippet(body, HTTP_ERROR_BODY_SNIPPET_LEN))?;
+                }
+                Ok(())
+            }
             FunctionsError::RelayError(msg) => write!(f, "RelayError: {}", msg),
+            FunctionsError::InvalidHeader { name, reason } => write!(f, "InvalidHeader: {}: {}", name, reason),
+            FunctionsError::InvalidUrl(msg) => write!(f, "InvalidUrl: {}", msg),
+            FunctionsError::InvalidMethod(method) => write!(f, "InvalidMethod: \"{}\" is not a valid HTTP method", method),
+            FunctionsError::InvalidFunctionName { name, reason } => write!(f, "InvalidFunctionName: \"{}\": {}", name, reason),
+            FunctionsError::BuildError(msg) => write!(f, "BuildError: {}", msg),
+            FunctionsError::DeserializeError(msg) => write!(f, "DeserializeError: {}", msg),
+            FunctionsError::InvalidJson { error, body } => {
+                write!(f, "InvalidJson: {}", error)?;
+                if !body.is_empty() {
+                    write!(f, ": {}", body_snippet(body, HTTP_ERROR_BODY_SNIPPET_LEN))?;
+                }
+                Ok(())
+            }
+            FunctionsError::Timeout { kind, limit, elapsed, attempt, max_attempts } => {
+                write!(f, "Timeout: gave up after {:.2?} (", elapsed)?;
+                match limit {
+                    Some(limit) => write!(f, "{} {:?}", kind, limit)?,
+                    None => write!(f, "{}", kind)?,
+                }
+                write!(f, ", attempt {}/{})", attempt, max_attempts)
+            }
+            FunctionsError::RetriesExhausted { attempts, elapsed, last_error, .. } => {
+                write!(f, "RetriesExhausted: gave up after {} attempt(s) in {:?}: {}", attempts, elapsed, last_error)
+            }
+            FunctionsError::BlockingInAsyncContext => {
+                write!(f, "BlockingInAsyncContext: invoke_blocking cannot run inside an async runtime, use invoke")
+            }
+            FunctionsError::NotModified(metadata) => match metadata.etag() {
+                Some(etag) => write!(f, "NotModified: the function's response is unchanged (ETag {})", etag),
+                None => write!(f, "NotModified: the function's response is unchanged"),
+            },
+            FunctionsError::ShuttingDown => write!(f, "ShuttingDown: the client no longer accepts invocations"),
+            FunctionsError::StalledStream { idle, received } => {
+                write!(f, "StalledStream: {}", Stalled { idle: *idle, received: *received })
+            }
+            FunctionsError::PollTimeout { elapsed, polls, .. } => {
+                write!(f, "PollTimeout: no poll matched in {:.2?} after {} poll(s)", elapsed, polls)
+            }
+            FunctionsError::PageLimit { max_pages } => {
+                write!(f, "PageLimit: still paginating after {} page(s)", max_pages)
+            }
+            FunctionsError::ResponseTooLarge { limit } => {
+                write!(f, "ResponseTooLarge: the response body is over the {} byte limit", limit)
+            }
         }
     }
 }
 
-impl std::error::Error for FunctionsError {}
+impl FunctionsError {
+    /// A stable code for grouping errors in logs and alerts, such as `"timeout"` or
+    /// `"http_5xx"`. A code never changes meaning between releases; new ones may be added.
+    ///
+    /// Each variant has its own codes, and `HttpError` splits by status into `"http_4xx"`,
+    /// `"http_5xx"` and `"http_error"` for any other status.
+    pub fn code(&self) -> &'static str {
+        match self {
+            FunctionsError::HttpError { status: 400..=499, .. } => "http_4xx",
+            FunctionsError::HttpError { status: 500..=599, .. } => "http_5xx",
+            error => error.kind_name(),
+        }
+    }
 
-pub struct FunctionsFetchError;
+    /// The `Display` text without the response body that `HttpError` and `InvalidJson` quote,
+    /// the `message` of the JSON form. An `HttpError` names the URL it came from instead.
+    fn summary(&self) -> String {
+        match self {
+            FunctionsError::HttpError { function_name, status, metadata, .. } => {
+                let mut summary = format!("HttpError: {} returned {}", function_name, status);
+                if let Some(reason) = StatusCode::from_u16(*status).ok().and_then(|s| s.canonical_reason()) {
+                    summary.push(' ');
+                    summary.push_str(reason);
+                }
+                if let Some(ref url) = metadata.url {
+                    summary.push_str(&format!(" from {}", url));
+                }
+                summary
+            }
+            FunctionsError::InvalidJson { error, .. } => format!("InvalidJson: {}", error),
+            FunctionsError::RetriesExhausted { attempts, elapsed, last_error, .. } => {
+                format!("RetriesExhausted: gave up after {} attempt(s) in {:?}: {}", attempts, elapsed, last_error.summary())
+            }
+            error => error.to_string(),
+        }
+    }
+
+    /// The variant's name in snake case, the `kind` of its JSON form.
+    fn kind_name(&self) -> &'static str {
+        match self {
+            FunctionsError::FetchError(_) => "fetch_error",
+            FunctionsError::ConnectError { .. } => "connect_error",
+            FunctionsError::HttpError { .. } => "http_error",
+            FunctionsError::RelayError(_) => "relay_error",
+            FunctionsError::InvalidHeader { .. } => "invalid_header",
+            FunctionsError::InvalidUrl(_) => "invalid_url",
+            FunctionsError::InvalidMethod(_) => "invalid_method",
+            FunctionsError::InvalidFunctionName { .. } => "invalid_function_name",
+            FunctionsError::BuildError(_) => "build_error",
+            FunctionsError::DeserializeError(_) => "deserialize_error",
+            FunctionsError::InvalidJson { .. } => "invalid_json",
+            FunctionsError::Timeout { .. } => "timeout",
+            FunctionsError::RetriesExhausted { .. } => "retries_exhausted",
+            FunctionsError::BlockingInAsyncContext => "blocking_in_async_context",
+            FunctionsError::NotModified(_) => "not_modified",
+            FunctionsError::ShuttingDown => "shutting_down",
+            FunctionsError::StalledStream { .. } => "stalled_stream",
+            FunctionsError::PollTimeout { .. } => "poll_timeout",
+            FunctionsError::PageLimit { .. } => "page_limit",
+            FunctionsError::ResponseTooLarge { .. } => "response_too_large",
+        }
+    }
+}
+
+/// A flat object with the variant as `kind`, its [`code`](FunctionsError::code), a
+/// `message` and the variant's details, for returning errors from an API:
+/// `{"kind":"http_error","code":"http_5xx","message":"...","function":"send-email","status":502}`.
+/// Durations are in whole milliseconds. URLs in messages lose their credentials, query and
+/// fragment, and response bodies, headers and polled values are left out.
+impl Serialize for FunctionsError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("kind", self.kind_name())?;
+        map.serialize_entry("code", self.code())?;
+        map.serialize_entry("message", &redact_urls(&self.summary()))?;
+        match self {
+            FunctionsError::ConnectError { kind, .. } => map.serialize_entry("connect_kind", kind)?,
+            FunctionsError::HttpError { function_name, status, .. } => {
+                map.serialize_entry("function", function_name)?;
+                map.serialize_entry("status", status)?;
+            }
+            FunctionsError::RelayError(FunctionsRelayError { context: Some(context), .. }) => map.serialize_entry("status", &context.status)?,
+            FunctionsError::InvalidHeader { name, .. } => map.serialize_entry("header", name)?,
+            FunctionsError::InvalidFunctionName { name, .. } => map.serialize_entry("function", name)?,
+            FunctionsError::Timeout { kind, limit, elapsed, attempt, max_attempts } => {
+                map.serialize_entry("timeout_kind", kind)?;
+                map.serialize_entry("limit_ms", &limit.map(millis))?;
+                map.serialize_entry("elapsed_ms", &millis(*elapsed))?;
+                map.serialize_entry("attempt", attempt)?;
+                map.serialize_entry("max_attempts", max_attempts)?;
+            }
+            FunctionsError::RetriesExhausted { attempts, elapsed, last_error, .. } => {
+                map.serialize_entry("attempts", attempts)?;
+                map.serialize_entry("elapsed_ms", &millis(*elapsed))?;
+                map.serialize_entry("last_error", last_error)?;
+            }
+            FunctionsError::NotModified(metadata) => map.serialize_entry("etag", &metadata.etag())?,
+            FunctionsError::StalledStream { idle, received } => {
+                map.serialize_entry("idle_ms", &millis(*idle))?;
+                map.serialize_entry("received", received)?;
+            }
+            FunctionsError::PollTimeout { elapsed, polls, .. } => {
+                map.serialize_entry("elapsed_ms", &millis(*elapsed))?;
+                map.serialize_entry("polls", polls)?;
+            }
+            FunctionsError::PageLimit { max_pages } => map.serialize_entry("max_pages", max_pages)?,
+            FunctionsError::ResponseTooLarge { limit } => map.serialize_entry("limit", limit)?,
+            _ => {}
+        }
+        map.end()
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+// Drops the credentials, query and fragment of every URL in `message`, where tokens and keys
+// tend to be.
+fn redact_urls(message: &str) -> String {
+    let mut out = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(start) = [rest.find("http://"), rest.find("https://")].into_iter().flatten().min() {
+        out.push_str(&rest[..start]);
+        let end = rest[start..].find(|c: char| c.is_whitespace() || matches!(c, ')' | '"' | '\'' | '>')).map_or(rest.len(), |end| start + end);
+        // Punctuation after a URL, as in `InvalidUrl`'s "<url>: <reason>", belongs to the text.
+        let end = start + rest[start..end].trim_end_matches([':', ',', '.', ';']).len();
+        match Url::parse(&rest[start..end]) {
+            Ok(mut url) => {
+                let _ = url.set_username("");
+                let _ = url.set_password(None);
+                url.set_query(None);
+                url.set_fragment(None);
+                out.push_str(url.as_str());
+            }
+            Err(_) => out.push_str(&rest[start..end]),
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+impl std::error::Error for FunctionsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FunctionsError::RetriesExhausted { last_error, .. } => Some(last_error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+// reqwest only exposes `is_connect`, so the specific cause is found by type in the source
+// chain: our resolver's error for DNS, and native-tls's, reqwest's TLS backend here, for TLS.
+fn classify_connect_error(error: &reqwest::Error) -> Option<ConnectErrorKind> {
+    let mut io_kind = None;
+    let mut dns = false;
+    let mut tls = false;
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    while let Some(err) = source {
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            io_kind = io_kind.or(Some(io.kind()));
+        }
+        dns |= err.is::<dns::ResolveError>();
+        tls |= err.is::<native_tls::Error>();
+        source = err.source();
+    }
+
+    let reset = matches!(
+        io_kind,
+        Some(std::io::ErrorKind::ConnectionReset) | Some(std::io::ErrorKind::ConnectionAborted)
+    );
+    if !error.is_connect() {
+        return if reset { Some(ConnectErrorKind::ConnectionReset) } else { None };
+    }
+    Some(if dns {
+        ConnectErrorKind::DnsResolution
+    } else if io_kind == Some(std::io::ErrorKind::ConnectionRefused) {
+        ConnectErrorKind::ConnectionRefused
+    } else if reset {
+        ConnectErrorKind::ConnectionReset
+    } else if tls {
+        ConnectErrorKind::TlsHandshake
+    } else {
+        ConnectErrorKind::Other
+    })
+}
+
+/// Whether the connection was reset or closed before a response started, which is what a
+/// pooled connection the server has since dropped looks like.
+pub(crate) fn is_stale_connection(error: &reqwest::Error) -> bool {
+    if error.is_timeout() {
+        return false;
+    }
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    while let Some(err) = source {
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            use std::io::ErrorKind::*;
+            if matches!(io.kind(), ConnectionReset | ConnectionAborted | BrokenPipe | UnexpectedEof) {
+                return true;
+            }
+        }
+        if err.downcast_ref::<hyper::Error>().is_some_and(hyper::Error::is_incomplete_message) {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+fn error_chain(error: &reqwest::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(err) = source {
+        message.push_str(": ");
+        message.push_str(&err.to_string());
+        source = err.source();
+    }
+    message
+}
+
+// Escapes control characters and truncates to `max_chars` so the result is safe to log.
+pub(crate) fn preview(value: &str, max_chars: usize) -> String {
+    let mut out = String::new();
+    for (i, c) in value.chars().enumerate() {
+        if i == max_chars {
+            out.push_str("...");
+            break;
+        }
+        if c.is_control() {
+            out.extend(c.escape_default());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// Renders the start of a response body on a single line, or a placeholder for binary payloads.
+pub(crate) fn body_snippet(body: &[u8], max_bytes: usize) -> String {
+    let truncated = body.len() > max_bytes;
+    let mut head = &body[..body.len().min(max_bytes)];
+    if head.iter().any(|b| (*b < 0x20 && !b"\t\r\n".contains(b)) || *b == 0x7f) {
+        return format!("<{} bytes of binary data>", body.len());
+    }
+    if truncated {
+        // Don't let the cut split a multi-byte character into a replacement char.
+        if let Err(e) = std::str::from_utf8(head) {
+            if e.error_len().is_none() {
+                head = &head[..e.valid_up_to()];
+            }
+        }
+    }
+    let text = String::from_utf8_lossy(head);
+    let mut snippet = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect::<Vec<_>>().join(" ");
+    if truncated {
+        snippet.push_str("...");
+    }
+    snippet
+}
+
+/// The response behind an `HttpError` or a relay error, like the `context` supabase-js attaches.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    pub url: Option<Url>,
+}
+
+impl ErrorContext {
+    /// The body as text, with invalid UTF-8 replaced by U+FFFD.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Deserializes the JSON body, e.g. the `{ "error": ... }` a function returned with its status.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, FunctionsError> {
+        serde_json::from_slice(&self.body).map_err(|e| FunctionsError::DeserializeError(format!("invalid error body: {}", e)))
+    }
+}
+
+/// A request that never got a response: a `FetchError` or a `ConnectError`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionsFetchError {
+    pub message: String,
+    /// Set for a `ConnectError`.
+    pub connect_kind: Option<ConnectErrorKind>,
+}
 
 impl FunctionsFetchError {
-    pub fn new(context: String) -> FunctionsError {
-        FunctionsError::FetchError(context)
+    pub fn new(message: impl Into<String>) -> Self {
+        FunctionsFetchError { message: message.into(), connect_kind: None }
     }
 }
 
-pub struct FunctionsRelayError;
+impl From<FunctionsFetchError> for FunctionsError {
+    fn from(error: FunctionsFetchError) -> Self {
+        match error.connect_kind {
+            Some(kind) => FunctionsError::ConnectError { kind, message: error.message },
+            None => FunctionsError::FetchError(error.message),
+        }
+    }
+}
+
+/// Takes out a `FetchError` or `ConnectError`, giving back any other error unchanged.
+impl TryFrom<FunctionsError> for FunctionsFetchError {
+    type Error = FunctionsError;
+
+    fn try_from(error: FunctionsError) -> Result<Self, FunctionsError> {
+        match error {
+            FunctionsError::FetchError(message) => Ok(FunctionsFetchError { message, connect_kind: None }),
+            FunctionsError::ConnectError { kind, message } => Ok(FunctionsFetchError { message, connect_kind: Some(kind) }),
+            other => Err(other),
+        }
+    }
+}
+
+impl fmt::Display for FunctionsFetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for FunctionsFetchError {}
+
+/// Serializes as the `FunctionsError` it converts into.
+impl Serialize for FunctionsFetchError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        FunctionsError::from(self.clone()).serialize(serializer)
+    }
+}
+
+/// The relay in front of the function failed, which the response marks with `x-relay-error: true`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionsRelayError {
+    pub message: String,
+    /// The relay's response; `None` for an error built from a message alone.
+    pub context: Option<Box<ErrorContext>>,
+}
 
 impl FunctionsRelayError {
-    pub fn new(context: String) -> FunctionsError {
-        FunctionsError::RelayError(context)
+    pub fn new(message: impl Into<String>) -> Self {
+        FunctionsRelayError { message: message.into(), context: None }
+    }
+}
+
+impl From<String> for FunctionsRelayError {
+    fn from(message: String) -> Self {
+        Self::new(message)
     }
 }
 
-pub struct FunctionsHttpError;
+impl From<&str> for FunctionsRelayError {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<FunctionsRelayError> for FunctionsError {
+    fn from(error: FunctionsRelayError) -> Self {
+        FunctionsError::RelayError(error)
+    }
+}
+
+/// Takes out a `RelayError`, giving back any other error unchanged.
+impl TryFrom<FunctionsError> for FunctionsRelayError {
+    type Error = FunctionsError;
+
+    fn try_from(error: FunctionsError) -> Result<Self, FunctionsError> {
+        match error {
+            FunctionsError::RelayError(relay) => Ok(relay),
+            other => Err(other),
+        }
+    }
+}
+
+impl fmt::Display for FunctionsRelayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for FunctionsRelayError {}
+
+/// Serializes as the `FunctionsError` it converts into.
+impl Serialize for FunctionsRelayError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        FunctionsError::from(self.clone()).serialize(serializer)
+    }
+}
+
+/// The function answered with a status that isn't a success.
+///
+/// ```
+/// use supabase_function_rs::{FunctionsError, FunctionsHttpError};
+///
+/// let error: FunctionsError = FunctionsHttpError::new("reserve-seat", 409, r#"{"reason":"taken"}"#).into();
+/// if let Ok(http) = FunctionsHttpError::try_from(error) {
+///     let body: serde_json::Value = http.context.json().unwrap();
+///     assert_eq!((http.context.status, &body["reason"]), (409, &"taken".into()));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionsHttpError {
+    pub function_name: String,
+    pub context: ErrorContext,
+    /// The rest of the response metadata, such as its version and timing.
+    pub metadata: Box<ResponseMetadata>,
+}
 
 impl FunctionsHttpError {
-    pub fn new(context: String) -> FunctionsError {
-        FunctionsError::HttpError(context)
+    pub fn new(function_name: impl Into<String>, status: u16, body: impl Into<Bytes>) -> Self {
+        FunctionsHttpError {
+            function_name: function_name.into(),
+            context: ErrorContext { status, body: body.into(), ..Default::default() },
+            metadata: Box::default(),
+        }
+    }
+}
+
+impl From<FunctionsHttpError> for FunctionsError {
+    fn from(error: FunctionsHttpError) -> Self {
+        let mut metadata = error.metadata;
+        metadata.status = error.context.status;
+        metadata.headers = error.context.headers;
+        metadata.url = error.context.url;
+        FunctionsError::HttpError { function_name: error.function_name, status: error.context.status, body: error.context.body, metadata }
+    }
+}
+
+/// Takes out an `HttpError`, giving back any other error unchanged.
+impl TryFrom<FunctionsError> for FunctionsHttpError {
+    type Error = FunctionsError;
+
+    fn try_from(error: FunctionsError) -> Result<Self, FunctionsError> {
+        match error {
+            FunctionsError::HttpError { function_name, status, body, metadata } => Ok(FunctionsHttpError {
+                function_name,
+                context: ErrorContext { status, headers: metadata.headers.clone(), body, url: metadata.url.clone() },
+                metadata,
+            }),
+            other => Err(other),
+        }
+    }
+}
+
+impl fmt::Display for FunctionsHttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&FunctionsError::from(self.clone()), f)
+    }
+}
+
+impl std::error::Error for FunctionsHttpError {}
+
+/// Serializes as the `FunctionsError` it converts into.
+impl Serialize for FunctionsHttpError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        FunctionsError::from(self.clone()).serialize(serializer)
     }
 }
@@ -0,0 +1,201 @@
+use crate::errors::{ConnectErrorKind, FunctionsError, TimeoutKind};
+use crate::progress;
+use crate::retry::Sleeper;
+use bytes::Bytes;
+use futures_util::stream::{self, StreamExt};
+use reqwest::{Response, ResponseBuilderExt, Url};
+use std::future::Future;
+use std::io;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// One way to make an invoke misbehave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Waits this long on the client's sleeper before sending. Adds up across rules, and
+    /// counts against the attempt's timeout.
+    Latency(Duration),
+    /// Fails with a `ConnectError` of this kind without sending anything.
+    ConnectError(ConnectErrorKind),
+    /// Answers with this status and an empty body without sending anything.
+    Status(u16),
+    /// Sends the request, then cuts the response body off after this many bytes as if the
+    /// connection dropped.
+    Truncate(usize),
+}
+
+/// When a [`FaultRule`] fires, counted over the calls it applies to.
+#[derive(Debug, Clone, PartialEq)]
+enum Trigger {
+    Always,
+    Sequence(Vec<bool>),
+    Probability(f64),
+}
+
+/// A [`Fault`] and the calls it applies to: by default every call to every function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultRule {
+    fault: Fault,
+    function: Option<String>,
+    trigger: Trigger,
+}
+
+impl FaultRule {
+    pub fn new(fault: Fault) -> Self {
+        FaultRule { fault, function: None, trigger: Trigger::Always }
+    }
+
+    /// Only applies to calls to `function_name`.
+    pub fn function(mut self, function_name: impl Into<String>) -> Self {
+        self.function = Some(function_name.into());
+        self
+    }
+
+    /// Only fires on the first `calls` calls.
+    pub fn first(self, calls: usize) -> Self {
+        self.sequence(vec![true; calls])
+    }
+
+    /// Fires on the calls whose entry is `true`, in order. Calls past the end of `pattern`
+    /// are left alone.
+    pub fn sequence(mut self, pattern: impl IntoIterator<Item = bool>) -> Self {
+        self.trigger = Trigger::Sequence(pattern.into_iter().collect());
+        self
+    }
+
+    /// Fires on each call with `probability`, from 0.0 to 1.0.
+    pub fn probability(mut self, probability: f64) -> Self {
+        self.trigger = Trigger::Probability(probability.clamp(0.0, 1.0));
+        self
+    }
+}
+
+/// Injects latency, connection errors, error statuses and truncated bodies into a client's
+/// invokes for resilience tests, see [`FaultRule`]. Faults apply to each attempt, including
+/// retries, and fail through the same errors a real network would, so retry policies and
+/// `error_for_status` react as they would in production.
+///
+/// Rules are checked in order on every attempt. Latency from every rule that fires is
+/// added up; of the other faults, the first one that fires wins.
+#[derive(Debug)]
+pub struct FaultInjector {
+    rules: Vec<FaultRule>,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    calls: Vec<usize>,
+    rng: fastrand::Rng,
+}
+
+impl FaultInjector {
+    pub fn new() -> Self {
+        FaultInjector { rules: Vec::new(), state: Mutex::new(State { calls: Vec::new(), rng: fastrand::Rng::new() }) }
+    }
+
+    pub fn rule(mut self, rule: FaultRule) -> Self {
+        self.rules.push(rule);
+        self.state.get_mut().unwrap().calls.push(0);
+        self
+    }
+
+    /// Seeds the random draws of `probability` rules so a run can be repeated.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.state.get_mut().unwrap().rng = fastrand::Rng::with_seed(seed);
+        self
+    }
+
+    /// Picks the faults for one attempt, waits out their latency and returns the fault left
+    /// to apply to the request, if any.
+    pub(crate) async fn before_send(
+        &self,
+        function_name: &str,
+        timeout: Option<Duration>,
+        sleeper: &dyn Sleeper,
+    ) -> Result<Option<Fault>, FunctionsError> {
+        let (latency, fault) = self.pick(function_name);
+        if latency > Duration::ZERO {
+            match timeout {
+                Some(timeout) if latency >= timeout => {
+                    sleeper.sleep(timeout).await;
+                    return Err(FunctionsError::Timeout { kind: TimeoutKind::Attempt, limit: None, elapsed: Duration::ZERO, attempt: 1, max_attempts: 1 });
+                }
+                _ => sleeper.sleep(latency).await,
+            }
+        }
+        Ok(fault)
+    }
+
+    fn pick(&self, function_name: &str) -> (Duration, Option<Fault>) {
+        let mut state = self.state.lock().unwrap();
+        let State { calls, rng } = &mut *state;
+        let mut latency = Duration::ZERO;
+        let mut picked = None;
+        for (rule, calls) in self.rules.iter().zip(calls.iter_mut()) {
+            if rule.function.as_deref().is_some_and(|name| name != function_name) {
+                continue;
+            }
+            let call = *calls;
+            *calls += 1;
+            let fires = match rule.trigger {
+                Trigger::Always => true,
+                Trigger::Sequence(ref pattern) => pattern.get(call).copied().unwrap_or(false),
+                Trigger::Probability(probability) => rng.f64() < probability,
+            };
+            match rule.fault {
+                Fault::Latency(delay) if fires => latency += delay,
+                fault if fires && picked.is_none() => picked = Some(fault),
+                _ => {}
+            }
+        }
+        (latency, picked)
+    }
+}
+
+impl Default for FaultInjector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Runs `execute`, the sending of the request to `url`, with `fault` applied to it.
+pub(crate) async fn apply(
+    fault: Option<Fault>,
+    url: Url,
+    execute: impl Future<Output = Result<Response, FunctionsError>>,
+) -> Result<Response, FunctionsError> {
+    match fault {
+        None | Some(Fault::Latency(_)) => execute.await,
+        Some(Fault::ConnectError(kind)) => Err(FunctionsError::ConnectError { kind, message: format!("injected fault sending to {}", url) }),
+        Some(Fault::Status(status)) => {
+            let response = http::Response::builder()
+                .status(status)
+                .url(url)
+                .header("content-length", "0")
+                .body(Bytes::new())
+                .map_err(|e| FunctionsError::BuildError(format!("invalid injected status {}: {}", status, e)))?;
+            Ok(Response::from(response))
+        }
+        Some(Fault::Truncate(limit)) => progress::map_body(execute.await?, move |chunks| {
+            stream::unfold(Some((chunks, Some(limit))), move |state| async move {
+                let (mut chunks, left) = state?;
+                let Some(left) = left else { return Some((Err(cut_off(limit)), None)) };
+                match chunks.next().await {
+                    Some(Ok(chunk)) if chunk.len() <= left => {
+                        let left = left - chunk.len();
+                        Some((Ok(chunk), Some((chunks, Some(left)))))
+                    },
+                    Some(Ok(chunk)) if left > 0 => Some((Ok(chunk.slice(..left)), Some((chunks, None)))),
+                    Some(Ok(_)) => Some((Err(cut_off(limit)), None)),
+                    Some(Err(error)) => Some((Err(error.into()), None)),
+                    None => None,
+                }
+            })
+        }),
+    }
+}
+
+fn cut_off(limit: usize) -> Box<dyn std::error::Error + Send + Sync> {
+    io::Error::new(io::ErrorKind::UnexpectedEof, format!("injected fault: body cut off after {} bytes", limit)).into()
+}
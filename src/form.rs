@@ -0,0 +1,240 @@
+use crate::errors::FunctionsError;
+use crate::models::{shell_quote, FunctionInvokeOptions, InvokeBody};
+use reqwest::multipart::{Form, Part};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+
+impl FunctionInvokeOptions {
+    /// A `POST` with a `multipart/form-data` body.
+    ///
+    /// ```
+    /// use supabase_function_rs::{FunctionInvokeOptions, InvokeBody};
+    ///
+    /// let options = FunctionInvokeOptions::form([("field1", "value1"), ("field2", "value2")]);
+    /// assert!(matches!(options.body, Some(InvokeBody::FormData(ref form)) if form.get("field2") == Some(&"value2".into())));
+    /// ```
+    ///
+    /// Values of mixed types go through [`FormValue`]:
+    ///
+    /// ```
+    /// use supabase_function_rs::{FormValue, FunctionInvokeOptions};
+    ///
+    /// let options = FunctionInvokeOptions::form([
+    ///     ("title", FormValue::from("Q3 report")),
+    ///     ("pages", FormValue::from(12)),
+    ///     ("draft", FormValue::from(false)),
+    ///     ("file", FormValue::file(b"%PDF".to_vec(), "q3.pdf", "application/pdf")),
+    /// ]);
+    /// ```
+    pub fn form<K: Into<String>, V: Into<FormValue>>(fields: impl IntoIterator<Item = (K, V)>) -> Self {
+        Self::with_body(InvokeBody::FormData(fields.into_iter().collect()))
+    }
+}
+
+/// The value of one multipart form field. Numbers and booleans are sent as their plain text,
+/// e.g. `42` and `true`; `Bytes` is sent as a file part.
+///
+/// Serializes untagged, so a form of only `Text` values reads and writes as a map of strings.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FormValue {
+    Text(String),
+    Number(serde_json::Number),
+    Bool(bool),
+    Bytes {
+        data: Vec<u8>,
+        filename: Option<String>,
+        content_type: Option<String>,
+    },
+}
+
+impl FormValue {
+    /// A file part named `filename`, sent with `content_type`.
+    pub fn file(data: impl Into<Vec<u8>>, filename: impl Into<String>, content_type: impl Into<String>) -> Self {
+        FormValue::Bytes { data: data.into(), filename: Some(filename.into()), content_type: Some(content_type.into()) }
+    }
+
+    /// The text a non-file field is sent as.
+    pub fn as_text(&self) -> Option<String> {
+        match self {
+            FormValue::Text(text) => Some(text.clone()),
+            FormValue::Number(number) => Some(number.to_string()),
+            FormValue::Bool(value) => Some(value.to_string()),
+            FormValue::Bytes { .. } => None,
+        }
+    }
+}
+
+impl From<String> for FormValue {
+    fn from(text: String) -> Self {
+        FormValue::Text(text)
+    }
+}
+
+impl From<&str> for FormValue {
+    fn from(text: &str) -> Self {
+        FormValue::Text(text.to_string())
+    }
+}
+
+impl From<bool> for FormValue {
+    fn from(value: bool) -> Self {
+        FormValue::Bool(value)
+    }
+}
+
+macro_rules! form_value_from_integer {
+    ($($integer:ty),*) => {
+        $(impl From<$integer> for FormValue {
+            fn from(number: $integer) -> Self {
+                FormValue::Number(number.into())
+            }
+        })*
+    };
+}
+
+form_value_from_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// NaN and the infinities have no JSON number, so they are sent as text.
+impl From<f64> for FormValue {
+    fn from(number: f64) -> Self {
+        serde_json::Number::from_f64(number).map_or_else(|| FormValue::Text(number.to_string()), FormValue::Number)
+    }
+}
+
+/// The fields of a form body in the order they are sent. A name may repeat, as in
+/// `tags=a&tags=b`.
+///
+/// ```
+/// use supabase_function_rs::{FormFields, FormValue};
+///
+/// let mut fields = FormFields::new();
+/// fields.append("tags", "a");
+/// fields.append("tags", "b");
+/// assert_eq!(fields.get_all("tags").collect::<Vec<_>>(), [&FormValue::from("a"), &FormValue::from("b")]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FormFields(Vec<(String, FormValue)>);
+
+impl FormFields {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a field after the others, keeping any earlier ones with the same name.
+    pub fn append(&mut self, name: impl Into<String>, value: impl Into<FormValue>) {
+        self.0.push((name.into(), value.into()));
+    }
+
+    /// The first value of the field `name`.
+    pub fn get(&self, name: &str) -> Option<&FormValue> {
+        self.0.iter().find(|(field, _)| field == name).map(|(_, value)| value)
+    }
+
+    /// Every value of the field `name`, in order.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a FormValue> + 'a {
+        self.0.iter().filter(move |(field, _)| field == name).map(|(_, value)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FormValue)> {
+        self.0.iter().map(|(name, value)| (name.as_str(), value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<K: Into<String>, V: Into<FormValue>> FromIterator<(K, V)> for FormFields {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(fields: I) -> Self {
+        FormFields(fields.into_iter().map(|(name, value)| (name.into(), value.into())).collect())
+    }
+}
+
+/// A map has no order of its own, so its fields are sent sorted by name.
+impl<V: Into<FormValue>> From<HashMap<String, V>> for FormFields {
+    fn from(fields: HashMap<String, V>) -> Self {
+        let mut fields: Vec<_> = fields.into_iter().map(|(name, value)| (name, value.into())).collect();
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+        FormFields(fields)
+    }
+}
+
+impl IntoIterator for FormFields {
+    type Item = (String, FormValue);
+    type IntoIter = std::vec::IntoIter<(String, FormValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// Serializes as a list of `[name, value]` pairs; a map of fields deserializes too.
+impl Serialize for FormFields {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FormFields {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Fields {
+            Pairs(Vec<(String, FormValue)>),
+            Map(HashMap<String, FormValue>),
+        }
+        Ok(match Fields::deserialize(deserializer)? {
+            Fields::Pairs(pairs) => FormFields(pairs),
+            Fields::Map(map) => FormFields::from(map),
+        })
+    }
+}
+
+/// The multipart form a `FormData` body is sent as: one text part per value, and a file part per `Bytes`.
+pub(crate) fn multipart(fields: &FormFields) -> Result<Form, FunctionsError> {
+    fields.iter().try_fold(Form::new(), |form, (name, value)| {
+        let part = match value {
+            FormValue::Bytes { data, filename, content_type } => {
+                let part = Part::bytes(data.clone());
+                let part = match filename {
+                    Some(filename) => part.file_name(filename.clone()),
+                    None => part,
+                };
+                match content_type {
+                    Some(content_type) => part.mime_str(content_type).map_err(|_| {
+                        FunctionsError::BuildError(format!("invalid content type {:?} for form field {}", content_type, name))
+                    })?,
+                    None => part,
+                }
+            }
+            value => Part::text(value.as_text().unwrap_or_default()),
+        };
+        Ok(form.part(name.to_string(), part))
+    })
+}
+
+/// Appends the `--form` arguments that send `fields`. Values use `--form-string`, so ones
+/// starting with `@` or `<` aren't read as files; file fields are attached from a local file of
+/// their filename.
+pub(crate) fn curl_args(fields: &FormFields, command: &mut String) {
+    for (name, value) in fields.iter() {
+        match value {
+            FormValue::Bytes { filename, content_type, .. } => {
+                let mut field = format!("{}=@{}", name, filename.as_deref().unwrap_or(name));
+                if let Some(content_type) = content_type {
+                    field.push_str(&format!(";type={}", content_type));
+                }
+                command.push_str(&format!(" --form {}", shell_quote(&field)));
+            }
+            value => {
+                let text = value.as_text().unwrap_or_default();
+                command.push_str(&format!(" --form-string {}", shell_quote(&format!("{}={}", name, text))));
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::errors::FunctionsError;
+use crate::progress;
+use crate::retry::Sleeper;
+use futures_util::future::{self, Either};
+use futures_util::stream::{self, StreamExt};
+use reqwest::Response;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Ends a body that went `idle` without a byte, carried through reqwest as the source of its
+/// body error so [`FunctionsError::from_reqwest`] can turn it back into `StalledStream`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Stalled {
+    pub(crate) idle: Duration,
+    pub(crate) received: u64,
+}
+
+impl fmt::Display for Stalled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no body bytes for {:?} after {} bytes", self.idle, self.received)
+    }
+}
+
+impl Error for Stalled {}
+
+/// Rebuilds `response` around a body that fails once no bytes arrive for `idle`.
+pub(crate) fn watch(response: Response, idle: Duration, sleeper: Arc<dyn Sleeper>) -> Result<Response, FunctionsError> {
+    progress::map_body(response, move |chunks| {
+        stream::unfold(Some((chunks, 0u64)), move |state| {
+            let sleeper = sleeper.clone();
+            async move {
+                let (mut chunks, received) = state?;
+                match future::select(chunks.next(), sleeper.sleep(idle)).await {
+                    Either::Left((Some(Ok(chunk)), _)) => {
+                        let received = received + chunk.len() as u64;
+                        Some((Ok(chunk), Some((chunks, received))))
+                    }
+                    Either::Left((Some(Err(error)), _)) => Some((Err(error.into()), None)),
+                    Either::Left((None, _)) => None,
+                    Either::Right(_) => Some((Err(Stalled { idle, received }.into()), None)),
+                }
+            }
+        })
+    })
+}
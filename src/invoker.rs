@@ -0,0 +1,118 @@
+use crate::client::{self, FunctionsClient, INVALID_JSON_BODY_LIMIT};
+use crate::errors::FunctionsError;
+use crate::models::{FunctionInvokeOptions, FunctionsResponse};
+use bytes::Bytes;
+use futures_util::Stream;
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Invokes functions, so callers can hold any invoker as a `Box<dyn FunctionsInvoker>`, e.g. a
+/// `FunctionsClient` in production and a fake in tests. Typed helpers such as `invoke_for` are
+/// on [`FunctionsInvokerExt`], which every invoker gets.
+pub trait FunctionsInvoker: Send + Sync {
+    fn invoke<'a>(
+        &'a self,
+        function_name: &'a str,
+        options: Option<FunctionInvokeOptions>,
+    ) -> Pin<Box<dyn Future<Output = Result<FunctionsResponse, FunctionsError>> + Send + 'a>>;
+
+    /// The response body chunk by chunk, as [`FunctionsClient::invoke_stream`] yields it.
+    fn invoke_stream(
+        &self,
+        function_name: &str,
+        options: Option<FunctionInvokeOptions>,
+    ) -> Pin<Box<dyn Stream<Item = Result<Bytes, FunctionsError>> + Send + 'static>>;
+
+    /// How much of a body that isn't valid JSON `invoke_for` keeps in `InvalidJson`: the
+    /// client's [`invalid_json_body_limit`](FunctionsClient::invalid_json_body_limit), 64 KiB
+    /// unless the invoker says otherwise.
+    fn invalid_json_body_limit(&self) -> usize {
+        INVALID_JSON_BODY_LIMIT
+    }
+}
+
+impl FunctionsInvoker for FunctionsClient {
+    fn invoke<'a>(
+        &'a self,
+        function_name: &'a str,
+        options: Option<FunctionInvokeOptions>,
+    ) -> Pin<Box<dyn Future<Output = Result<FunctionsResponse, FunctionsError>> + Send + 'a>> {
+        Box::pin(FunctionsClient::invoke(self, function_name, options))
+    }
+
+    fn invoke_stream(
+        &self,
+        function_name: &str,
+        options: Option<FunctionInvokeOptions>,
+    ) -> Pin<Box<dyn Stream<Item = Result<Bytes, FunctionsError>> + Send + 'static>> {
+        Box::pin(FunctionsClient::invoke_stream(self, function_name, options))
+    }
+
+    fn invalid_json_body_limit(&self) -> usize {
+        FunctionsClient::invalid_json_body_limit(self)
+    }
+}
+
+impl FunctionsInvoker for Arc<FunctionsClient> {
+    fn invoke<'a>(
+        &'a self,
+        function_name: &'a str,
+        options: Option<FunctionInvokeOptions>,
+    ) -> Pin<Box<dyn Future<Output = Result<FunctionsResponse, FunctionsError>> + Send + 'a>> {
+        FunctionsInvoker::invoke(self.as_ref(), function_name, options)
+    }
+
+    fn invoke_stream(
+        &self,
+        function_name: &str,
+        options: Option<FunctionInvokeOptions>,
+    ) -> Pin<Box<dyn Stream<Item = Result<Bytes, FunctionsError>> + Send + 'static>> {
+        FunctionsInvoker::invoke_stream(self.as_ref(), function_name, options)
+    }
+
+    fn invalid_json_body_limit(&self) -> usize {
+        FunctionsInvoker::invalid_json_body_limit(self.as_ref())
+    }
+}
+
+impl FunctionsInvoker for &FunctionsClient {
+    fn invoke<'a>(
+        &'a self,
+        function_name: &'a str,
+        options: Option<FunctionInvokeOptions>,
+    ) -> Pin<Box<dyn Future<Output = Result<FunctionsResponse, FunctionsError>> + Send + 'a>> {
+        FunctionsInvoker::invoke(*self, function_name, options)
+    }
+
+    fn invoke_stream(
+        &self,
+        function_name: &str,
+        options: Option<FunctionInvokeOptions>,
+    ) -> Pin<Box<dyn Stream<Item = Result<Bytes, FunctionsError>> + Send + 'static>> {
+        FunctionsInvoker::invoke_stream(*self, function_name, options)
+    }
+
+    fn invalid_json_body_limit(&self) -> usize {
+        FunctionsInvoker::invalid_json_body_limit(*self)
+    }
+}
+
+/// Typed helpers for any [`FunctionsInvoker`], including `dyn FunctionsInvoker`.
+pub trait FunctionsInvokerExt: FunctionsInvoker {
+    /// Invokes a function and deserializes its body as JSON into `T`, as
+    /// [`FunctionsClient::invoke_for`] does. `InvalidJson` keeps as much of the body as
+    /// [`invalid_json_body_limit`](FunctionsInvoker::invalid_json_body_limit) says.
+    fn invoke_for<'a, T: DeserializeOwned>(
+        &'a self,
+        function_name: &'a str,
+        options: Option<FunctionInvokeOptions>,
+    ) -> impl Future<Output = Result<T, FunctionsError>> + Send + 'a {
+        let limit = self.invalid_json_body_limit();
+        let response = self.invoke(function_name, Some(client::invoke_for_options(options)));
+        async move { client::deserialize_body(response.await?.into_result()?, limit) }
+    }
+}
+
+impl<I: FunctionsInvoker + ?Sized> FunctionsInvokerExt for I {}
@@ -0,0 +1,16 @@
+use serde::de::DeserializeOwned;
+
+// simd-json only has SIMD parsers for these architectures; elsewhere serde_json is as fast.
+#[cfg(all(feature = "simd-json", any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+pub(crate) fn from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T, String> {
+    // simd-json parses in place, so it gets a copy of the body. Anything it rejects goes
+    // through serde_json again, for identical error messages and for types such as
+    // `RawValue` that only serde_json can produce.
+    let mut buffer = body.to_vec();
+    simd_json::serde::from_slice(&mut buffer).or_else(|_| serde_json::from_slice(body).map_err(|e| e.to_string()))
+}
+
+#[cfg(not(all(feature = "simd-json", any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))))]
+pub(crate) fn from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T, String> {
+    serde_json::from_slice(body).map_err(|e| e.to_string())
+}
@@ -0,0 +1,191 @@
+use crate::errors::FunctionsError;
+use crate::json;
+use futures_util::stream::{self, Stream};
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+
+/// Yields the elements of a top-level JSON array body as they complete, holding at most one
+/// partial element in memory.
+pub(crate) fn elements<T: DeserializeOwned>(response: Response) -> impl Stream<Item = Result<T, FunctionsError>> {
+    let state = ElementsState {
+        response: Some(response),
+        parser: JsonArrayParser::default(),
+        pending: VecDeque::new(),
+        error: None,
+        index: 0,
+        _element: PhantomData,
+    };
+    stream::unfold(state, next_element)
+}
+
+struct ElementsState<T> {
+    response: Option<Response>,
+    parser: JsonArrayParser,
+    pending: VecDeque<Vec<u8>>,
+    /// Ends the stream once the elements completed before it have been yielded.
+    error: Option<FunctionsError>,
+    index: usize,
+    _element: PhantomData<fn() -> T>,
+}
+
+async fn next_element<T: DeserializeOwned>(
+    mut state: ElementsState<T>,
+) -> Option<(Result<T, FunctionsError>, ElementsState<T>)> {
+    loop {
+        if let Some(element) = state.pending.pop_front() {
+            let index = state.index;
+            state.index += 1;
+            let result = json::from_slice(&element).map_err(|e| {
+                FunctionsError::DeserializeError(format!("JSON array element {}: {}", index, e))
+            });
+            if result.is_err() {
+                state.response = None;
+                state.pending.clear();
+                state.error = None;
+            }
+            return Some((result, state));
+        }
+        if let Some(error) = state.error.take() {
+            return Some((Err(error), state));
+        }
+        let response = state.response.as_mut()?;
+        let fed = match response.chunk().await {
+            Ok(Some(chunk)) => state.parser.feed(&chunk),
+            Ok(None) => {
+                state.response = None;
+                state.parser.finish().map(|()| Vec::new())
+            }
+            Err(e) => Err(FunctionsError::from_reqwest(e)),
+        };
+        match fed {
+            Ok(elements) => state.pending.extend(elements),
+            Err(error) => {
+                state.response = None;
+                state.error = Some(error);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Position {
+    #[default]
+    BeforeArray,
+    /// After `[` or `,`; `first` is whether this would be the first element.
+    BeforeElement { first: bool },
+    InElement,
+    AfterArray,
+}
+
+/// Splits a top-level JSON array into the raw bytes of its elements. Elements are only
+/// delimited here, `serde_json` validates them.
+#[derive(Debug, Default)]
+pub(crate) struct JsonArrayParser {
+    buffer: Vec<u8>,
+    scanned: usize,
+    position: Position,
+    element_start: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl JsonArrayParser {
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Result<Vec<Vec<u8>>, FunctionsError> {
+        self.buffer.extend_from_slice(chunk);
+        let mut elements = Vec::new();
+        while self.scanned < self.buffer.len() {
+            let i = self.scanned;
+            let byte = self.buffer[i];
+            self.scanned += 1;
+            match self.position {
+                Position::BeforeArray => match byte {
+                    b'[' => self.position = Position::BeforeElement { first: true },
+                    _ if byte.is_ascii_whitespace() => {}
+                    _ => return Err(not_an_array(byte)),
+                },
+                Position::BeforeElement { first } => match byte {
+                    b']' if first => self.position = Position::AfterArray,
+                    b']' | b',' => return Err(malformed("missing element")),
+                    _ if byte.is_ascii_whitespace() => {}
+                    _ => {
+                        self.position = Position::InElement;
+                        self.element_start = i;
+                        self.scan_element_byte(byte)?;
+                    }
+                },
+                Position::InElement => {
+                    if let Some(closes_array) = self.scan_element_byte(byte)? {
+                        elements.push(self.buffer[self.element_start..i].trim_ascii_end().to_vec());
+                        self.position = match closes_array {
+                            true => Position::AfterArray,
+                            false => Position::BeforeElement { first: false },
+                        };
+                    }
+                }
+                Position::AfterArray if byte.is_ascii_whitespace() => {}
+                Position::AfterArray => return Err(malformed("unexpected data after the closing `]`")),
+            }
+        }
+
+        // Only the element being read needs to stay buffered.
+        let keep_from = match self.position {
+            Position::InElement => self.element_start,
+            _ => self.buffer.len(),
+        };
+        self.buffer.drain(..keep_from);
+        self.scanned -= keep_from;
+        self.element_start = 0;
+        Ok(elements)
+    }
+
+    /// Checks that the body ended after the array was closed.
+    pub(crate) fn finish(&self) -> Result<(), FunctionsError> {
+        match self.position {
+            Position::AfterArray => Ok(()),
+            Position::BeforeArray => Err(malformed("expected a JSON array, got an empty body")),
+            _ => Err(malformed("the body ended before the closing `]`")),
+        }
+    }
+
+    // Some(true) when `byte` closes the array, Some(false) when it separates the next element.
+    fn scan_element_byte(&mut self, byte: u8) -> Result<Option<bool>, FunctionsError> {
+        if self.in_string {
+            match byte {
+                _ if self.escaped => self.escaped = false,
+                b'\\' => self.escaped = true,
+                b'"' => self.in_string = false,
+                _ => {}
+            }
+            return Ok(None);
+        }
+        match byte {
+            b'"' => self.in_string = true,
+            b'[' | b'{' => self.depth += 1,
+            b']' if self.depth == 0 => return Ok(Some(true)),
+            b',' if self.depth == 0 => return Ok(Some(false)),
+            b'}' if self.depth == 0 => return Err(malformed("unbalanced `}`")),
+            b']' | b'}' => self.depth -= 1,
+            _ => {}
+        }
+        Ok(None)
+    }
+}
+
+fn not_an_array(found: u8) -> FunctionsError {
+    let found = match found {
+        b'{' => "an object".to_string(),
+        b'"' => "a string".to_string(),
+        b'-' | b'0'..=b'9' => "a number".to_string(),
+        b't' | b'f' => "a boolean".to_string(),
+        b'n' => "null".to_string(),
+        other => format!("{:?}", other as char),
+    };
+    FunctionsError::DeserializeError(format!("expected a top-level JSON array, found {}", found))
+}
+
+fn malformed(problem: &str) -> FunctionsError {
+    FunctionsError::DeserializeError(format!("malformed JSON array: {}", problem))
+}
@@ -1,7 +1,82 @@
+pub mod auth;
+mod base64;
+pub mod cache;
+#[cfg(feature = "record-replay")]
+pub mod cassette;
+#[cfg(feature = "cbor")]
+mod cbor;
 pub mod client;
+mod coalesce;
+#[cfg(feature = "csv")]
+mod csv_records;
+mod dns;
 pub mod errors;
+#[cfg(feature = "test-util")]
+mod fault;
+#[cfg(feature = "multipart")]
+mod form;
+mod idle;
+mod invoker;
+mod json;
+mod json_array;
 pub mod models;
+pub mod multipart;
+mod paginate;
+mod poll;
+mod progress;
+mod query;
+#[cfg(feature = "prost")]
+mod protobuf;
+pub mod redirect;
+pub mod retry;
+#[cfg(feature = "tokio")]
+mod schedule;
+pub mod signing;
+mod shutdown;
+pub mod sse;
+mod status;
+#[cfg(feature = "test-util")]
+mod test_util;
+#[cfg(feature = "opentelemetry")]
+mod trace;
+#[cfg(feature = "websocket")]
+mod websocket;
+#[cfg(feature = "xml")]
+mod xml;
 
-pub use client::FunctionsClient;
-pub use errors::{FunctionsError, FunctionsFetchError, FunctionsHttpError, FunctionsRelayError};
-pub use models::{FunctionInvokeOptions, FunctionRegion, FunctionsResponse, InvokeBody, HttpMethod, ResponseData};
+pub use auth::{Secret, TokenProvider};
+pub use cache::CachePolicy;
+#[cfg(feature = "record-replay")]
+pub use cassette::Cassette;
+pub use client::{FunctionsClient, FunctionsClientBuilder, LOCAL_ANON_KEY};
+#[cfg(feature = "csv")]
+pub use csv_records::CsvOptions;
+#[cfg(feature = "test-util")]
+pub use fault::{Fault, FaultInjector, FaultRule};
+pub use errors::{ConnectErrorKind, ErrorContext, FunctionsError, FunctionsFetchError, FunctionsHttpError, FunctionsRelayError, TimeoutKind};
+pub use invoker::{FunctionsInvoker, FunctionsInvokerExt};
+pub use multipart::{Part, Parts};
+#[cfg(feature = "multipart")]
+pub use form::{FormFields, FormValue};
+pub use models::{FunctionInvokeOptions, FunctionRegion, FunctionsResponse, GatewayInfo, HeaderMode, InvokeBody, HttpMethod, PreparedRequest, RateLimitInfo, ResponseData, ResponseMetadata};
+pub use retry::{AttemptFailure, AttemptOutcome, AttemptSummary, RetryDecision, RetryEvent, RetryPolicy, Sleeper, RETRY_PEEK_LIMIT};
+#[cfg(feature = "tokio")]
+pub use retry::TokioSleeper;
+#[cfg(feature = "async-std")]
+pub use retry::AsyncStdSleeper;
+pub use paginate::Pagination;
+pub use poll::PollInterval;
+pub use progress::Progress;
+pub use query::ArrayStyle;
+pub use redirect::{RedirectAttempt, RedirectPolicy};
+#[cfg(feature = "tokio")]
+pub use schedule::ScheduledInvocation;
+pub use shutdown::ShutdownReport;
+pub use signing::SignableRequest;
+pub use sse::{SseEvent, SseReconnect, SseStream, StreamTextOptions};
+pub use status::SuccessStatus;
+#[cfg(feature = "test-util")]
+pub use test_util::MockSleeper;
+#[cfg(feature = "websocket")]
+pub use websocket::{WsMessage, WsStream};
+pub use reqwest::{Certificate, Method, StatusCode, Version};
@@ -1,13 +1,25 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::fmt::Display;
-use crate::errors::FunctionsError;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::errors::{preview, FunctionsError, HEADER_PREVIEW_LEN};
+#[cfg(feature = "multipart")]
+use crate::form;
+#[cfg(feature = "multipart")]
+pub use crate::form::{FormFields, FormValue};
+use crate::multipart::Parts;
+use crate::progress::Progress;
+use crate::query::ArrayStyle;
+use crate::status::SuccessStatus;
 use bytes::Bytes;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, PROXY_AUTHORIZATION};
+use reqwest::{Method, Url, Version};
 use serde::de::{self, Visitor, MapAccess};
 use serde::{Deserialize, Serialize, Serializer, Deserializer};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum FunctionRegion {
+    #[default]
     Any,
     ApNortheast1,
     ApNortheast2,
@@ -25,76 +37,407 @@ pub enum FunctionRegion {
     UsWest2,
 }
 
+impl FunctionRegion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FunctionRegion::Any => "any",
+            FunctionRegion::ApNortheast1 => "ap-northeast-1",
+            FunctionRegion::ApNortheast2 => "ap-northeast-2",
+            FunctionRegion::ApSouth1 => "ap-south-1",
+            FunctionRegion::ApSoutheast1 => "ap-southeast-1",
+            FunctionRegion::ApSoutheast2 => "ap-southeast-2",
+            FunctionRegion::CaCentral1 => "ca-central-1",
+            FunctionRegion::EuCentral1 => "eu-central-1",
+            FunctionRegion::EuWest1 => "eu-west-1",
+            FunctionRegion::EuWest2 => "eu-west-2",
+            FunctionRegion::EuWest3 => "eu-west-3",
+            FunctionRegion::SaEast1 => "sa-east-1",
+            FunctionRegion::UsEast1 => "us-east-1",
+            FunctionRegion::UsWest1 => "us-west-1",
+            FunctionRegion::UsWest2 => "us-west-2",
+        }
+    }
+}
+
 impl Display for FunctionRegion {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let str = match self {
-            FunctionRegion::Any => "any".to_string(),
-            FunctionRegion::ApNortheast1 => "ap-northeast-1".to_string(),
-            FunctionRegion::ApNortheast2 => "ap-northeast-2".to_string(),
-            FunctionRegion::ApSouth1 => "ap-south-1".to_string(),
-            FunctionRegion::ApSoutheast1 => "ap-southeast-1".to_string(),
-            FunctionRegion::ApSoutheast2 => "ap-southeast-2".to_string(),
-            FunctionRegion::CaCentral1 => "ca-central-1".to_string(),
-            FunctionRegion::EuCentral1 => "eu-central-1".to_string(),
-            FunctionRegion::EuWest1 => "eu-west-1".to_string(),
-            FunctionRegion::EuWest2 => "eu-west-2".to_string(),
-            FunctionRegion::EuWest3 => "eu-west-3".to_string(),
-            FunctionRegion::SaEast1 => "sa-east-1".to_string(),
-            FunctionRegion::UsEast1 => "us-east-1".to_string(),
-            FunctionRegion::UsWest1 => "us-west-1".to_string(),
-            FunctionRegion::UsWest2 => "us-west-2".to_string(),
-        };
-        write!(f, "{}", str)
+        f.write_str(self.as_str())
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FunctionInvokeOptions {
     pub headers: Option<HashMap<String, String>>,
+    /// Whether `headers` are added to the client's or sent instead of them.
+    pub header_mode: HeaderMode,
     pub method: Option<HttpMethod>,
     pub region: Option<FunctionRegion>,
     pub body: Option<InvokeBody>,
+    /// Infer the Content-Type from the body when none was set explicitly; a
+    /// `String` holding a JSON object or array is sent as `application/json`. Off when unset.
+    pub detect_content_type: Option<bool>,
+    /// Per-attempt timeout, overriding the client's default.
+    pub timeout: Option<Duration>,
+    /// How long the response body may go without a byte before failing with `StalledStream`,
+    /// overriding the client's [`idle_timeout`](crate::FunctionsClientBuilder::idle_timeout).
+    pub idle_timeout: Option<Duration>,
+    /// Media type used to parse a successful response instead of the one the
+    /// server sent. Types other than JSON, `text/*`, `multipart/*` and, with the `xml` feature,
+    /// XML types are read as bytes.
+    pub response_type: Option<String>,
+    /// Sent as `If-None-Match`, typically an earlier `metadata.etag()`.
+    pub if_none_match: Option<String>,
+    /// Sent as `If-Modified-Since`, an HTTP-date such as an earlier `metadata.last_modified()`.
+    pub if_modified_since: Option<String>,
+    /// Functions base URL for this call instead of the client's, e.g. another tenant's project.
+    /// The client's headers are still sent, but its `Authorization` and `apikey` only when the
+    /// origin is the same; pass the tenant's credentials in `headers`.
+    pub base_url: Option<String>,
+    /// Keep the exact response bytes in `metadata.raw_body` next to the parsed data,
+    /// e.g. to verify a signature over the body. It is the buffer the body was read into rather
+    /// than a second copy, and the client's
+    /// [`max_response_size`](crate::FunctionsClientBuilder::max_response_size) bounds it. Off
+    /// when unset.
+    pub keep_raw_body: Option<bool>,
+    /// Whether a non-2xx response fails with `HttpError`, overriding the client's
+    /// [`error_for_status`](crate::FunctionsClientBuilder::error_for_status).
+    pub error_for_status: Option<bool>,
+    /// Which statuses count as success instead of any 2xx; see
+    /// [`success_when`](Self::success_when).
+    #[serde(skip)]
+    pub success_status: Option<SuccessStatus>,
+    /// Path segments sent after the function name, each percent-encoded on its own, for
+    /// functions that route internally; see [`path`](Self::path).
+    pub path: Vec<String>,
+    /// Query parameters in order; a repeated name gives a parameter several values.
+    pub query: Vec<(String, String)>,
+    /// Names of query parameters that are lists whatever their number of values, as
+    /// [`query_from`](Self::query_from) marks its sequences.
+    pub query_lists: Vec<String>,
+    /// How parameters with several values are written; see [`ArrayStyle`].
+    pub array_style: ArrayStyle,
+    /// Called as the request body is sent; see [`on_upload_progress`](Self::on_upload_progress).
+    #[serde(skip)]
+    pub upload_progress: Option<Progress>,
+    /// Called as the response body arrives; see [`on_download_progress`](Self::on_download_progress).
+    #[serde(skip)]
+    pub download_progress: Option<Progress>,
+}
+
+/// Sets a header, replacing any earlier one whose name differs only in case.
+pub(crate) fn set_header(headers: &mut HashMap<String, String>, name: String, value: String) {
+    headers.retain(|existing, _| !existing.eq_ignore_ascii_case(&name));
+    headers.insert(name, value);
+}
+
+// A map has no order, so names differing only in case resolve the same way every time.
+fn sorted(headers: HashMap<String, String>) -> Vec<(String, String)> {
+    let mut headers: Vec<_> = headers.into_iter().collect();
+    headers.sort();
+    headers
+}
+
+/// How the per-invoke headers of [`FunctionInvokeOptions`] combine with the client's.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HeaderMode {
+    /// The client's default headers and credentials, the region header, trace context and an
+    /// inferred Content-Type, with the per-invoke headers taking precedence.
+    #[default]
+    Merge,
+    /// Only the per-invoke headers, including `If-None-Match` and `If-Modified-Since` from the
+    /// conditional options and the cache's revalidation, plus what the connection itself needs
+    /// such as `Host` and `Content-Length`. Credentials aren't added, nor is the request signed, so pass
+    /// `Authorization` in `headers` if the function needs it. The HTTP stack still sends
+    /// `Accept: */*` when there is no `Accept` header.
+    Replace,
+}
+
+impl FunctionInvokeOptions {
+    /// A `POST` with a JSON object body.
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use supabase_function_rs::{FunctionInvokeOptions, HttpMethod, InvokeBody};
+    ///
+    /// let options = FunctionInvokeOptions::json([("name", json!("Functions"))]);
+    /// assert!(matches!(options.method, Some(HttpMethod::Post)));
+    /// assert!(matches!(options.body, Some(InvokeBody::Json(ref body)) if body["name"] == "Functions"));
+    /// ```
+    pub fn json<K: Into<String>>(fields: impl IntoIterator<Item = (K, serde_json::Value)>) -> Self {
+        let body = fields.into_iter().map(|(key, value)| (key.into(), value)).collect();
+        Self::with_body(InvokeBody::Json(body))
+    }
+
+    /// A `POST` with a `text/plain` body.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use supabase_function_rs::{FunctionInvokeOptions, InvokeBody};
+    ///
+    /// let options = FunctionInvokeOptions {
+    ///     timeout: Some(Duration::from_secs(5)),
+    ///     ..FunctionInvokeOptions::text("hello")
+    /// };
+    /// assert!(matches!(options.body, Some(InvokeBody::String(ref s)) if s == "hello"));
+    /// ```
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::with_body(InvokeBody::String(text.into()))
+    }
+
+    /// A `POST` with an `application/octet-stream` body.
+    ///
+    /// ```
+    /// use supabase_function_rs::{FunctionInvokeOptions, InvokeBody};
+    ///
+    /// let options = FunctionInvokeOptions::bytes(vec![0xde, 0xad, 0xbe, 0xef]);
+    /// assert!(matches!(options.body, Some(InvokeBody::Blob(ref b)) if b.len() == 4));
+    /// ```
+    pub fn bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Self::with_body(InvokeBody::Blob(bytes.into()))
+    }
+
+    pub(crate) fn with_body(body: InvokeBody) -> Self {
+        Self { method: Some(HttpMethod::Post), body: Some(body), ..Self::default() }
+    }
+
+    /// Sets a header, replacing any earlier one whose name differs only in case.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        set_header(self.headers.get_or_insert_with(HashMap::new), name.into(), value.into());
+        self
+    }
+
+    /// These options over `defaults`: whatever they leave unset is taken from `defaults`, and
+    /// their headers and query parameters replace the defaults' of the same name. Under
+    /// `HeaderMode::Replace` the defaults' headers are dropped.
+    pub(crate) fn over(self, defaults: &FunctionInvokeOptions) -> Self {
+        let headers = match (&defaults.headers, self.headers) {
+            (Some(_), headers) if self.header_mode == HeaderMode::Replace => headers,
+            (Some(defaults), Some(headers)) => {
+                let mut merged = defaults.clone();
+                for (name, value) in sorted(headers) {
+                    set_header(&mut merged, name, value);
+                }
+                Some(merged)
+            }
+            (defaults, headers) => headers.or_else(|| defaults.clone()),
+        };
+        let mut query: Vec<_> = defaults.query.iter().filter(|(name, _)| !self.query.iter().any(|(own, _)| own == name)).cloned().collect();
+        let mut query_lists: Vec<_> = defaults.query_lists.iter().filter(|name| !self.query.iter().any(|(own, _)| own == *name)).cloned().collect();
+        query.extend(self.query);
+        query_lists.extend(self.query_lists);
+        FunctionInvokeOptions {
+            headers,
+            header_mode: if defaults.header_mode == HeaderMode::Replace { HeaderMode::Replace } else { self.header_mode },
+            method: self.method.or_else(|| defaults.method.clone()),
+            region: self.region.or(defaults.region),
+            body: self.body.or_else(|| defaults.body.clone()),
+            detect_content_type: self.detect_content_type.or(defaults.detect_content_type),
+            timeout: self.timeout.or(defaults.timeout),
+            idle_timeout: self.idle_timeout.or(defaults.idle_timeout),
+            response_type: self.response_type.or_else(|| defaults.response_type.clone()),
+            if_none_match: self.if_none_match.or_else(|| defaults.if_none_match.clone()),
+            if_modified_since: self.if_modified_since.or_else(|| defaults.if_modified_since.clone()),
+            base_url: self.base_url.or_else(|| defaults.base_url.clone()),
+            keep_raw_body: self.keep_raw_body.or(defaults.keep_raw_body),
+            error_for_status: self.error_for_status.or(defaults.error_for_status),
+            success_status: self.success_status.or_else(|| defaults.success_status.clone()),
+            path: defaults.path.iter().cloned().chain(self.path).collect(),
+            query,
+            query_lists,
+            array_style: if self.array_style == ArrayStyle::default() { defaults.array_style } else { self.array_style },
+            upload_progress: self.upload_progress.or_else(|| defaults.upload_progress.clone()),
+            download_progress: self.download_progress.or_else(|| defaults.download_progress.clone()),
+        }
+    }
+
+    /// Sets the `Accept` header. `mime` is sent as-is, so lists with q-params work;
+    /// a single concrete media type also becomes the `response_type` hint.
+    pub fn accept(mut self, mime: &str) -> Self {
+        self.response_type = accept_hint(mime);
+        self.header("Accept", mime)
+    }
+
+    /// Appends the `/`-separated segments of `route` to the URL after the function name, so
+    /// `invoke("api", ...)` with `.path("users/42")` calls `/functions/v1/api/users/42`. Empty
+    /// segments are skipped; use [`path_segment`](Self::path_segment) for a value that may
+    /// contain `/`.
+    ///
+    /// ```
+    /// use supabase_function_rs::FunctionInvokeOptions;
+    ///
+    /// let options = FunctionInvokeOptions::default().path("/users/").path_segment("a/b");
+    /// assert_eq!(options.path, ["users", "a/b"]);
+    /// ```
+    pub fn path(mut self, route: &str) -> Self {
+        self.path.extend(route.split('/').filter(|segment| !segment.is_empty()).map(String::from));
+        self
+    }
+
+    /// Appends a single path segment; a `/` inside it is sent as `%2F`.
+    pub fn path_segment(mut self, segment: impl Into<String>) -> Self {
+        self.path.push(segment.into());
+        self
+    }
+
+    /// Calls `callback` with the bytes of the body sent so far and the total as it is written,
+    /// e.g. to drive a progress bar for a large upload. A retry or redirect sends the body
+    /// again, so the count starts over. Multipart form bodies aren't tracked.
+    pub fn on_upload_progress(mut self, callback: impl Fn(u64, Option<u64>) + Send + Sync + 'static) -> Self {
+        self.upload_progress = Some(Progress::new(callback));
+        self
+    }
+
+    /// Calls `callback` with the bytes of the response body received so far and its
+    /// `Content-Length`, or `None` for a chunked response, as the body is read. Applies to
+    /// `invoke` and to the streaming calls such as `invoke_sse`. Error bodies aren't tracked.
+    pub fn on_download_progress(mut self, callback: impl Fn(u64, Option<u64>) + Send + Sync + 'static) -> Self {
+        self.download_progress = Some(Progress::new(callback));
+        self
+    }
 }
 
-#[derive(Debug, Clone)]
+fn accept_hint(accept: &str) -> Option<String> {
+    if accept.contains(',') {
+        return None;
+    }
+    let media_type = accept.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    if media_type.is_empty() || media_type.contains('*') {
+        return None;
+    }
+    Some(media_type)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InvokeBody {
     File(Vec<u8>),
     Blob(Vec<u8>),
     ArrayBuffer(Vec<u8>),
-    FormData(HashMap<String, String>),
+    #[cfg(feature = "multipart")]
+    FormData(FormFields),
     Json(HashMap<String, serde_json::Value>),
     String(String),
+    /// An encoded protobuf message, sent as `application/x-protobuf`.
+    #[cfg(feature = "prost")]
+    Protobuf(Vec<u8>),
+    /// A CBOR document, sent as `application/cbor`.
+    #[cfg(feature = "cbor")]
+    Cbor(Vec<u8>),
+}
+
+impl InvokeBody {
+    pub(crate) fn content_type(&self, detect: bool) -> Option<&'static str> {
+        match self {
+            InvokeBody::File(_) | InvokeBody::Blob(_) | InvokeBody::ArrayBuffer(_) => Some("application/octet-stream"),
+            InvokeBody::String(s) if detect && looks_like_json(s) => Some("application/json"),
+            InvokeBody::String(_) => Some("text/plain"),
+            // reqwest sets multipart/form-data along with the boundary
+            #[cfg(feature = "multipart")]
+            InvokeBody::FormData(_) => None,
+            InvokeBody::Json(_) => Some("application/json"),
+            #[cfg(feature = "prost")]
+            InvokeBody::Protobuf(_) => Some("application/x-protobuf"),
+            #[cfg(feature = "cbor")]
+            InvokeBody::Cbor(_) => Some("application/cbor"),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+fn looks_like_json(s: &str) -> bool {
+    let trimmed = s.trim_start();
+    (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(s).is_ok()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HttpMethod {
     Post,
     Get,
     Put,
     Patch,
     Delete,
+    /// Any other method token, e.g. `HEAD` or `OPTIONS`.
+    Custom(String),
+}
+
+impl TryFrom<&HttpMethod> for reqwest::Method {
+    type Error = FunctionsError;
+
+    fn try_from(method: &HttpMethod) -> Result<Self, Self::Error> {
+        Ok(match method {
+            HttpMethod::Post => reqwest::Method::POST,
+            HttpMethod::Get => reqwest::Method::GET,
+            HttpMethod::Put => reqwest::Method::PUT,
+            HttpMethod::Patch => reqwest::Method::PATCH,
+            HttpMethod::Delete => reqwest::Method::DELETE,
+            HttpMethod::Custom(method) => reqwest::Method::from_bytes(method.as_bytes())
+                .map_err(|_| FunctionsError::InvalidMethod(preview(method, HEADER_PREVIEW_LEN)))?,
+        })
+    }
 }
 
+const STANDARD_METHODS: [(&str, HttpMethod); 5] = [
+    ("POST", HttpMethod::Post),
+    ("GET", HttpMethod::Get),
+    ("PUT", HttpMethod::Put),
+    ("PATCH", HttpMethod::Patch),
+    ("DELETE", HttpMethod::Delete),
+];
+
 impl HttpMethod {
-    pub(crate) fn as_str(&self) -> &str {
+    pub fn as_str(&self) -> &str {
         match self {
             HttpMethod::Post => "POST",
             HttpMethod::Get => "GET",
             HttpMethod::Put => "PUT",
             HttpMethod::Patch => "PATCH",
             HttpMethod::Delete => "DELETE",
+            HttpMethod::Custom(method) => method,
+        }
+    }
+}
+
+impl Serialize for HttpMethod {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+// Standard methods are matched case-insensitively; anything else must be an
+// uppercase method token and becomes `Custom`.
+impl<'de> Deserialize<'de> for HttpMethod {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let method = String::deserialize(deserializer)?;
+        if let Some((_, standard)) = STANDARD_METHODS.iter().find(|(name, _)| name.eq_ignore_ascii_case(&method)) {
+            return Ok(standard.clone());
         }
+        let custom = HttpMethod::Custom(method);
+        if custom.as_str().bytes().all(|b| b.is_ascii_uppercase()) && reqwest::Method::try_from(&custom).is_ok() {
+            return Ok(custom);
+        }
+        let expected = STANDARD_METHODS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ");
+        Err(de::Error::custom(format!(
+            "invalid HTTP method \"{}\", expected one of {} or an uppercase method token",
+            preview(custom.as_str(), HEADER_PREVIEW_LEN),
+            expected
+        )))
     }
 }
 
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum ResponseData {
     Json(serde_json::Value),
     Text(String),
     #[serde(serialize_with = "serialize_bytes")]
     Bytes(Bytes),
-    FormData(HashMap<String, String>),
+    FormData(Parts),
 }
 
 // Implement custom deserialization for ResponseData
@@ -120,7 +463,7 @@ impl<'de> Visitor<'de> for ResponseDataVisitor {
     where
         V: MapAccess<'de>,
     {
-        while let Some(key) = map.next_key()? {
+        if let Some(key) = map.next_key()? {
             match key {
                 "Json" => {
                     let value = map.next_value()?;
@@ -145,7 +488,7 @@ impl<'de> Visitor<'de> for ResponseDataVisitor {
     }
 }
 
-const FIELDS: &'static [&'static str] = &["Json", "Text", "Bytes", "FormData"];
+const FIELDS: &[&str] = &["Json", "Text", "Bytes", "FormData"];
 
 // Custom serializer for Bytes
 fn serialize_bytes<S>(bytes: &Bytes, serializer: S) -> Result<S::Ok, S::Error>
@@ -156,8 +499,326 @@ where
 }
 
 
-#[derive(Debug)]
+/// A request as `invoke` would send it, from `FunctionsClient::build_request`.
+#[derive(Clone)]
+pub struct PreparedRequest {
+    pub method: Method,
+    pub url: Url,
+    headers: HeaderMap,
+    /// `None` without a body, and for multipart form bodies, which are streamed.
+    pub body: Option<Bytes>,
+    /// The fields of a form body.
+    #[cfg(feature = "multipart")]
+    pub(crate) form: Option<FormFields>,
+}
+
+impl PreparedRequest {
+    fn is_form(&self) -> bool {
+        #[cfg(feature = "multipart")]
+        return self.form.is_some();
+        #[cfg(not(feature = "multipart"))]
+        false
+    }
+
+    /// The headers in the order they'd be sent, with credential values replaced by `[REDACTED]`.
+    pub fn headers(&self) -> Vec<(String, String)> {
+        self.headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if is_secret(name, value) {
+                    REDACTED.to_string()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                (name.to_string(), value)
+            })
+            .collect()
+    }
+
+    /// The headers as they'd be sent, credentials included.
+    pub fn unredacted_headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// A curl command that sends this request. Credentials are `[REDACTED]` when `redact_auth`
+    /// is set. Form fields use `--form-string`, so values starting with `@` or `<` aren't read
+    /// as files; file fields are attached from a local file of their filename. A body that
+    /// isn't UTF-8 is left out and read from stdin with `--data-binary @-`.
+    pub fn to_curl(&self, redact_auth: bool) -> String {
+        let mut command = format!("curl -X {}", shell_quote(self.method.as_str()));
+        for (name, value) in &self.headers {
+            // curl works out the length, and writes its own multipart boundary.
+            if name == CONTENT_LENGTH || (self.is_form() && name == CONTENT_TYPE) {
+                continue;
+            }
+            let value = if redact_auth && is_secret(name, value) {
+                REDACTED.into()
+            } else {
+                String::from_utf8_lossy(value.as_bytes())
+            };
+            command.push_str(&format!(" -H {}", shell_quote(&format!("{}: {}", name, value))));
+        }
+        let mut note = None;
+        #[cfg(feature = "multipart")]
+        if let Some(ref fields) = self.form {
+            form::curl_args(fields, &mut command);
+        }
+        #[cfg(feature = "multipart")]
+        let body = self.body.as_ref().filter(|_| self.form.is_none());
+        #[cfg(not(feature = "multipart"))]
+        let body = self.body.as_ref();
+        if let Some(body) = body {
+            match std::str::from_utf8(body) {
+                Ok(text) => command.push_str(&format!(" --data-binary {}", shell_quote(text))),
+                Err(_) => {
+                    command.push_str(" --data-binary @-");
+                    note = Some(format!(" # pipe the {}-byte binary body to stdin", body.len()));
+                }
+            }
+        }
+        command.push(' ');
+        command.push_str(&shell_quote(self.url.as_str()));
+        command.extend(note);
+        command
+    }
+}
+
+/// Single-quotes `value` for a POSIX shell, or uses `$'...'` when it has control characters
+/// such as newlines so the command stays on one line.
+pub(crate) fn shell_quote(value: &str) -> String {
+    if !value.chars().any(char::is_control) {
+        return format!("'{}'", value.replace('\'', r"'\''"));
+    }
+    let mut quoted = String::from("$'");
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str(r"\\"),
+            '\'' => quoted.push_str(r"\'"),
+            '\n' => quoted.push_str(r"\n"),
+            '\r' => quoted.push_str(r"\r"),
+            '\t' => quoted.push_str(r"\t"),
+            c if c.is_ascii_control() => quoted.push_str(&format!("\\x{:02x}", c as u32)),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+const REDACTED: &str = "[REDACTED]";
+
+pub(crate) fn is_secret(name: &HeaderName, value: &HeaderValue) -> bool {
+    value.is_sensitive()
+        || name == AUTHORIZATION
+        || name == PROXY_AUTHORIZATION
+        || name == COOKIE
+        || name.as_str() == "apikey"
+}
+
+impl From<reqwest::Request> for PreparedRequest {
+    fn from(request: reqwest::Request) -> Self {
+        PreparedRequest {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            headers: request.headers().clone(),
+            body: request.body().and_then(|body| body.as_bytes()).map(Bytes::copy_from_slice),
+            #[cfg(feature = "multipart")]
+            form: None,
+        }
+    }
+}
+
+impl fmt::Debug for PreparedRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreparedRequest")
+            .field("method", &self.method)
+            .field("url", &self.url.as_str())
+            .field("headers", &self.headers())
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseMetadata {
+    /// URL that produced the response, after any redirects were followed.
+    pub url: Option<Url>,
+    pub status: u16,
+    /// The HTTP version the response was received over.
+    pub version: Version,
+    pub headers: HeaderMap,
+    /// Time from sending the request until the last body byte was read.
+    pub elapsed: Duration,
+    /// `None` when the response had no `Content-Type`; its body is then returned as `Bytes`.
+    pub content_type: Option<String>,
+    pub content_length: Option<u64>,
+    /// The text body had invalid UTF-8 that was replaced with U+FFFD.
+    pub lossy: bool,
+    /// The body as received, when the invoke set `keep_raw_body`.
+    pub raw_body: Option<Bytes>,
+    /// When the response headers arrived, the reference for [`ResponseMetadata::rate_limit`].
+    pub received_at: Option<SystemTime>,
+}
+
+impl ResponseMetadata {
+    /// The lowercased media type of the `Content-Type`, without parameters, e.g. `text/html`.
+    pub fn media_type(&self) -> Option<String> {
+        let content_type = self.content_type.as_deref()?;
+        Some(content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase())
+    }
+
+    /// The lowercased `charset` parameter of the `Content-Type`.
+    pub fn charset(&self) -> Option<String> {
+        self.content_type.as_deref()?.split(';').skip(1).find_map(|param| {
+            let (name, value) = param.split_once('=')?;
+            name.trim().eq_ignore_ascii_case("charset").then(|| value.trim().trim_matches('"').to_ascii_lowercase())
+        })
+    }
+
+    /// Whether the response is `text/html`, so its `Text` data is an HTML document.
+    pub fn is_html(&self) -> bool {
+        self.media_type().as_deref() == Some("text/html")
+    }
+
+    pub fn location(&self) -> Option<&str> {
+        self.headers.get(reqwest::header::LOCATION).and_then(|v| v.to_str().ok())
+    }
+
+    pub fn etag(&self) -> Option<&str> {
+        self.headers.get(reqwest::header::ETAG).and_then(|v| v.to_str().ok())
+    }
+
+    pub fn last_modified(&self) -> Option<&str> {
+        self.headers.get(reqwest::header::LAST_MODIFIED).and_then(|v| v.to_str().ok())
+    }
+
+    /// The `sb-*` and `x-sb-*` headers added by the Supabase functions gateway.
+    pub fn gateway(&self) -> GatewayInfo {
+        let mut info = GatewayInfo::default();
+        for (name, value) in &self.headers {
+            let name = name.as_str();
+            if !(name.starts_with("sb-") || name.starts_with("x-sb-")) {
+                continue;
+            }
+            let value = match value.to_str() {
+                Ok(value) => value.to_string(),
+                Err(_) => continue,
+            };
+            match name {
+                "sb-gateway-version" => info.gateway_version = Some(value),
+                "sb-project-ref" => info.project_ref = Some(value),
+                "sb-request-id" => info.request_id = Some(value),
+                "x-sb-edge-region" => info.edge_region = Some(value),
+                _ => {
+                    info.other.insert(name.to_string(), value);
+                }
+            }
+        }
+        info
+    }
+
+    /// The `x-ratelimit-*` headers, or the IETF draft's `ratelimit-*` ones, when the response
+    /// has any. A reset given as a Unix timestamp is turned into the time left until then.
+    /// Either form is measured from `received_at`, so the result doesn't change with when
+    /// this is called; without `received_at` the current time is used.
+    pub fn rate_limit(&self) -> Option<RateLimitInfo> {
+        let number = |name: &str| {
+            ["x-ratelimit-", "ratelimit-"].iter().find_map(|prefix| {
+                let value = self.headers.get(format!("{}{}", prefix, name))?.to_str().ok()?;
+                // The draft allows a policy after the number, as in `100, 100;w=60`.
+                value.split([',', ';']).next()?.trim().parse::<u64>().ok()
+            })
+        };
+        let info = RateLimitInfo { limit: number("limit"), remaining: number("remaining"), reset: number("reset").map(|reset| reset_delay(reset, self.received_at.unwrap_or_else(SystemTime::now))) };
+        (info != RateLimitInfo::default()).then_some(info)
+    }
+}
+
+// Resets this large are Unix timestamps (September 2001 onwards); smaller ones are seconds to wait.
+const RESET_EPOCH_THRESHOLD: u64 = 1_000_000_000;
+
+fn reset_delay(reset: u64, received_at: SystemTime) -> Duration {
+    if reset < RESET_EPOCH_THRESHOLD {
+        return Duration::from_secs(reset);
+    }
+    (UNIX_EPOCH + Duration::from_secs(reset)).duration_since(received_at).unwrap_or_default()
+}
+
+/// A gateway's rate limit for the caller, see [`ResponseMetadata::rate_limit`]. Fields are
+/// `None` when their header is missing or isn't a number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// Requests allowed per window.
+    pub limit: Option<u64>,
+    /// Requests left in the current window.
+    pub remaining: Option<u64>,
+    /// Time from when the response arrived until the window resets, zero if it already had.
+    pub reset: Option<Duration>,
+}
+
+/// Debugging details from the functions gateway, see [`ResponseMetadata::gateway`].
+/// Fields are `None` when the gateway didn't send the header.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GatewayInfo {
+    /// `sb-gateway-version`
+    pub gateway_version: Option<String>,
+    /// `sb-project-ref`
+    pub project_ref: Option<String>,
+    /// `sb-request-id`, useful when reporting an issue to Supabase.
+    pub request_id: Option<String>,
+    /// `x-sb-edge-region`, the region that ran the function.
+    pub edge_region: Option<String>,
+    /// Other `sb-*` and `x-sb-*` headers, keyed by lowercase name.
+    pub other: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
 pub enum FunctionsResponse {
-    Success { data: ResponseData },
+    Success { data: ResponseData, metadata: ResponseMetadata },
     Failure { error: FunctionsError },
+    /// The server answered a conditional request with 304; the copy you already have is current.
+    NotModified { metadata: ResponseMetadata },
+}
+
+impl FunctionsResponse {
+    /// Flattens `Failure` into the error, dropping the metadata of a success.
+    /// `NotModified` has no body and becomes `FunctionsError::NotModified`.
+    pub fn into_result(self) -> Result<ResponseData, FunctionsError> {
+        match self {
+            FunctionsResponse::Success { data, .. } => Ok(data),
+            FunctionsResponse::Failure { error } => Err(error),
+            FunctionsResponse::NotModified { metadata } => Err(FunctionsError::NotModified(Box::new(metadata))),
+        }
+    }
+
+    pub fn data(&self) -> Option<&ResponseData> {
+        match self {
+            FunctionsResponse::Success { data, .. } => Some(data),
+            FunctionsResponse::Failure { .. } | FunctionsResponse::NotModified { .. } => None,
+        }
+    }
+
+    pub fn metadata(&self) -> Option<&ResponseMetadata> {
+        match self {
+            FunctionsResponse::Success { metadata, .. } | FunctionsResponse::NotModified { metadata } => Some(metadata),
+            FunctionsResponse::Failure { .. } => None,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, FunctionsResponse::Success { .. })
+    }
+
+    pub fn is_not_modified(&self) -> bool {
+        matches!(self, FunctionsResponse::NotModified { .. })
+    }
+
+    pub fn err(&self) -> Option<&FunctionsError> {
+        match self {
+            FunctionsResponse::Failure { error } => Some(error),
+            FunctionsResponse::Success { .. } | FunctionsResponse::NotModified { .. } => None,
+        }
+    }
 }
@@ -0,0 +1,162 @@
+use crate::errors::FunctionsError;
+use crate::json;
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::ops::Deref;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Part {
+    pub name: Option<String>,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    #[serde(serialize_with = "serialize_bytes", deserialize_with = "deserialize_bytes")]
+    pub data: Bytes,
+}
+
+impl Part {
+    pub fn text(&self) -> Result<&str, FunctionsError> {
+        std::str::from_utf8(&self.data).map_err(|e| FunctionsError::DeserializeError(e.to_string()))
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, FunctionsError> {
+        json::from_slice(&self.data).map_err(FunctionsError::DeserializeError)
+    }
+}
+
+/// Parts of a multipart response in the order they were received. Names may repeat.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Parts(Vec<Part>);
+
+impl Parts {
+    /// First part with the given name.
+    pub fn find(&self, name: &str) -> Option<&Part> {
+        self.0.iter().find(|part| part.name.as_deref() == Some(name))
+    }
+
+    pub fn into_vec(self) -> Vec<Part> {
+        self.0
+    }
+}
+
+impl Deref for Parts {
+    type Target = [Part];
+
+    fn deref(&self) -> &[Part] {
+        &self.0
+    }
+}
+
+impl From<Vec<Part>> for Parts {
+    fn from(parts: Vec<Part>) -> Self {
+        Parts(parts)
+    }
+}
+
+impl IntoIterator for Parts {
+    type Item = Part;
+    type IntoIter = std::vec::IntoIter<Part>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+pub(crate) fn boundary(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        if key.trim().eq_ignore_ascii_case("boundary") {
+            Some(unquote(value.trim()).to_string())
+        } else {
+            None
+        }
+    })
+}
+
+pub(crate) fn parse(body: &Bytes, boundary: &str) -> Result<Parts, FunctionsError> {
+    let invalid = |reason: &str| FunctionsError::DeserializeError(format!("invalid multipart body: {}", reason));
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut parts = Vec::new();
+
+    let mut pos = find(body, &delimiter, 0).ok_or_else(|| invalid("missing opening boundary"))? + delimiter.len();
+    loop {
+        if body[pos..].starts_with(b"--") {
+            return Ok(Parts(parts));
+        }
+        pos += line_break_len(&body[pos..]);
+
+        // A part without headers has the blank line straight after the boundary line.
+        let (headers, data_start) = if body[pos..].starts_with(b"\r\n") {
+            ("", pos + 2)
+        } else {
+            let headers_end = find(body, b"\r\n\r\n", pos).ok_or_else(|| invalid("unterminated part headers"))?;
+            let headers = std::str::from_utf8(&body[pos..headers_end]).map_err(|_| invalid("part headers are not UTF-8"))?;
+            (headers, headers_end + 4)
+        };
+
+        let mut closing = b"\r\n".to_vec();
+        closing.extend_from_slice(&delimiter);
+        let data_end = find(body, &closing, data_start).ok_or_else(|| invalid("missing closing boundary"))?;
+
+        let mut part = Part { name: None, filename: None, content_type: None, data: body.slice(data_start..data_end) };
+        for line in headers.split("\r\n") {
+            let (key, value) = match line.split_once(':') {
+                Some(header) => header,
+                None => continue,
+            };
+            if key.trim().eq_ignore_ascii_case("content-disposition") {
+                for param in value.split(';').skip(1) {
+                    match param.split_once('=') {
+                        Some((k, v)) if k.trim() == "name" => part.name = Some(unquote(v.trim()).to_string()),
+                        Some((k, v)) if k.trim() == "filename" => part.filename = Some(unquote(v.trim()).to_string()),
+                        _ => {}
+                    }
+                }
+            } else if key.trim().eq_ignore_ascii_case("content-type") {
+                part.content_type = Some(value.trim().to_string());
+            }
+        }
+        parts.push(part);
+        pos = data_end + closing.len();
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|i| i + from)
+}
+
+fn line_break_len(rest: &[u8]) -> usize {
+    if rest.starts_with(b"\r\n") {
+        2
+    } else if rest.starts_with(b"\n") {
+        1
+    } else {
+        0
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+fn serialize_bytes<S>(bytes: &Bytes, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_bytes(bytes)
+}
+
+fn deserialize_bytes<'de, D>(deserializer: D) -> Result<Bytes, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<u8>::deserialize(deserializer).map(Bytes::from)
+}
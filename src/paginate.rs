@@ -0,0 +1,70 @@
+use crate::errors::FunctionsError;
+use serde_json::Value;
+
+const DEFAULT_ITEMS_FIELD: &str = "items";
+const DEFAULT_MAX_PAGES: u32 = 1000;
+
+/// Where [`FunctionsClient::invoke_paginated`](crate::FunctionsClient::invoke_paginated) finds
+/// the items and the cursor in each page. A field name converts into a `Pagination` reading
+/// that cursor and the `items` array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pagination {
+    cursor_field: String,
+    items_field: String,
+    max_pages: u32,
+}
+
+impl Pagination {
+    pub fn new(cursor_field: impl Into<String>) -> Self {
+        Pagination { cursor_field: cursor_field.into(), items_field: DEFAULT_ITEMS_FIELD.to_string(), max_pages: DEFAULT_MAX_PAGES }
+    }
+
+    /// The field holding each page's array of items, `items` by default.
+    pub fn items_field(mut self, field: impl Into<String>) -> Self {
+        self.items_field = field.into();
+        self
+    }
+
+    /// How many pages are fetched before the stream fails with `PageLimit`, 1000 by default.
+    /// Guards against a function that never stops returning a cursor.
+    pub fn max_pages(mut self, max_pages: u32) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+
+    pub(crate) fn limit(&self) -> u32 {
+        self.max_pages
+    }
+
+    /// Splits a page into its items and the cursor of the next page, `None` on the last one.
+    pub(crate) fn page(&self, mut body: Value) -> Result<(Vec<Value>, Option<String>), FunctionsError> {
+        let cursor = match body.get(&self.cursor_field) {
+            None | Some(Value::Null) => None,
+            Some(Value::String(cursor)) => Some(cursor.clone()),
+            Some(Value::Number(cursor)) => Some(cursor.to_string()),
+            Some(other) => {
+                return Err(FunctionsError::DeserializeError(format!("cursor \"{}\" is neither a string nor a number: {}", self.cursor_field, other)))
+            }
+        };
+        let items = match body.get_mut(&self.items_field).map(Value::take) {
+            Some(Value::Array(items)) => items,
+            None | Some(Value::Null) => Vec::new(),
+            Some(other) => {
+                return Err(FunctionsError::DeserializeError(format!("items \"{}\" is not an array: {}", self.items_field, other)))
+            }
+        };
+        Ok((items, cursor))
+    }
+}
+
+impl From<&str> for Pagination {
+    fn from(cursor_field: &str) -> Self {
+        Pagination::new(cursor_field)
+    }
+}
+
+impl From<String> for Pagination {
+    fn from(cursor_field: String) -> Self {
+        Pagination::new(cursor_field)
+    }
+}
@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+/// How long [`FunctionsClient::poll_until`](crate::FunctionsClient::poll_until) waits between
+/// polls. A plain `Duration` converts into a fixed interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollInterval {
+    initial: Duration,
+    max: Duration,
+}
+
+impl PollInterval {
+    /// Waits `interval` between every two polls.
+    pub fn fixed(interval: Duration) -> Self {
+        PollInterval { initial: interval, max: interval }
+    }
+
+    /// Waits `initial` after the first poll and doubles the wait after each one, up to `max`.
+    pub fn backoff(initial: Duration, max: Duration) -> Self {
+        PollInterval { initial, max: max.max(initial) }
+    }
+
+    /// The wait after poll number `poll`, counting from 1.
+    pub(crate) fn delay_for(&self, poll: u32) -> Duration {
+        let factor = 2u32.saturating_pow(poll.saturating_sub(1));
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+}
+
+impl From<Duration> for PollInterval {
+    fn from(interval: Duration) -> Self {
+        PollInterval::fixed(interval)
+    }
+}
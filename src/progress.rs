@@ -0,0 +1,101 @@
+use crate::errors::FunctionsError;
+use bytes::Bytes;
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use reqwest::header::{HeaderValue, CONTENT_LENGTH};
+use reqwest::{Response, ResponseBuilderExt};
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+/// How much of a body is handed to the connection at a time while its progress is tracked.
+const CHUNK_LEN: usize = 64 * 1024;
+
+/// Called with the bytes of a body transferred so far and its total length when known, as
+/// the body is sent or received.
+///
+/// It runs on whichever thread drives the connection, so it should return quickly, e.g. by
+/// storing the count in an atomic. It can't stop the request: a panic inside it is swallowed.
+#[derive(Clone)]
+pub struct Progress(Arc<dyn Fn(u64, Option<u64>) + Send + Sync>);
+
+impl Progress {
+    pub fn new(callback: impl Fn(u64, Option<u64>) + Send + Sync + 'static) -> Self {
+        Progress(Arc::new(callback))
+    }
+
+    fn report(&self, transferred: u64, total: Option<u64>) {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| (self.0)(transferred, total)));
+    }
+
+    /// Replaces an in-memory body with one streamed in chunks that reports each as it's taken.
+    /// The `Content-Length` is kept, so the body isn't sent chunked. Multipart forms, which are
+    /// already streams, are sent untracked.
+    pub(crate) fn track_upload(&self, mut request: reqwest::Request) -> reqwest::Request {
+        let body = match request.body().and_then(|body| body.as_bytes()) {
+            Some(body) if !body.is_empty() => Bytes::copy_from_slice(body),
+            _ => return request,
+        };
+        let total = body.len() as u64;
+        let progress = self.clone();
+        let mut sent = 0;
+        let chunks = (0..body.len()).step_by(CHUNK_LEN).map(move |start| body.slice(start..(start + CHUNK_LEN).min(body.len())));
+        let chunks = stream::iter(chunks).map(move |chunk| {
+            sent += chunk.len() as u64;
+            progress.report(sent, Some(total));
+            Ok::<_, Infallible>(chunk)
+        });
+        *request.body_mut() = Some(reqwest::Body::wrap_stream(chunks));
+        request.headers_mut().insert(CONTENT_LENGTH, HeaderValue::from(total));
+        request
+    }
+
+    /// Rebuilds `response` around a body that reports each chunk as it's read against `total`,
+    /// the `Content-Length` of the response as received, so `None` for a chunked response.
+    /// A body already rebuilt, e.g. by the idle watch, no longer knows its length itself.
+    pub(crate) fn track_download(&self, response: Response, total: Option<u64>) -> Result<Response, FunctionsError> {
+        let progress = self.clone();
+        let mut received = 0;
+        map_body(response, move |chunks| {
+            chunks.map(move |chunk| {
+                if let Ok(ref chunk) = chunk {
+                    received += chunk.len() as u64;
+                    progress.report(received, total);
+                }
+                chunk.map_err(Into::into)
+            })
+        })
+    }
+}
+
+/// Rebuilds `response` with its body passed through `wrap`, keeping the status, version, URL
+/// and headers.
+pub(crate) fn map_body<S>(response: Response, wrap: impl FnOnce(BoxStream<'static, reqwest::Result<Bytes>>) -> S) -> Result<Response, FunctionsError>
+where
+    S: Stream<Item = Result<Bytes, Box<dyn Error + Send + Sync>>> + Send + 'static,
+{
+    let mut builder = http::Response::builder().status(response.status()).version(response.version()).url(response.url().clone());
+    if let Some(headers) = builder.headers_mut() {
+        *headers = response.headers().clone();
+    }
+    let response = builder
+        .body(reqwest::Body::wrap_stream(wrap(response.bytes_stream().boxed())))
+        .map_err(|e| FunctionsError::DeserializeError(format!("invalid response: {}", e)))?;
+    Ok(Response::from(response))
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Progress(..)")
+    }
+}
+
+/// Two callbacks are equal when they are the same closure.
+impl PartialEq for Progress {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Progress {}
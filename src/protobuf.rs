@@ -0,0 +1,28 @@
+use crate::errors::FunctionsError;
+use crate::models::{InvokeBody, ResponseData};
+use prost::Message;
+
+impl InvokeBody {
+    /// Encodes `message` as an `application/x-protobuf` body.
+    pub fn protobuf(message: &impl Message) -> Self {
+        InvokeBody::Protobuf(message.encode_to_vec())
+    }
+}
+
+impl ResponseData {
+    /// Decodes a protobuf response into `M`. Responses labelled `application/x-protobuf`,
+    /// `application/protobuf` or `application/vnd.google.protobuf` arrive as `Bytes`.
+    pub fn protobuf_into<M: Message + Default>(&self) -> Result<M, FunctionsError> {
+        match self {
+            ResponseData::Bytes(bytes) => {
+                M::decode(bytes.as_ref()).map_err(|e| FunctionsError::DeserializeError(format!("invalid protobuf message: {}", e)))
+            }
+            other => Err(FunctionsError::DeserializeError(format!("expected a protobuf message, got {:?}", other))),
+        }
+    }
+}
+
+/// `application/x-protobuf` and the other names protobuf bodies go by, which are read as `Bytes`.
+pub(crate) fn is_protobuf(media_type: &str) -> bool {
+    matches!(media_type, "application/x-protobuf" | "application/protobuf" | "application/vnd.google.protobuf")
+}
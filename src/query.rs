@@ -0,0 +1,134 @@
+use crate::errors::FunctionsError;
+use crate::models::FunctionInvokeOptions;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// How a query parameter with several values, or one listed in
+/// [`query_lists`](FunctionInvokeOptions::query_lists), is written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ArrayStyle {
+    /// `ids=1&ids=2`
+    #[default]
+    Repeat,
+    /// `ids[]=1&ids[]=2`, as PHP and Rails expect.
+    Brackets,
+    /// `ids=1,2`; commas inside a value are percent-encoded.
+    CommaSeparated,
+}
+
+impl FunctionInvokeOptions {
+    /// Appends a query parameter. Adding a name again gives it several values, written in the
+    /// options' [`ArrayStyle`].
+    pub fn query_param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((name.into(), value.into()));
+        self
+    }
+
+    /// Appends the fields of `value`, a struct or map, as query parameters sorted by name.
+    /// Sequences give a parameter several values and are marked as lists, so `Brackets`
+    /// writes `ids[]=4` for a single id; an empty sequence is left out. Nested structs and maps
+    /// become `parent[child]` names, and `None` fields are left out.
+    ///
+    /// `value` goes through `serde_json::to_value` rather than `serde_urlencoded`, which
+    /// rejects sequences and nested structs.
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use supabase_function_rs::FunctionInvokeOptions;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Filter { status: &'static str, after: Option<u32> }
+    /// #[derive(Serialize)]
+    /// struct Search { ids: Vec<u32>, filter: Filter }
+    ///
+    /// let search = Search { ids: vec![1, 2], filter: Filter { status: "open", after: None } };
+    /// let options = FunctionInvokeOptions::default().query_from(&search).unwrap();
+    /// assert_eq!(options.query, [("filter[status]".into(), "open".into()), ("ids".into(), "1".into()), ("ids".into(), "2".into())]);
+    /// assert_eq!(options.query_lists, ["ids"]);
+    /// ```
+    pub fn query_from<T: Serialize>(mut self, value: &T) -> Result<Self, FunctionsError> {
+        let value = serde_json::to_value(value).map_err(|e| FunctionsError::BuildError(format!("invalid query: {}", e)))?;
+        match value {
+            Value::Object(fields) => {
+                for (name, value) in fields {
+                    flatten(name, value, &mut self.query, &mut self.query_lists)?;
+                }
+                Ok(self)
+            }
+            other => Err(FunctionsError::BuildError(format!("query must be a struct or map, got {}", other))),
+        }
+    }
+
+    /// How parameters with several values are written.
+    pub fn array_style(mut self, style: ArrayStyle) -> Self {
+        self.array_style = style;
+        self
+    }
+}
+
+fn flatten(name: String, value: Value, query: &mut Vec<(String, String)>, lists: &mut Vec<String>) -> Result<(), FunctionsError> {
+    match value {
+        Value::Null => {}
+        Value::String(text) => query.push((name, text)),
+        Value::Bool(_) | Value::Number(_) => query.push((name, value.to_string())),
+        Value::Array(items) => {
+            for item in items {
+                if item.is_array() || item.is_object() {
+                    return Err(FunctionsError::BuildError(format!("query parameter {} has a nested list", name)));
+                }
+                flatten(name.clone(), item, query, lists)?;
+            }
+            if !lists.contains(&name) {
+                lists.push(name);
+            }
+        }
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                flatten(format!("{}[{}]", name, key), value, query, lists)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The query string for `params`, with the values of a repeated name or one of `lists` written
+/// in `style` at the position of its first occurrence. Names keep `[` and `]` as they are;
+/// everything else is form-encoded.
+pub(crate) fn encode(params: &[(String, String)], lists: &[String], style: ArrayStyle) -> String {
+    let mut grouped: Vec<(&str, Vec<&str>)> = Vec::new();
+    for (name, value) in params {
+        match grouped.iter_mut().find(|(existing, _)| existing == name) {
+            Some((_, values)) => values.push(value),
+            None => grouped.push((name, vec![value])),
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for (name, values) in grouped {
+        let list = values.len() > 1 || lists.iter().any(|list| list == name);
+        let name = encode_name(name);
+        match style {
+            _ if !list => pairs.push(format!("{}={}", name, encode_value(values[0]))),
+            ArrayStyle::Repeat => pairs.extend(values.iter().map(|value| format!("{}={}", name, encode_value(value)))),
+            ArrayStyle::Brackets => pairs.extend(values.iter().map(|value| format!("{}[]={}", name, encode_value(value)))),
+            ArrayStyle::CommaSeparated => {
+                let joined: Vec<_> = values.iter().map(|value| encode_value(value)).collect();
+                pairs.push(format!("{}={}", name, joined.join(",")));
+            }
+        }
+    }
+    pairs.join("&")
+}
+
+fn encode_name(name: &str) -> String {
+    name.split_inclusive(['[', ']'])
+        .map(|piece| match piece.strip_suffix(['[', ']']) {
+            Some(text) => format!("{}{}", encode_value(text), &piece[text.len()..]),
+            None => encode_value(piece),
+        })
+        .collect()
+}
+
+fn encode_value(value: &str) -> String {
+    form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
@@ -0,0 +1,50 @@
+use reqwest::Url;
+use std::fmt;
+use std::sync::Arc;
+
+/// A redirect the client is about to follow.
+pub struct RedirectAttempt<'a> {
+    pub status: u16,
+    pub next: &'a Url,
+    /// URLs already requested, starting with the function URL.
+    pub previous: &'a [Url],
+}
+
+#[derive(Clone)]
+pub enum RedirectPolicy {
+    /// Follow at most this many redirects.
+    Limited(usize),
+    /// Never follow redirects; the 3xx response is returned as an `HttpError`.
+    None,
+    Custom(Arc<dyn Fn(&RedirectAttempt) -> bool + Send + Sync>),
+}
+
+impl RedirectPolicy {
+    pub fn custom(predicate: impl Fn(&RedirectAttempt) -> bool + Send + Sync + 'static) -> Self {
+        RedirectPolicy::Custom(Arc::new(predicate))
+    }
+
+    pub(crate) fn follows(&self, attempt: &RedirectAttempt) -> bool {
+        match self {
+            RedirectPolicy::Limited(max) => attempt.previous.len() <= *max,
+            RedirectPolicy::None => false,
+            RedirectPolicy::Custom(predicate) => predicate(attempt),
+        }
+    }
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        RedirectPolicy::Limited(10)
+    }
+}
+
+impl fmt::Debug for RedirectPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RedirectPolicy::Limited(max) => f.debug_tuple("Limited").field(max).finish(),
+            RedirectPolicy::None => f.write_str("None"),
+            RedirectPolicy::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use crate::errors::{ConnectErrorKind, FunctionsError, TimeoutKind};
+use reqwest::header::HeaderMap;
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const RETRYABLE_STATUSES: [u16; 3] = [502, 503, 504];
+const GATEWAY_STATUSES: [u16; 8] = [502, 503, 504, 520, 521, 522, 523, 524];
+/// How much of an error body a retry predicate gets to see.
+pub const RETRY_PEEK_LIMIT: usize = 8 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Upper bound on the time spent across all attempts and the sleeps between them.
+    pub max_elapsed: Option<Duration>,
+    /// Response statuses worth another attempt; 502, 503 and 504 unless changed.
+    pub retry_statuses: Vec<u16>,
+    predicate: Option<RetryPredicate>,
+    on_retry: Option<RetryCallback>,
+}
+
+/// A failed attempt, as seen by a [`RetryPolicy::retry_if`] predicate.
+#[derive(Debug)]
+pub struct AttemptOutcome<'a> {
+    /// 1 for the first attempt.
+    pub attempt: u32,
+    pub status: Option<u16>,
+    pub headers: Option<&'a HeaderMap>,
+    /// The start of the response body, at most `RETRY_PEEK_LIMIT` bytes.
+    pub body: &'a [u8],
+    /// The transport-level error when no response was received.
+    pub error: Option<&'a FunctionsError>,
+    /// Whether the policy would retry without the predicate.
+    pub retryable: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryDecision {
+    /// Retry after the policy's backoff, or the server's `Retry-After`.
+    Retry,
+    RetryAfter(Duration),
+    Stop,
+}
+
+/// A retry about to happen, as passed to [`RetryPolicy::on_retry`].
+#[derive(Debug)]
+pub struct RetryEvent<'a> {
+    pub function_name: &'a str,
+    /// The attempt that failed, 1 for the first.
+    pub attempt: u32,
+    pub status: Option<u16>,
+    pub error: &'a FunctionsError,
+    /// How long the client will wait before the next attempt.
+    pub delay: Duration,
+}
+
+/// One failed attempt of an invoke that ran out of retries, from `RetriesExhausted::history`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttemptSummary {
+    /// 1 for the first attempt.
+    pub attempt: u32,
+    pub failure: AttemptFailure,
+    pub elapsed: Duration,
+}
+
+/// How an attempt failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttemptFailure {
+    Status(u16),
+    Connect(ConnectErrorKind),
+    Timeout(TimeoutKind),
+    /// Any other error, as its `Display` text.
+    Other(String),
+}
+
+impl AttemptFailure {
+    pub(crate) fn of(error: &FunctionsError) -> Self {
+        match error {
+            FunctionsError::HttpError { status, .. } => AttemptFailure::Status(*status),
+            FunctionsError::ConnectError { kind, .. } => AttemptFailure::Connect(*kind),
+            FunctionsError::Timeout { kind, .. } => AttemptFailure::Timeout(*kind),
+            error => AttemptFailure::Other(error.to_string()),
+        }
+    }
+}
+
+/// Why an attempt failed, for logs: a status, connect error or timeout kind, or the error
+/// variant, never a body or header value.
+#[cfg(any(feature = "tracing", feature = "log"))]
+pub(crate) fn reason(error: &FunctionsError) -> String {
+    if let FunctionsError::RetriesExhausted { last_error, .. } = error {
+        return reason(last_error);
+    }
+    match AttemptFailure::of(error) {
+        AttemptFailure::Status(status) => format!("status {}", status),
+        AttemptFailure::Connect(kind) => format!("connect error {:?}", kind),
+        AttemptFailure::Timeout(kind) => kind.to_string(),
+        // `Display` puts the variant name before the first colon.
+        AttemptFailure::Other(message) => message.split(':').next().unwrap_or_default().to_string(),
+    }
+}
+
+type PredicateFn = dyn Fn(&AttemptOutcome) -> RetryDecision + Send + Sync;
+type CallbackFn = dyn Fn(&RetryEvent) + Send + Sync;
+
+#[derive(Clone)]
+struct RetryPredicate(Arc<PredicateFn>);
+
+impl fmt::Debug for RetryPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPredicate").finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone)]
+struct RetryCallback(Arc<CallbackFn>);
+
+impl fmt::Debug for RetryCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryCallback").finish_non_exhaustive()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            max_elapsed: None,
+            retry_statuses: RETRYABLE_STATUSES.to_vec(),
+            predicate: None,
+            on_retry: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32) -> Self {
+        Self { max_attempts, ..Self::default() }
+    }
+
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// Replaces the retried statuses. Nothing outside this list is retried, 4xx included.
+    pub fn retry_on_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.retry_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Retries every 5xx status.
+    pub fn server_errors(self) -> Self {
+        self.retry_on_statuses(500..=599)
+    }
+
+    /// Retries 502, 503 and 504 plus the 520 to 524 origin errors reported by Cloudflare-style proxies.
+    pub fn gateway_errors(self) -> Self {
+        self.retry_on_statuses(GATEWAY_STATUSES)
+    }
+
+    /// Decides every failed attempt with `predicate`, overriding the status and error
+    /// classification either way. `max_attempts` and `max_elapsed` still apply, and the
+    /// error returned on `Stop` keeps the full body.
+    pub fn retry_if(mut self, predicate: impl Fn(&AttemptOutcome) -> RetryDecision + Send + Sync + 'static) -> Self {
+        self.predicate = Some(RetryPredicate(Arc::new(predicate)));
+        self
+    }
+
+    /// Calls `callback` before every backoff sleep, to surface retries that would otherwise
+    /// only show up as latency. A panic in `callback` is caught and ignored.
+    pub fn on_retry(mut self, callback: impl Fn(&RetryEvent) + Send + Sync + 'static) -> Self {
+        self.on_retry = Some(RetryCallback(Arc::new(callback)));
+        self
+    }
+
+    pub(crate) fn notify_retry(&self, event: &RetryEvent) {
+        if let Some(ref callback) = self.on_retry {
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| (callback.0)(event)));
+        }
+    }
+
+    pub(crate) fn should_retry(&self, error: &FunctionsError) -> bool {
+        match error {
+            FunctionsError::HttpError { status, .. } => self.retry_statuses.contains(status),
+            _ => error.is_retryable(),
+        }
+    }
+
+    pub(crate) fn decide(&self, attempt: u32, error: &FunctionsError) -> RetryDecision {
+        let retryable = self.should_retry(error);
+        let predicate = match self.predicate {
+            Some(ref predicate) => predicate,
+            None if retryable => return RetryDecision::Retry,
+            None => return RetryDecision::Stop,
+        };
+        let outcome = match error {
+            FunctionsError::HttpError { status, body, metadata, .. } => AttemptOutcome {
+                attempt,
+                status: Some(*status),
+                headers: Some(&metadata.headers),
+                body: &body[..body.len().min(RETRY_PEEK_LIMIT)],
+                error: None,
+                retryable,
+            },
+            _ => AttemptOutcome { attempt, status: None, headers: None, body: &[], error: Some(error), retryable },
+        };
+        (predicate.0)(&outcome)
+    }
+
+    // Retry-After from the server wins over the exponential backoff.
+    pub(crate) fn delay_for(&self, attempt: u32, error: &FunctionsError) -> Duration {
+        if let Some(delay) = retry_after(error) {
+            return delay;
+        }
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+impl FunctionsError {
+    /// Whether the default `RetryPolicy` retries this error.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            FunctionsError::FetchError(_) | FunctionsError::Timeout { .. } => true,
+            FunctionsError::ConnectError { kind, .. } => *kind != ConnectErrorKind::TlsHandshake,
+            FunctionsError::HttpError { status, .. } => RETRYABLE_STATUSES.contains(status),
+            _ => false,
+        }
+    }
+}
+
+fn retry_after(error: &FunctionsError) -> Option<Duration> {
+    match error {
+        FunctionsError::HttpError { metadata, .. } => metadata
+            .headers
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs),
+        _ => None,
+    }
+}
+
+/// The client's source of time: how it waits between retries and polls, and the clock that
+/// retry deadlines and `poll_until` timeouts are measured on. A fake that skips its sleeps
+/// should move `now` forward by them, as [`MockSleeper`](crate::MockSleeper) does with the
+/// `test-util` feature.
+pub trait Sleeper: fmt::Debug + Send + Sync {
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Default)]
+pub struct TokioSleeper;
+
+#[cfg(feature = "tokio")]
+impl Sleeper for TokioSleeper {
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+#[cfg(feature = "async-std")]
+#[derive(Debug, Clone, Default)]
+pub struct AsyncStdSleeper;
+
+#[cfg(feature = "async-std")]
+impl Sleeper for AsyncStdSleeper {
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+#[cfg(not(any(feature = "tokio", feature = "async-std")))]
+compile_error!("enable either the `tokio` or the `async-std` feature");
+
+#[cfg(feature = "tokio")]
+pub(crate) fn default_sleeper() -> Arc<dyn Sleeper> {
+    Arc::new(TokioSleeper)
+}
+
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+pub(crate) fn default_sleeper() -> Arc<dyn Sleeper> {
+    Arc::new(AsyncStdSleeper)
+}
@@ -0,0 +1,72 @@
+use crate::errors::FunctionsError;
+use crate::models::FunctionsResponse;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::task::JoinHandle;
+
+/// An invocation waiting on a spawned task for its delay, from
+/// [`FunctionsClient::invoke_after`](crate::FunctionsClient::invoke_after).
+///
+/// Await it for the result. Dropping it cancels the invocation, even after it has been sent;
+/// call [`detach`](Self::detach) to let it run on its own instead.
+#[derive(Debug)]
+pub struct ScheduledInvocation {
+    task: Option<JoinHandle<Result<FunctionsResponse, FunctionsError>>>,
+    fired: Arc<AtomicBool>,
+}
+
+impl ScheduledInvocation {
+    pub(crate) fn new(task: JoinHandle<Result<FunctionsResponse, FunctionsError>>, fired: Arc<AtomicBool>) -> Self {
+        Self { task: Some(task), fired }
+    }
+
+    /// Whether the delay has elapsed and the invocation started.
+    pub fn has_fired(&self) -> bool {
+        self.fired.load(Ordering::SeqCst)
+    }
+
+    /// Cancels the invocation. Returns `true` if it hadn't started yet, so nothing was sent;
+    /// `false` means a request may already have reached the function.
+    pub fn cancel(mut self) -> bool {
+        let fired = self.has_fired();
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        !fired
+    }
+
+    /// Lets the invocation run to completion without anyone waiting for its result.
+    pub fn detach(mut self) {
+        self.task = None;
+    }
+}
+
+impl Future for ScheduledInvocation {
+    type Output = Result<FunctionsResponse, FunctionsError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let task = self.task.as_mut().expect("ScheduledInvocation polled after completion");
+        let result = match Pin::new(task).poll(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+        self.task = None;
+        Poll::Ready(match result {
+            Ok(result) => result,
+            Err(error) if error.is_panic() => std::panic::resume_unwind(error.into_panic()),
+            // Only the runtime shutting down cancels the task while this handle owns it.
+            Err(_) => Err(FunctionsError::FetchError("the scheduled invocation's runtime shut down".into())),
+        })
+    }
+}
+
+impl Drop for ScheduledInvocation {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use crate::errors::FunctionsError;
+use std::future::{poll_fn, Future};
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+
+/// What [`FunctionsClient::shutdown`](crate::FunctionsClient::shutdown) found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Invocations in flight when shutdown began that finished within the grace period.
+    pub completed: usize,
+    /// Invocations still running when the grace period ran out. They aren't cancelled; they
+    /// keep running until they finish or their caller drops them.
+    pub abandoned: usize,
+}
+
+/// Counts the invocations in flight across a client and its clones, and turns new ones away
+/// once shutdown has begun.
+#[derive(Debug, Default)]
+pub(crate) struct InFlight {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    closed: bool,
+    running: usize,
+    drain_waiters: Vec<Waker>,
+}
+
+impl InFlight {
+    /// Admits an invocation until the returned guard is dropped.
+    pub(crate) fn enter(self: &Arc<Self>) -> Result<InFlightGuard, FunctionsError> {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return Err(FunctionsError::ShuttingDown);
+        }
+        state.running += 1;
+        Ok(InFlightGuard(self.clone()))
+    }
+
+    /// Stops admitting invocations and returns how many are running.
+    pub(crate) fn close(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        state.running
+    }
+
+    pub(crate) fn running(&self) -> usize {
+        self.state.lock().unwrap().running
+    }
+
+    /// Resolves once nothing is running.
+    pub(crate) fn drained(&self) -> impl Future<Output = ()> + '_ {
+        poll_fn(move |cx| {
+            let mut state = self.state.lock().unwrap();
+            if state.running == 0 {
+                return Poll::Ready(());
+            }
+            if !state.drain_waiters.iter().any(|waiter| waiter.will_wake(cx.waker())) {
+                state.drain_waiters.push(cx.waker().clone());
+            }
+            Poll::Pending
+        })
+    }
+}
+
+pub(crate) struct InFlightGuard(Arc<InFlight>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let mut state = self.0.state.lock().unwrap();
+        state.running -= 1;
+        if state.running == 0 {
+            state.drain_waiters.drain(..).for_each(Waker::wake);
+        }
+    }
+}
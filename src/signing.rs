@@ -0,0 +1,41 @@
+use crate::errors::FunctionsError;
+use reqwest::header::HeaderMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// What a request signer sees: the request as it is about to be sent.
+///
+/// Signers run once per attempt, including retries and redirects, so a timestamp in the
+/// signature is always fresh.
+#[derive(Debug)]
+pub struct SignableRequest<'a> {
+    pub method: &'a str,
+    /// The URL path, percent-encoded as sent.
+    pub path: &'a str,
+    pub headers: &'a HeaderMap,
+    /// The exact body bytes, empty when there is no body.
+    pub body: &'a [u8],
+}
+
+type SignFn = dyn Fn(&SignableRequest) -> Result<Vec<(String, String)>, FunctionsError> + Send + Sync;
+
+#[derive(Clone)]
+pub(crate) struct RequestSigner(Arc<SignFn>);
+
+impl RequestSigner {
+    pub(crate) fn new(
+        sign: impl Fn(&SignableRequest) -> Result<Vec<(String, String)>, FunctionsError> + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(sign))
+    }
+
+    pub(crate) fn sign(&self, request: &SignableRequest) -> Result<Vec<(String, String)>, FunctionsError> {
+        (self.0)(request)
+    }
+}
+
+impl fmt::Debug for RequestSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestSigner").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,338 @@
+use crate::client::FunctionsClient;
+use crate::errors::FunctionsError;
+use crate::json;
+use crate::models::FunctionInvokeOptions;
+use bytes::Bytes;
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use reqwest::{Response, StatusCode};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+const LAST_EVENT_ID: &str = "Last-Event-ID";
+/// The data of the event that ends a streamed completion.
+const DONE: &str = "[DONE]";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The `id:` field of this event, if it had one.
+    pub id: Option<String>,
+    /// The `event:` field; `None` means the default `message` type.
+    pub event: Option<String>,
+    pub data: String,
+}
+
+/// Reconnects a dropped event stream, sending the last seen event id as `Last-Event-ID`.
+#[derive(Debug, Clone)]
+pub struct SseReconnect {
+    /// Reconnects allowed over the lifetime of the stream.
+    pub max_reconnects: u32,
+    /// Wait before reconnecting until the server sends a `retry:` interval.
+    pub default_retry: Duration,
+}
+
+impl SseReconnect {
+    pub fn new(max_reconnects: u32) -> Self {
+        Self { max_reconnects, default_retry: Duration::from_secs(3) }
+    }
+
+    pub fn default_retry(mut self, retry: Duration) -> Self {
+        self.default_retry = retry;
+        self
+    }
+}
+
+/// Where [`FunctionsClient::invoke_stream_text`] finds the text in each event, and whether an
+/// event without it ends the stream with an error.
+///
+/// By default the text is the string at `/delta` in the event's JSON data. An event where the
+/// pointer finds nothing or `null` adds no text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamTextOptions {
+    pointer: Option<String>,
+    skip_malformed: bool,
+}
+
+impl Default for StreamTextOptions {
+    fn default() -> Self {
+        StreamTextOptions { pointer: Some("/delta".to_string()), skip_malformed: false }
+    }
+}
+
+impl StreamTextOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// JSON pointer to the text, e.g. `/choices/0/delta/content`.
+    pub fn pointer(mut self, pointer: impl Into<String>) -> Self {
+        self.pointer = Some(pointer.into());
+        self
+    }
+
+    /// Takes each event's data as the text, without parsing it.
+    pub fn raw(mut self) -> Self {
+        self.pointer = None;
+        self
+    }
+
+    /// Skips events whose data isn't JSON or whose pointer finds something other than a string,
+    /// instead of failing with `InvalidJson` or `DeserializeError`.
+    pub fn skip_malformed(mut self, skip: bool) -> Self {
+        self.skip_malformed = skip;
+        self
+    }
+
+    fn extract(&self, data: &str) -> Result<String, FunctionsError> {
+        let pointer = match self.pointer {
+            Some(ref pointer) => pointer,
+            None => return Ok(data.to_string()),
+        };
+        let value: serde_json::Value = json::from_slice(data.as_bytes())
+            .map_err(|error| FunctionsError::InvalidJson { error, body: Bytes::copy_from_slice(data.as_bytes()) })?;
+        match value.pointer(pointer) {
+            Some(serde_json::Value::String(text)) => Ok(text.clone()),
+            None | Some(serde_json::Value::Null) => Ok(String::new()),
+            Some(other) => Err(FunctionsError::DeserializeError(format!("expected a string at {}, got {}", pointer, other))),
+        }
+    }
+}
+
+/// Passes the text of each event to `on_chunk` until `[DONE]` or the end of the stream, and
+/// returns all of it.
+pub(crate) async fn collect_text(
+    mut events: SseStream,
+    text: &StreamTextOptions,
+    mut on_chunk: impl FnMut(&str),
+) -> Result<String, FunctionsError> {
+    let mut collected = String::new();
+    while let Some(event) = events.next().await {
+        let event = event?;
+        if event.data.trim() == DONE {
+            break;
+        }
+        let chunk = match text.extract(&event.data) {
+            Ok(chunk) => chunk,
+            Err(_) if text.skip_malformed => continue,
+            Err(error) => return Err(error),
+        };
+        if !chunk.is_empty() {
+            on_chunk(&chunk);
+            collected.push_str(&chunk);
+        }
+    }
+    Ok(collected)
+}
+
+/// Events from a `text/event-stream` response, see [`FunctionsClient::invoke_sse`].
+pub struct SseStream {
+    state: Option<StreamState>,
+    events: Option<BoxStream<'static, Result<SseEvent, FunctionsError>>>,
+}
+
+impl SseStream {
+    pub(crate) fn new(client: FunctionsClient, function_name: &str, options: FunctionInvokeOptions, response: Response) -> Self {
+        let state = StreamState {
+            client,
+            function_name: function_name.to_string(),
+            options,
+            reconnect: None,
+            reconnects: 0,
+            response: Some(response),
+            parser: SseParser::default(),
+            pending: VecDeque::new(),
+        };
+        Self { state: Some(state), events: None }
+    }
+
+    /// Reconnect when the stream ends or fails instead of ending the stream.
+    /// Has no effect once the stream has been polled.
+    pub fn reconnect(mut self, reconnect: SseReconnect) -> Self {
+        if let Some(state) = self.state.as_mut() {
+            state.reconnect = Some(reconnect);
+        }
+        self
+    }
+}
+
+impl Stream for SseStream {
+    type Item = Result<SseEvent, FunctionsError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Some(state) = this.state.take() {
+            this.events = Some(stream::unfold(state, next_event).boxed());
+        }
+        match this.events.as_mut() {
+            Some(events) => events.poll_next_unpin(cx),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+impl fmt::Debug for SseStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SseStream").finish_non_exhaustive()
+    }
+}
+
+struct StreamState {
+    client: FunctionsClient,
+    function_name: String,
+    options: FunctionInvokeOptions,
+    reconnect: Option<SseReconnect>,
+    reconnects: u32,
+    response: Option<Response>,
+    parser: SseParser,
+    pending: VecDeque<SseEvent>,
+}
+
+async fn next_event(mut state: StreamState) -> Option<(Result<SseEvent, FunctionsError>, StreamState)> {
+    loop {
+        if let Some(event) = state.pending.pop_front() {
+            return Some((Ok(event), state));
+        }
+        let response = state.response.as_mut()?;
+        let failure = match response.chunk().await {
+            Ok(Some(chunk)) => {
+                let events = state.parser.feed(&chunk);
+                state.pending.extend(events);
+                continue;
+            }
+            Ok(None) => None,
+            Err(e) => Some(FunctionsError::from_reqwest(e)),
+        };
+        state.response = None;
+
+        match state.reconnect(failure).await {
+            Ok(()) => continue,
+            Err(Some(error)) => return Some((Err(error), state)),
+            Err(None) => return None,
+        }
+    }
+}
+
+impl StreamState {
+    // Err(None) ends the stream cleanly, Err(Some(_)) ends it with that error.
+    async fn reconnect(&mut self, mut failure: Option<FunctionsError>) -> Result<(), Option<FunctionsError>> {
+        let reconnect = match self.reconnect {
+            Some(ref reconnect) => reconnect.clone(),
+            None => return Err(failure),
+        };
+        while self.reconnects < reconnect.max_reconnects {
+            self.reconnects += 1;
+            self.parser.discard_pending();
+            self.client.sleep(self.parser.retry.unwrap_or(reconnect.default_retry)).await;
+
+            let mut options = self.options.clone();
+            if let Some(id) = self.parser.last_event_id.as_deref().filter(|id| !id.is_empty()) {
+                options.headers.get_or_insert_with(HashMap::new).insert(LAST_EVENT_ID.to_string(), id.to_string());
+            }
+            match self.client.open_stream(&self.function_name, &options).await {
+                // The server tells us to stop reconnecting.
+                Ok(response) if response.status() == StatusCode::NO_CONTENT => return Err(None),
+                Ok(response) => {
+                    self.response = Some(response);
+                    return Ok(());
+                }
+                Err(error) => failure = Some(error),
+            }
+        }
+        Err(failure)
+    }
+}
+
+/// Incremental parser following the HTML event-stream interpretation rules.
+#[derive(Debug, Default)]
+pub(crate) struct SseParser {
+    buffer: Vec<u8>,
+    started: bool,
+    data: Option<String>,
+    event: Option<String>,
+    id: Option<String>,
+    pub(crate) last_event_id: Option<String>,
+    pub(crate) retry: Option<Duration>,
+}
+
+impl SseParser {
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        let mut start = 0;
+        while let Some(offset) = self.buffer[start..].iter().position(|b| *b == b'\n' || *b == b'\r') {
+            let end = start + offset;
+            let next = match self.buffer[end] {
+                // A trailing \r may be the first half of a \r\n split across chunks.
+                b'\r' if end + 1 == self.buffer.len() => break,
+                b'\r' if self.buffer[end + 1] == b'\n' => end + 2,
+                _ => end + 1,
+            };
+            let line = String::from_utf8_lossy(&self.buffer[start..end]).into_owned();
+            if let Some(event) = self.process_line(&line) {
+                events.push(event);
+            }
+            start = next;
+        }
+        self.buffer.drain(..start);
+        events
+    }
+
+    /// Drops a partially received event, as required when the connection closes.
+    pub(crate) fn discard_pending(&mut self) {
+        self.buffer.clear();
+        self.started = false;
+        self.data = None;
+        self.event = None;
+        self.id = None;
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        let line = if self.started {
+            line
+        } else {
+            self.started = true;
+            line.strip_prefix('\u{feff}').unwrap_or(line)
+        };
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => match self.data {
+                Some(ref mut data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            },
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                if let Ok(millis) = value.parse() {
+                    self.retry = Some(Duration::from_millis(millis));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let id = self.id.take();
+        if let Some(ref id) = id {
+            self.last_event_id = Some(id.clone());
+        }
+        let event = self.event.take();
+        let data = self.data.take()?;
+        Some(SseEvent { id, event, data })
+    }
+}
@@ -0,0 +1,62 @@
+use crate::models::FunctionInvokeOptions;
+use reqwest::StatusCode;
+use std::fmt;
+use std::sync::Arc;
+
+/// Decides which response statuses count as success, in place of "any 2xx". Responses it
+/// rejects fail with `HttpError`.
+#[derive(Clone)]
+pub struct SuccessStatus(Arc<dyn Fn(StatusCode) -> bool + Send + Sync>);
+
+impl SuccessStatus {
+    pub fn new(predicate: impl Fn(StatusCode) -> bool + Send + Sync + 'static) -> Self {
+        SuccessStatus(Arc::new(predicate))
+    }
+
+    /// Accepts exactly the statuses in `statuses`.
+    pub fn any_of(statuses: impl IntoIterator<Item = u16>) -> Self {
+        let statuses: Vec<u16> = statuses.into_iter().collect();
+        Self::new(move |status| statuses.contains(&status.as_u16()))
+    }
+
+    pub(crate) fn accepts(&self, status: StatusCode) -> bool {
+        (self.0)(status)
+    }
+}
+
+impl FunctionInvokeOptions {
+    /// Treats the statuses for which `predicate` returns `true` as success, and every other
+    /// status as an `HttpError`. A `304 Not Modified` is still returned as `NotModified`.
+    ///
+    /// ```
+    /// use supabase_function_rs::{FunctionInvokeOptions, StatusCode};
+    ///
+    /// // Only 2xx and 404, e.g. for a lookup that may find nothing.
+    /// let options = FunctionInvokeOptions::default().success_when(|status| status.is_success() || status == StatusCode::NOT_FOUND);
+    /// ```
+    pub fn success_when(mut self, predicate: impl Fn(StatusCode) -> bool + Send + Sync + 'static) -> Self {
+        self.success_status = Some(SuccessStatus::new(predicate));
+        self
+    }
+
+    /// Treats exactly `statuses` as success, e.g. `[204]` for a function that must not return a body.
+    pub fn success_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.success_status = Some(SuccessStatus::any_of(statuses));
+        self
+    }
+}
+
+impl fmt::Debug for SuccessStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SuccessStatus(..)")
+    }
+}
+
+/// Two predicates are equal when they are the same closure.
+impl PartialEq for SuccessStatus {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SuccessStatus {}
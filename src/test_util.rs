@@ -0,0 +1,62 @@
+use crate::retry::Sleeper;
+use std::future::{self, Future};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A [`Sleeper`] whose sleeps return at once and move its clock forward instead, so retry
+/// backoff, `max_elapsed` deadlines and polling run instantly and deterministically in tests.
+/// Its clock is the real one plus everything slept so far, so time spent on requests still
+/// counts. Clones share the clock and the record of sleeps.
+///
+/// ```
+/// use std::time::Duration;
+/// use supabase_function_rs::{MockSleeper, Sleeper};
+///
+/// let sleeper = MockSleeper::new();
+/// let before = sleeper.now();
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// sleeper.sleep(Duration::from_secs(30)).await;
+/// # });
+/// assert!(sleeper.now() - before >= Duration::from_secs(30));
+/// assert_eq!(sleeper.slept(), [Duration::from_secs(30)]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockSleeper {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    advanced: Duration,
+    slept: Vec<Duration>,
+}
+
+impl MockSleeper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every sleep so far, in order.
+    pub fn slept(&self) -> Vec<Duration> {
+        self.state.lock().unwrap().slept.clone()
+    }
+
+    /// Moves the clock forward without recording a sleep, e.g. to let a deadline pass.
+    pub fn advance(&self, duration: Duration) {
+        self.state.lock().unwrap().advanced += duration;
+    }
+}
+
+impl Sleeper for MockSleeper {
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        let mut state = self.state.lock().unwrap();
+        state.advanced += duration;
+        state.slept.push(duration);
+        Box::pin(future::ready(()))
+    }
+
+    fn now(&self) -> Instant {
+        Instant::now() + self.state.lock().unwrap().advanced
+    }
+}
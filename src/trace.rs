@@ -0,0 +1,109 @@
+use crate::errors::FunctionsError;
+use crate::models::FunctionsResponse;
+use opentelemetry::trace::{SpanKind, Status, TraceContextExt, TraceFlags, Tracer};
+use opentelemetry::{global, Context, KeyValue};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Url;
+use std::time::Duration;
+
+const TRACEPARENT: HeaderName = HeaderName::from_static("traceparent");
+const TRACESTATE: HeaderName = HeaderName::from_static("tracestate");
+
+/// Adds W3C `traceparent` and `tracestate` headers for the current OpenTelemetry span, or a
+/// fresh sampled trace when there is none. A `traceparent` already in `headers` is left alone.
+pub(crate) fn inject(headers: &mut HeaderMap) {
+    if headers.contains_key(TRACEPARENT) {
+        return;
+    }
+    let context = Context::current();
+    let span = context.span();
+    let span_context = span.span_context();
+    let traceparent = if span_context.is_valid() {
+        let trace_state = span_context.trace_state().header();
+        if !trace_state.is_empty() && !headers.contains_key(TRACESTATE) {
+            if let Ok(value) = HeaderValue::from_str(&trace_state) {
+                headers.insert(TRACESTATE, value);
+            }
+        }
+        format!("00-{}-{}-{:02x}", span_context.trace_id(), span_context.span_id(), span_context.trace_flags())
+    } else {
+        // All-zero ids are invalid, so draw from 1 upwards.
+        let trace_id = fastrand::u128(1..);
+        let span_id = fastrand::u64(1..);
+        format!("00-{:032x}-{:016x}-{:02x}", trace_id, span_id, TraceFlags::SAMPLED)
+    };
+    if let Ok(value) = HeaderValue::from_str(&traceparent) {
+        headers.insert(TRACEPARENT, value);
+    }
+}
+
+/// Starts the client span of one `invoke` as a child of the current span, with the HTTP client
+/// semantic convention attributes known before sending. Injected trace context points at it.
+pub(crate) fn start_invoke_span(method: &str, url: Option<Url>) -> Context {
+    let mut attributes = vec![KeyValue::new("http.request.method", method.to_string())];
+    if let Some(mut url) = url {
+        let _ = url.set_username("");
+        let _ = url.set_password(None);
+        if let Some(host) = url.host_str() {
+            attributes.push(KeyValue::new("server.address", host.to_string()));
+        }
+        if let Some(port) = url.port_or_known_default() {
+            attributes.push(KeyValue::new("server.port", i64::from(port)));
+        }
+        attributes.push(KeyValue::new("url.full", url.to_string()));
+    }
+    let tracer = global::tracer(env!("CARGO_PKG_NAME"));
+    let span = tracer
+        .span_builder(method.to_string())
+        .with_kind(SpanKind::Client)
+        .with_attributes(attributes)
+        .start(&tracer);
+    Context::current_with_span(span)
+}
+
+/// Adds a `retry` event to the current invoke span.
+pub(crate) fn record_retry(attempt: u32, status: Option<u16>, delay: Duration) {
+    let context = Context::current();
+    let span = context.span();
+    let mut attributes = vec![
+        KeyValue::new("http.request.resend_count", i64::from(attempt)),
+        KeyValue::new("retry.delay_ms", delay.as_millis() as i64),
+    ];
+    if let Some(status) = status {
+        attributes.push(KeyValue::new("http.response.status_code", i64::from(status)));
+    }
+    span.add_event("retry", attributes);
+    span.set_attribute(KeyValue::new("http.request.resend_count", i64::from(attempt)));
+}
+
+pub(crate) fn end_invoke_span(context: &Context, result: &Result<FunctionsResponse, FunctionsError>) {
+    let span = context.span();
+    let error = match result {
+        Ok(FunctionsResponse::Success { metadata, .. }) | Ok(FunctionsResponse::NotModified { metadata }) => {
+            span.set_attribute(KeyValue::new("http.response.status_code", i64::from(metadata.status)));
+            None
+        }
+        Ok(FunctionsResponse::Failure { error }) | Err(error) => Some(error),
+    };
+    if let Some(error) = error {
+        let error_type = match http_status(error) {
+            Some(status) => {
+                span.set_attribute(KeyValue::new("http.response.status_code", i64::from(status)));
+                status.to_string()
+            }
+            // The same stable code the serialized error carries.
+            None => error.code().to_string(),
+        };
+        span.set_attribute(KeyValue::new("error.type", error_type));
+        span.set_status(Status::error(error.to_string()));
+    }
+    span.end();
+}
+
+fn http_status(error: &FunctionsError) -> Option<u16> {
+    match error {
+        FunctionsError::HttpError { status, .. } => Some(*status),
+        FunctionsError::RetriesExhausted { last_error, .. } => http_status(last_error),
+        _ => None,
+    }
+}
@@ -0,0 +1,78 @@
+use crate::errors::{ConnectErrorKind, ErrorContext, FunctionsError, FunctionsRelayError, TimeoutKind};
+use crate::models::ResponseMetadata;
+use bytes::Bytes;
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use reqwest::Url;
+use std::time::{Duration, SystemTime};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::{self, http};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// A WebSocket connection to a function, from
+/// [`FunctionsClient::connect_websocket`](crate::FunctionsClient::connect_websocket). It is a
+/// `Stream` of incoming and a `Sink` of outgoing [`WsMessage`]s; `StreamExt::split` separates
+/// the two halves.
+pub type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+pub use tungstenite::Message as WsMessage;
+
+/// Opens `url` as `ws://` or `wss://` and performs the upgrade handshake with `headers`.
+pub(crate) async fn connect(
+    function_name: &str,
+    mut url: Url,
+    headers: HeaderMap,
+    timeout: Option<Duration>,
+) -> Result<WsStream, FunctionsError> {
+    let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+    url.set_scheme(scheme).map_err(|()| FunctionsError::InvalidUrl(format!("{}: can't be opened as a WebSocket", url)))?;
+    let mut request = url.as_str().into_client_request().map_err(|error| handshake_error(function_name, &url, error))?;
+    request.headers_mut().extend(headers);
+
+    let connect = tokio_tungstenite::connect_async(request);
+    let connected = match timeout {
+        Some(limit) => tokio::time::timeout(limit, connect).await.map_err(|_| FunctionsError::Timeout {
+            kind: TimeoutKind::Attempt,
+            limit: Some(limit),
+            elapsed: limit,
+            attempt: 1,
+            max_attempts: 1,
+        })?,
+        None => connect.await,
+    };
+    match connected {
+        Ok((stream, _)) => Ok(stream),
+        Err(error) => Err(handshake_error(function_name, &url, error)),
+    }
+}
+
+// A function that answered without upgrading is reported like any other response it sends.
+fn handshake_error(function_name: &str, url: &Url, error: tungstenite::Error) -> FunctionsError {
+    let response = match error {
+        tungstenite::Error::Http(response) => response,
+        tungstenite::Error::Io(error) if error.kind() == std::io::ErrorKind::ConnectionRefused => {
+            return FunctionsError::ConnectError { kind: ConnectErrorKind::ConnectionRefused, message: error.to_string() };
+        }
+        error => return FunctionsError::FetchError(format!("WebSocket handshake failed: {}", error)),
+    };
+    let (parts, body): (http::response::Parts, _) = response.into_parts();
+    let body = Bytes::from(body.unwrap_or_default());
+    let status = parts.status.as_u16();
+    if parts.headers.get("x-relay-error").is_some_and(|v| v == "true") {
+        return FunctionsError::RelayError(FunctionsRelayError {
+            message: format!("Relay Error invoking the Edge Function at {}", url),
+            context: Some(Box::new(ErrorContext { status, headers: parts.headers, body, url: Some(url.clone()) })),
+        });
+    }
+    let metadata = ResponseMetadata {
+        url: Some(url.clone()),
+        status,
+        version: parts.version,
+        content_type: parts.headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(str::to_string),
+        content_length: Some(body.len() as u64),
+        headers: parts.headers,
+        received_at: Some(SystemTime::now()),
+        ..Default::default()
+    };
+    FunctionsError::HttpError { function_name: function_name.to_string(), status, body, metadata: Box::new(metadata) }
+}
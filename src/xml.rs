@@ -0,0 +1,74 @@
+use crate::errors::{preview, FunctionsError};
+use crate::models::{FunctionInvokeOptions, ResponseData};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// How much of the document around a parse error goes into the error message.
+const SNIPPET_LEN: usize = 60;
+
+impl FunctionInvokeOptions {
+    /// A `POST` with an `application/xml` body.
+    ///
+    /// ```
+    /// use supabase_function_rs::{FunctionInvokeOptions, InvokeBody};
+    ///
+    /// let options = FunctionInvokeOptions::xml("<ping/>");
+    /// assert!(matches!(options.body, Some(InvokeBody::String(ref s)) if s == "<ping/>"));
+    /// assert_eq!(options.headers.unwrap()["Content-Type"], "application/xml");
+    /// ```
+    pub fn xml(xml: impl Into<String>) -> Self {
+        let headers = [("Content-Type".to_string(), "application/xml".to_string())].into();
+        Self { headers: Some(headers), ..Self::text(xml) }
+    }
+
+    /// A `POST` with `value` serialized as an XML document; the root element is named after its type.
+    pub fn xml_from<T: Serialize>(value: &T) -> Result<Self, FunctionsError> {
+        let xml = quick_xml::se::to_string(value).map_err(|e| FunctionsError::BuildError(format!("invalid XML body: {}", e)))?;
+        Ok(Self::xml(xml))
+    }
+}
+
+impl ResponseData {
+    /// Deserializes an XML response into `T`. Works on `Text` and on UTF-8 `Bytes`. The
+    /// `DeserializeError` of a malformed document quotes it around the offending spot.
+    pub fn xml_into<T: DeserializeOwned>(&self) -> Result<T, FunctionsError> {
+        let xml = match self {
+            ResponseData::Text(text) => text.as_str(),
+            ResponseData::Bytes(bytes) => std::str::from_utf8(bytes)
+                .map_err(|e| FunctionsError::DeserializeError(format!("XML body is not UTF-8: {}", e)))?,
+            other => return Err(FunctionsError::DeserializeError(format!("expected an XML document, got {:?}", other))),
+        };
+        quick_xml::de::from_str(xml).map_err(|error| {
+            let snippet = snippet(xml, syntax_error_position(xml).unwrap_or(0));
+            FunctionsError::DeserializeError(format!("invalid XML: {} near `{}`", error, snippet))
+        })
+    }
+}
+
+/// `application/xml`, `text/xml` and any `+xml` type such as `application/soap+xml`.
+pub(crate) fn is_xml(media_type: &str) -> bool {
+    matches!(media_type, "application/xml" | "text/xml") || media_type.ends_with("+xml")
+}
+
+// Where a well-formedness error starts, or `None` when the document is well-formed and only
+// its shape didn't fit the target type.
+fn syntax_error_position(xml: &str) -> Option<usize> {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) => return None,
+            Ok(_) => {}
+            Err(_) => return usize::try_from(reader.error_position()).ok(),
+        }
+    }
+}
+
+fn snippet(xml: &str, position: usize) -> String {
+    let mut start = position.saturating_sub(SNIPPET_LEN / 2).min(xml.len());
+    while !xml.is_char_boundary(start) {
+        start -= 1;
+    }
+    preview(&xml[start..], SNIPPET_LEN)
+}
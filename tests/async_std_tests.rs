@@ -0,0 +1,71 @@
+#![cfg(feature = "async-std")]
+
+mod async_std_tests {
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use supabase_function_rs::{
+        FunctionInvokeOptions, FunctionsClient, FunctionsResponse, InvokeBody, ResponseData, RetryPolicy,
+    };
+
+    fn bodies() -> Vec<(InvokeBody, Matcher)> {
+        let mut json_body = HashMap::new();
+        json_body.insert("key".to_string(), json!("value"));
+        #[allow(unused_mut)]
+        let mut bodies = vec![
+            (InvokeBody::File(b"file".to_vec()), Matcher::Exact("file".into())),
+            (InvokeBody::Blob(b"blob".to_vec()), Matcher::Exact("blob".into())),
+            (InvokeBody::ArrayBuffer(b"buffer".to_vec()), Matcher::Exact("buffer".into())),
+            (InvokeBody::Json(json_body), Matcher::Json(json!({"key": "value"}))),
+            (InvokeBody::String("text".into()), Matcher::Exact("text".into())),
+        ];
+        #[cfg(feature = "multipart")]
+        {
+            let mut form = HashMap::new();
+            form.insert("field".to_string(), "value".to_string());
+            bodies.insert(3, (InvokeBody::FormData(form.into()), Matcher::Regex("name=\"field\"\r\n\r\nvalue".into())));
+        }
+        bodies
+    }
+
+    #[test]
+    fn test_invoke_all_body_types_on_async_std() {
+        async_std::task::block_on(async {
+            let client = FunctionsClient::new(mockito::server_url(), None, None);
+            for (body, matcher) in bodies() {
+                let _m = mock("POST", "/function-name")
+                    .match_body(matcher)
+                    .with_status(200)
+                    .with_header("content-type", "application/json")
+                    .with_body(r#"{"ok": true}"#)
+                    .create();
+
+                let options = FunctionInvokeOptions { body: Some(body.clone()), ..Default::default() };
+                match client.invoke("function-name", Some(options)).await {
+                    Ok(FunctionsResponse::Success { data: ResponseData::Json(json), .. }) => {
+                        assert_eq!(json, json!({"ok": true}));
+                    }
+                    other => panic!("Expected JSON response for {:?}, got {:?}", body, other),
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_retry_backoff_and_timeout_on_async_std() {
+        async_std::task::block_on(async {
+            let _failure = mock("POST", "/function-name").with_status(503).expect(1).create();
+            let _success = mock("POST", "/function-name").with_status(200).with_header("content-type", "text/plain").with_body("ok").create();
+
+            let mut client = FunctionsClient::new(mockito::server_url(), None, None);
+            client.set_timeout(Duration::from_secs(5));
+            client.set_retry_policy(RetryPolicy::new(2).backoff(Duration::from_millis(10), Duration::from_millis(10)));
+
+            match client.invoke("function-name", None).await {
+                Ok(FunctionsResponse::Success { data: ResponseData::Text(text), .. }) => assert_eq!(text, "ok"),
+                other => panic!("Expected success after retry, got {:?}", other),
+            }
+        });
+    }
+}
@@ -0,0 +1,57 @@
+mod base64_tests {
+    use serde_json::json;
+    use supabase_function_rs::{FunctionsError, ResponseData};
+
+    fn decode(text: &str) -> Result<Vec<u8>, FunctionsError> {
+        ResponseData::Text(text.into()).decode_base64().map(|bytes| bytes.to_vec())
+    }
+
+    fn error(result: Result<Vec<u8>, FunctionsError>) -> String {
+        match result {
+            Err(FunctionsError::DeserializeError(message)) => message,
+            other => panic!("Expected DeserializeError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_base64_alphabets_and_padding() {
+        // 0xfb 0xff encodes to `+/8` in the standard alphabet and `-_8` in the URL-safe one.
+        assert_eq!(decode("+/8=").unwrap(), [0xfb, 0xff]);
+        assert_eq!(decode("-_8=").unwrap(), [0xfb, 0xff]);
+        assert_eq!(decode("-_8").unwrap(), [0xfb, 0xff]);
+
+        assert_eq!(decode("").unwrap(), b"");
+        assert_eq!(decode("aGk=").unwrap(), b"hi");
+        assert_eq!(decode("aA==").unwrap(), b"h");
+        assert_eq!(decode("aA").unwrap(), b"h");
+        assert_eq!(decode("aGV5").unwrap(), b"hey");
+        assert_eq!(decode("aGVs\r\n bG8g\td29y\nbGQ=\n").unwrap(), b"hello world");
+        let json = ResponseData::Json(json!("aGk="));
+        assert_eq!(json.decode_base64().unwrap(), "hi");
+    }
+
+    #[test]
+    fn test_invalid_base64_reports_offset() {
+        assert_eq!(error(decode("aGVs bG8*")), "invalid base64 at offset 8: unexpected '*'");
+        assert_eq!(error(decode("aGVsé")), "invalid base64 at offset 4: unexpected 'é'");
+        assert_eq!(error(decode("aG=k")), "invalid base64 at offset 3: unexpected 'k'");
+        assert_eq!(error(decode("a===")), "invalid base64 at offset 1: unexpected '='");
+        assert_eq!(error(decode("aA=")), "invalid base64 at offset 3: incomplete padding");
+        assert_eq!(error(decode("aGVsb")), "invalid base64 at offset 5: input ends mid-byte");
+        assert!(ResponseData::Bytes("aGk=".into()).decode_base64().is_err());
+    }
+
+    #[test]
+    fn test_decode_base64_field() {
+        let data = ResponseData::Json(json!({"name": "logo.png", "content": "iVBO\nRw0K", "size": 6}));
+        assert_eq!(data.decode_base64_field("content").unwrap(), &[0x89, b'P', b'N', b'G', b'\r', b'\n'][..]);
+
+        let message = |result: Result<_, FunctionsError>| match result {
+            Err(FunctionsError::DeserializeError(message)) => message,
+            other => panic!("Expected DeserializeError, got {:?}", other),
+        };
+        assert_eq!(message(data.decode_base64_field("size")), "field `size` is not a string: 6");
+        assert_eq!(message(data.decode_base64_field("missing")), "no field `missing` in the JSON object");
+        assert_eq!(message(data.decode_base64_field("name")), "field `name`: invalid base64 at offset 4: unexpected '.'");
+    }
+}
@@ -0,0 +1,78 @@
+mod common;
+
+mod base_url_tests {
+    use crate::common;
+    use mockito::{mock, Matcher};
+    use std::collections::HashMap;
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient, FunctionsError};
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok";
+
+    fn tenant_client() -> FunctionsClient {
+        let headers = HashMap::from([("x-client-info".to_string(), "backfill".to_string())]);
+        let client = FunctionsClient::new(mockito::server_url(), Some(headers), None);
+        client.set_api_key("tenant-a-key");
+        client.set_auth("tenant-a-key").unwrap();
+        client
+    }
+
+    #[tokio::test]
+    async fn test_one_client_reaches_two_projects() {
+        let tenant_a = mock("POST", "/report")
+            .match_header("authorization", "Bearer tenant-a-key")
+            .match_header("x-client-info", "backfill")
+            .with_status(200)
+            .expect(1)
+            .create();
+        let (tenant_b_url, requests) = common::serve_recorded(vec![OK.to_string()]);
+
+        let client = tenant_client();
+        client.invoke("report", None).await.unwrap();
+
+        let options = FunctionInvokeOptions {
+            base_url: Some(format!("{}/functions/v1", tenant_b_url)),
+            headers: Some(HashMap::from([("Authorization".to_string(), "Bearer tenant-b-key".to_string())])),
+            ..Default::default()
+        };
+        assert!(client.invoke("report", Some(options)).await.unwrap().is_success());
+
+        tenant_a.assert();
+        let requests = requests.lock().unwrap();
+        let head = requests[0].to_ascii_lowercase();
+        assert!(head.starts_with("post /functions/v1/report "), "{}", head);
+        assert!(head.contains("authorization: bearer tenant-b-key"), "{}", head);
+        assert!(head.contains("x-client-info: backfill"), "{}", head);
+        assert!(!head.contains("tenant-a-key"), "{}", head);
+    }
+
+    #[tokio::test]
+    async fn test_same_origin_override_keeps_credentials() {
+        let v2 = mock("POST", "/functions/v2/report")
+            .match_header("authorization", "Bearer tenant-a-key")
+            .match_header("apikey", "tenant-a-key")
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let options = FunctionInvokeOptions {
+            base_url: Some(format!("{}/functions/v2", mockito::server_url())),
+            ..Default::default()
+        };
+        tenant_client().invoke("report", Some(options)).await.unwrap();
+        v2.assert();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_override_is_rejected() {
+        let m = mock("POST", Matcher::Any).expect(0).create();
+
+        for base_url in ["not a url", "ftp://example.com/functions/v1"] {
+            let options = FunctionInvokeOptions { base_url: Some(base_url.to_string()), ..Default::default() };
+            match tenant_client().invoke("report", Some(options)).await {
+                Err(FunctionsError::InvalidUrl(_)) => {}
+                other => panic!("Expected InvalidUrl for {}, got {:?}", base_url, other),
+            }
+        }
+        m.assert();
+    }
+}
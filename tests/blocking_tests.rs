@@ -0,0 +1,35 @@
+#![cfg(feature = "blocking")]
+
+mod blocking_tests {
+    use mockito::mock;
+    use supabase_function_rs::{FunctionsClient, FunctionsError, FunctionsResponse, ResponseData};
+
+    #[test]
+    fn test_invoke_blocking_without_runtime() {
+        let _m = mock("POST", "/function-name")
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_body("ok")
+            .expect(2)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        // The second call reuses the runtime created by the first.
+        for _ in 0..2 {
+            match client.invoke_blocking("function-name", None) {
+                Ok(FunctionsResponse::Success { data: ResponseData::Text(text), .. }) => assert_eq!(text, "ok"),
+                other => panic!("Expected text response, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invoke_blocking_inside_runtime_is_an_error() {
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        match client.invoke_blocking("function-name", None) {
+            Err(FunctionsError::BlockingInAsyncContext) => {}
+            other => panic!("Expected BlockingInAsyncContext, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+mod cache_tests {
+    use futures_util::future::join_all;
+    use mockito::mock;
+    use std::time::Duration;
+    use supabase_function_rs::{
+        CachePolicy, FunctionInvokeOptions, FunctionsClient, FunctionsResponse, HttpMethod, ResponseData,
+    };
+
+    fn get() -> Option<FunctionInvokeOptions> {
+        Some(FunctionInvokeOptions { method: Some(HttpMethod::Get), ..Default::default() })
+    }
+
+    fn cached_client(policy: CachePolicy) -> FunctionsClient {
+        FunctionsClient::builder(mockito::server_url()).cache(policy).build().unwrap()
+    }
+
+    fn text(response: FunctionsResponse) -> String {
+        match response.into_result().unwrap() {
+            ResponseData::Text(text) => text,
+            other => panic!("Expected text, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_is_served_from_cache_within_ttl() {
+        let pricing = mock("GET", "/pricing").with_status(200).with_header("content-type", "text/plain").with_body("v1").expect(1).create();
+
+        let client = cached_client(CachePolicy::new(Duration::from_secs(60)));
+        assert_eq!(text(client.invoke("pricing", get()).await.unwrap()), "v1");
+        assert_eq!(text(client.clone().invoke("pricing", get()).await.unwrap()), "v1");
+        pricing.assert();
+    }
+
+    #[tokio::test]
+    async fn test_entry_is_refreshed_after_ttl() {
+        let first = mock("GET", "/pricing").with_status(200).with_header("content-type", "text/plain").with_body("v1").expect(1).create();
+        let second = mock("GET", "/pricing").with_status(200).with_header("content-type", "text/plain").with_body("v2").expect(1).create();
+
+        let client = cached_client(CachePolicy::new(Duration::from_millis(50)));
+        assert_eq!(text(client.invoke("pricing", get()).await.unwrap()), "v1");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(text(client.invoke("pricing", get()).await.unwrap()), "v2");
+        assert_eq!(text(client.invoke("pricing", get()).await.unwrap()), "v2");
+        first.assert();
+        second.assert();
+    }
+
+    #[tokio::test]
+    async fn test_post_is_cached_only_when_enabled() {
+        let uncached = mock("POST", "/flags").with_status(200).with_body("on").expect(2).create();
+        let client = cached_client(CachePolicy::new(Duration::from_secs(60)));
+        for _ in 0..2 {
+            client.invoke("flags", Some(FunctionInvokeOptions::text("user-1"))).await.unwrap();
+        }
+        uncached.assert();
+
+        let cached = mock("POST", "/flags").with_status(200).with_body("on").expect(2).create();
+        let client = cached_client(CachePolicy::new(Duration::from_secs(60)).cache_post(true));
+        for body in ["user-1", "user-1", "user-2"] {
+            client.invoke("flags", Some(FunctionInvokeOptions::text(body))).await.unwrap();
+        }
+        cached.assert();
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_and_clear() {
+        let pricing = mock("GET", "/pricing").with_status(200).with_header("content-type", "text/plain").with_body("v1").expect(2).create();
+        let flags = mock("GET", "/flags").with_status(200).with_body("on").expect(2).create();
+
+        let client = cached_client(CachePolicy::new(Duration::from_secs(60)));
+        client.invoke("pricing", get()).await.unwrap();
+        client.invoke("flags", get()).await.unwrap();
+
+        client.invalidate("pricing");
+        client.invoke("pricing", get()).await.unwrap();
+        client.invoke("flags", get()).await.unwrap();
+
+        client.clear_cache();
+        client.invoke("flags", get()).await.unwrap();
+        pricing.assert();
+        flags.assert();
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_not_cached() {
+        let failing = mock("GET", "/pricing").with_status(500).expect(1).create();
+        let ok = mock("GET", "/pricing").with_status(200).with_header("content-type", "text/plain").with_body("v1").expect(1).create();
+
+        let client = cached_client(CachePolicy::new(Duration::from_secs(60)));
+        assert!(client.invoke("pricing", get()).await.is_err());
+        assert_eq!(text(client.invoke("pricing", get()).await.unwrap()), "v1");
+        assert_eq!(text(client.invoke("pricing", get()).await.unwrap()), "v1");
+        failing.assert();
+        ok.assert();
+    }
+
+    #[tokio::test]
+    async fn test_max_entries_evicts_oldest() {
+        let a = mock("GET", "/a").with_status(200).with_body("a").expect(2).create();
+        let b = mock("GET", "/b").with_status(200).with_body("b").expect(1).create();
+
+        let client = cached_client(CachePolicy::new(Duration::from_secs(60)).max_entries(1));
+        client.invoke("a", get()).await.unwrap();
+        client.invoke("b", get()).await.unwrap();
+        client.invoke("b", get()).await.unwrap();
+        client.invoke("a", get()).await.unwrap();
+        a.assert();
+        b.assert();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_misses_are_coalesced() {
+        let pricing = mock("GET", "/pricing").with_status(200).with_header("content-type", "text/plain").with_body("v1").expect(1).create();
+
+        let client = cached_client(CachePolicy::new(Duration::from_secs(60)));
+        let responses = join_all((0..5).map(|_| client.invoke("pricing", get()))).await;
+        for response in responses {
+            assert_eq!(text(response.unwrap()), "v1");
+        }
+        pricing.assert();
+    }
+
+    #[tokio::test]
+    async fn test_debug_output_leaves_out_the_cached_request() {
+        let _account = mock("GET", "/account").with_status(200).with_body("ok").create();
+
+        let client = cached_client(CachePolicy::new(Duration::from_secs(60)));
+        let options = FunctionInvokeOptions { method: Some(HttpMethod::Get), ..Default::default() }.header("x-session", "session-secret");
+        client.invoke("account", Some(options)).await.unwrap();
+
+        let debug = format!("{:?}", client);
+        assert!(debug.contains("\"account\""), "{}", debug);
+        assert!(!debug.contains("session-secret"), "{}", debug);
+    }
+}
@@ -0,0 +1,97 @@
+mod cancellation_tests {
+    use futures_util::StreamExt;
+    use mockito::mock;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+        use std::thread;
+    use std::time::{Duration, Instant};
+    use supabase_function_rs::{FunctionsClient, RetryPolicy};
+    use tokio::sync::oneshot;
+
+    /// Accepts one connection, optionally writes `head`, then reports when the client hangs up.
+    fn serve_until_closed(head: &'static str) -> (String, oneshot::Receiver<Instant>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = oneshot::channel();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+                line.clear();
+            }
+            reader.get_mut().write_all(head.as_bytes()).unwrap();
+            let mut buf = [0; 64];
+            while reader.read(&mut buf).map(|n| n > 0).unwrap_or(false) {}
+            let _ = tx.send(Instant::now());
+        });
+        (url, rx)
+    }
+
+    #[tokio::test]
+    async fn test_dropping_invoke_closes_the_connection() {
+        let (url, closed) = serve_until_closed("");
+        let client = FunctionsClient::new(url, None, None);
+
+        tokio::select! {
+            _ = client.invoke("function-name", None) => panic!("the server never answers"),
+            _ = tokio::time::sleep(Duration::from_millis(100)) => {}
+        }
+        let dropped = Instant::now();
+
+        let closed_at = wait_closed(closed).await;
+        assert!(closed_at.duration_since(dropped) < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_dropping_invoke_mid_body_closes_the_connection() {
+        let (url, closed) = serve_until_closed(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: 1000\r\n\r\npartial",
+        );
+        let client = FunctionsClient::new(url, None, None);
+
+        tokio::select! {
+            _ = client.invoke("function-name", None) => panic!("the body never completes"),
+            _ = tokio::time::sleep(Duration::from_millis(100)) => {}
+        }
+
+        wait_closed(closed).await;
+    }
+
+    #[tokio::test]
+    async fn test_dropping_sse_stream_closes_the_connection() {
+        let (url, closed) = serve_until_closed(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nTransfer-Encoding: chunked\r\n\r\n9\r\ndata: a\n\n\r\n",
+        );
+        let client = FunctionsClient::new(url, None, None);
+
+        let mut events = client.invoke_sse("function-name", None).await.unwrap();
+        assert_eq!(events.next().await.unwrap().unwrap().data, "a");
+        drop(events);
+
+        wait_closed(closed).await;
+    }
+
+    async fn wait_closed(closed: oneshot::Receiver<Instant>) -> Instant {
+        tokio::time::timeout(Duration::from_secs(2), closed)
+            .await
+            .expect("connection left open")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_dropping_invoke_during_backoff_stops_retries() {
+        let m = mock("POST", "/function-name").with_status(503).expect(1).create();
+
+        let mut client = FunctionsClient::new(mockito::server_url(), None, None);
+        client.set_retry_policy(RetryPolicy::new(3).backoff(Duration::from_millis(200), Duration::from_millis(200)));
+
+        tokio::select! {
+            _ = client.invoke("function-name", None) => panic!("still backing off"),
+            _ = tokio::time::sleep(Duration::from_millis(100)) => {}
+        }
+        tokio::time::sleep(Duration::from_millis(400)).await;
+
+        m.assert();
+    }
+}
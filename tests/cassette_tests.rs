@@ -0,0 +1,127 @@
+#![cfg(feature = "record-replay")]
+
+mod cassette_tests {
+    use mockito::mock;
+    use serde_json::json;
+    use std::path::PathBuf;
+    use supabase_function_rs::{Cassette, FunctionInvokeOptions, FunctionsClient, FunctionsError, HttpMethod, ResponseData};
+
+    // Nothing listens here, so replays that touched the network would fail.
+    const OFFLINE_URL: &str = "http://127.0.0.1:9";
+
+    fn cassette_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("supabase-function-rs-{}-{}.json", name, std::process::id()))
+    }
+
+    fn client(url: &str, cassette: Cassette) -> FunctionsClient {
+        FunctionsClient::builder(url).header("Authorization", "Bearer secret-token").cassette(cassette).build().unwrap()
+    }
+
+    fn get() -> FunctionInvokeOptions {
+        FunctionInvokeOptions { method: Some(HttpMethod::Get), ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay_offline() {
+        let path = cassette_path("roundtrip");
+        {
+            let _hello = mock("POST", "/hello")
+                .match_body(r#"{"name":"Ada"}"#)
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(r#"{"greeting":"Hi Ada"}"#)
+                .expect(1)
+                .create();
+            let _image = mock("GET", "/image")
+                .with_status(200)
+                .with_header("content-type", "application/octet-stream")
+                .with_body([0, 159, 255])
+                .expect(1)
+                .create();
+            let _missing = mock("GET", "/missing").with_status(404).with_body("gone").expect(1).create();
+
+            let client = client(&mockito::server_url(), Cassette::record(&path));
+            client.invoke("hello", Some(FunctionInvokeOptions::json([("name", json!("Ada"))]))).await.unwrap();
+            client.invoke("image", Some(get())).await.unwrap();
+            client.invoke("missing", Some(get())).await.unwrap_err();
+        }
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("secret-token"));
+        assert!(contents.contains(r#""body": "{\"greeting\":\"Hi Ada\"}""#));
+
+        let client = client(OFFLINE_URL, Cassette::replay(&path).unwrap());
+        let response = client.invoke("hello", Some(FunctionInvokeOptions::json([("name", json!("Ada"))]))).await.unwrap();
+        assert_eq!(response.data(), Some(&ResponseData::Json(json!({"greeting": "Hi Ada"}))));
+        assert_eq!(response.metadata().unwrap().url.as_ref().unwrap().as_str(), format!("{}/hello", OFFLINE_URL));
+        let response = client.invoke("image", Some(get())).await.unwrap();
+        assert_eq!(response.data(), Some(&ResponseData::Bytes(vec![0, 159, 255].into())));
+        match client.invoke("missing", Some(get())).await {
+            Err(FunctionsError::HttpError { status: 404, body, .. }) => assert_eq!(body, "gone"),
+            other => panic!("Expected the recorded 404, got {:?}", other),
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unmatched_request_reports_closest_difference() {
+        let path = cassette_path("unmatched");
+        {
+            let _m = mock("POST", "/greet").with_status(200).with_body("ok").create();
+            let client = client(&mockito::server_url(), Cassette::record(&path));
+            client.invoke("greet", Some(FunctionInvokeOptions::text("first"))).await.unwrap();
+            client.invoke("unknown", Some(get())).await.unwrap_err();
+        }
+
+        let client = client(OFFLINE_URL, Cassette::replay(&path).unwrap());
+        let error = client.invoke("greet", Some(FunctionInvokeOptions::text("second"))).await.unwrap_err();
+        match error {
+            FunctionsError::FetchError(message) => assert_eq!(
+                message,
+                format!(
+                    "no interaction in cassette {} matches POST /greet; the closest, #1, differs in:\n  body:\n    - \"first\"\n    + \"second\"",
+                    path.display()
+                )
+            ),
+            other => panic!("Expected FetchError, got {:?}", other),
+        }
+
+        // Without body matching the same request replays.
+        let client = self::client(OFFLINE_URL, Cassette::replay(&path).unwrap().match_body(false));
+        client.invoke("greet", Some(FunctionInvokeOptions::text("second"))).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_header_matching_and_replay_order() {
+        let path = cassette_path("headers");
+        {
+            let _first = mock("GET", "/counter").match_header("x-tenant", "a").with_status(200).with_body("1").expect(1).create();
+            let client = client(&mockito::server_url(), Cassette::record(&path));
+            let mut options = get();
+            options.headers = Some([("x-tenant".to_string(), "a".to_string())].into());
+            client.invoke("counter", Some(options.clone())).await.unwrap();
+            drop(_first);
+            let _second = mock("GET", "/counter").with_status(200).with_body("2").expect(1).create();
+            client.invoke("counter", Some(options)).await.unwrap();
+        }
+
+        let client = client(OFFLINE_URL, Cassette::replay(&path).unwrap().match_headers(["X-Tenant"]));
+        let mut options = get();
+        options.headers = Some([("x-tenant".to_string(), "a".to_string())].into());
+        let bodies: Vec<_> = [
+            client.invoke("counter", Some(options.clone())).await.unwrap(),
+            client.invoke("counter", Some(options.clone())).await.unwrap(),
+            // Every match is used, so the first is reused.
+            client.invoke("counter", Some(options)).await.unwrap(),
+        ]
+        .iter()
+        .map(|response| response.data().cloned())
+        .collect();
+        assert_eq!(bodies, [Some(ResponseData::Bytes("1".into())), Some(ResponseData::Bytes("2".into())), Some(ResponseData::Bytes("1".into()))]);
+
+        let error = client.invoke("counter", Some(get())).await.unwrap_err();
+        assert!(error.to_string().contains("header x-tenant:\n    - \"a\"\n    + (none)"), "{}", error);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
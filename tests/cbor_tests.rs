@@ -0,0 +1,79 @@
+#![cfg(feature = "cbor")]
+
+mod cbor_tests {
+    use mockito::mock;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient, FunctionsError, InvokeBody, ResponseData};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Reading {
+        sensor: String,
+        // Integer keys stay integers in CBOR; JSON would turn them into strings.
+        samples: BTreeMap<u16, f32>,
+    }
+
+    fn reading() -> Reading {
+        Reading { sensor: "porch".into(), samples: [(1, 20.5), (2, 21.0)].into() }
+    }
+
+    fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(value, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[tokio::test]
+    async fn test_cbor_round_trip() {
+        let encoded = encode(&reading());
+        let _m = mock("POST", "/cbor-echo")
+            .match_header("content-type", "application/cbor")
+            .with_status(200)
+            .with_header("content-type", "application/cbor")
+            .with_body(&encoded)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let options = FunctionInvokeOptions { body: Some(InvokeBody::cbor(&reading()).unwrap()), ..Default::default() };
+        let prepared = client.build_request("cbor-echo", Some(options.clone())).await.unwrap();
+        assert_eq!(prepared.body.as_deref(), Some(encoded.as_slice()));
+
+        let response = client.invoke("cbor-echo", Some(options)).await.unwrap();
+        assert_eq!(response.data(), Some(&ResponseData::Bytes(encoded.into())));
+        assert_eq!(response.deserialize_into::<Reading>().unwrap(), reading());
+    }
+
+    #[test]
+    fn test_integer_keys_encode_as_integers() {
+        let map: BTreeMap<i64, &str> = [(-1, "a"), (1, "b")].into();
+        match InvokeBody::cbor(&map).unwrap() {
+            // A two-entry map, then the keys -1 and 1 as CBOR integers.
+            InvokeBody::Cbor(bytes) => assert_eq!(bytes, [0xa2, 0x20, 0x61, b'a', 0x01, 0x61, b'b']),
+            other => panic!("Expected a CBOR body, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_into_by_content_type() {
+        let _json = mock("GET", "/cbor-json")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sensor": "porch", "samples": {}}"#)
+            .create();
+        let _broken = mock("GET", "/cbor-broken")
+            .with_status(200)
+            .with_header("content-type", "application/cbor")
+            .with_body([0xa2, 0x01])
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let get = || FunctionInvokeOptions { method: Some(supabase_function_rs::HttpMethod::Get), ..Default::default() };
+        let reading: Reading = client.invoke("cbor-json", Some(get())).await.unwrap().deserialize_into().unwrap();
+        assert_eq!(reading, Reading { sensor: "porch".into(), samples: BTreeMap::new() });
+
+        match client.invoke("cbor-broken", Some(get())).await.unwrap().deserialize_into::<Reading>() {
+            Err(FunctionsError::DeserializeError(message)) => assert!(message.starts_with("invalid CBOR: "), "{}", message),
+            other => panic!("Expected DeserializeError, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+mod common;
+
+mod channel_tests {
+    use crate::common;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+    use supabase_function_rs::{FunctionsClient, FunctionsError};
+    use tokio::sync::mpsc;
+
+    fn chunked(chunks: &[&str]) -> String {
+        let mut response = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n".to_string();
+        for chunk in chunks {
+            response.push_str(&format!("{:x}\r\n{}\r\n", chunk.len(), chunk));
+        }
+        response + "0\r\n\r\n"
+    }
+
+    #[tokio::test]
+    async fn test_backpressure_and_clean_termination() {
+        let url = common::serve(vec![chunked(&["one", "two", "three", "four", "five"])]);
+        let client = FunctionsClient::new(url, None, None);
+
+        let (tx, mut rx) = mpsc::channel(1);
+        let forwarding = tokio::spawn(async move { client.invoke_to_channel("function-name", None, tx).await });
+
+        // The whole body is on the socket, but only one chunk fits in the channel.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!forwarding.is_finished());
+
+        let mut received = Vec::new();
+        while let Some(chunk) = rx.recv().await {
+            received.push(String::from_utf8(chunk.unwrap().to_vec()).unwrap());
+        }
+        assert_eq!(received.concat(), "onetwothreefourfive");
+        assert!(received.len() > 1);
+        forwarding.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mid_stream_failure_is_the_last_item() {
+        let url = common::serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 100\r\nConnection: close\r\n\r\npartial".to_string(),
+        ]);
+        let client = FunctionsClient::new(url, None, None);
+
+        let (tx, mut rx) = mpsc::channel(1);
+        let forwarding = tokio::spawn(async move { client.invoke_to_channel("function-name", None, tx).await });
+
+        assert_eq!(rx.recv().await.unwrap().unwrap(), "partial");
+        assert!(matches!(rx.recv().await, Some(Err(FunctionsError::FetchError(_)))));
+        assert!(rx.recv().await.is_none());
+        forwarding.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dropping_the_receiver_cancels() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (closed_tx, closed_rx) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            common::read_request(&mut stream);
+            let head = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nfirst\r\n";
+            stream.write_all(head.as_bytes()).unwrap();
+            // Keep the body open until the client hangs up.
+            let _ = stream.peek(&mut [0; 1]);
+            closed_tx.send(()).unwrap();
+        });
+        let client = FunctionsClient::new(url, None, None);
+
+        let (tx, mut rx) = mpsc::channel(1);
+        let forwarding = tokio::spawn(async move { client.invoke_to_channel("function-name", None, tx).await });
+        assert_eq!(rx.recv().await.unwrap().unwrap(), "first");
+        drop(rx);
+
+        tokio::time::timeout(Duration::from_secs(5), forwarding).await.unwrap().unwrap().unwrap();
+        closed_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+}
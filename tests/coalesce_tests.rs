@@ -0,0 +1,57 @@
+mod coalesce_tests {
+    use futures_util::future::join_all;
+    use mockito::mock;
+    use std::time::Duration;
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient, FunctionsError, FunctionsResponse, HttpMethod, ResponseData};
+
+    fn get() -> Option<FunctionInvokeOptions> {
+        Some(FunctionInvokeOptions { method: Some(HttpMethod::Get), ..Default::default() })
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_identical_gets_send_one_request() {
+        let m = mock("GET", "/coalesced-prices")
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_body("42")
+            .expect(2)
+            .create();
+
+        let client = FunctionsClient::builder(mockito::server_url()).coalesce_requests(true).build().unwrap();
+        for _ in 0..2 {
+            let results = join_all((0..20).map(|_| client.invoke("coalesced-prices", get()))).await;
+            for result in results {
+                match result {
+                    Ok(FunctionsResponse::Success { data: ResponseData::Text(text), .. }) => assert_eq!(text, "42"),
+                    other => panic!("Expected text, got {:?}", other),
+                }
+            }
+        }
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_share_errors_and_posts_are_not_coalesced() {
+        let missing = mock("GET", "/coalesced-missing").with_status(404).expect(1).create();
+        let posts = mock("POST", "/coalesced-post").with_status(200).expect(3).create();
+
+        let client = FunctionsClient::builder(mockito::server_url()).coalesce_requests(true).build().unwrap();
+        let results = join_all((0..5).map(|_| client.invoke("coalesced-missing", get()))).await;
+        assert!(results.iter().all(|result| matches!(result, Err(FunctionsError::HttpError { status: 404, .. }))));
+
+        let results = join_all((0..3).map(|_| client.invoke("coalesced-post", None))).await;
+        assert!(results.iter().all(Result::is_ok));
+        missing.assert();
+        posts.assert();
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_leader_does_not_block_later_calls() {
+        let m = mock("GET", "/coalesced-cancel").with_status(200).with_body("ok").expect_at_least(1).create();
+
+        let client = FunctionsClient::builder(mockito::server_url()).coalesce_requests(true).build().unwrap();
+        let _ = tokio::time::timeout(Duration::ZERO, client.invoke("coalesced-cancel", get())).await;
+        assert!(client.invoke("coalesced-cancel", get()).await.is_ok());
+        m.assert();
+    }
+}
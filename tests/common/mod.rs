@@ -0,0 +1,51 @@
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Answers one connection per canned response, in order, for cases mockito can't express.
+/// Responses should carry `Connection: close` so the client doesn't try to reuse the socket.
+pub fn serve(responses: Vec<String>) -> String {
+    serve_recorded(responses).0
+}
+
+/// Reads a full HTTP/1.1 request (head and Content-Length body) and returns the head.
+pub fn read_request(stream: &mut impl Read) -> String {
+    let mut reader = BufReader::new(stream);
+    let mut head = String::new();
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        head.push_str(&line);
+    }
+    let mut body = vec![0; content_length];
+    let _ = reader.read_exact(&mut body);
+    head
+}
+
+/// Like `serve`, but also returns the request heads received so far.
+pub fn serve_recorded(responses: Vec<String>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    thread::spawn(move || {
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let head = read_request(&mut stream);
+            recorded.lock().unwrap().push(head);
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    (url, requests)
+}
@@ -0,0 +1,89 @@
+mod conditional_tests {
+    use mockito::{mock, Matcher};
+    use std::time::Duration;
+    use supabase_function_rs::{
+        CachePolicy, FunctionInvokeOptions, FunctionsClient, FunctionsError, FunctionsResponse, HttpMethod, ResponseData,
+    };
+
+    fn get() -> FunctionInvokeOptions {
+        FunctionInvokeOptions { method: Some(HttpMethod::Get), ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn test_if_none_match_returns_not_modified() {
+        let first = mock("GET", "/report")
+            .match_header("if-none-match", Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", "\"v1\"")
+            .with_header("content-type", "text/plain")
+            .with_body("expensive blob")
+            .expect(1)
+            .create();
+        let second = mock("GET", "/report")
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .with_header("etag", "\"v1\"")
+            .expect(1)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let response = client.invoke("report", Some(get())).await.unwrap();
+        let etag = response.metadata().unwrap().etag().unwrap().to_string();
+        assert_eq!(etag, "\"v1\"");
+
+        let options = FunctionInvokeOptions { if_none_match: Some(etag), ..get() };
+        match client.invoke("report", Some(options)).await {
+            Ok(FunctionsResponse::NotModified { metadata }) => {
+                assert_eq!(metadata.status, 304);
+                assert_eq!(metadata.etag(), Some("\"v1\""));
+            }
+            other => panic!("Expected NotModified, got {:?}", other),
+        }
+        first.assert();
+        second.assert();
+    }
+
+    #[tokio::test]
+    async fn test_if_modified_since_is_sent() {
+        let date = "Wed, 21 Oct 2015 07:28:00 GMT";
+        let report = mock("GET", "/report").match_header("if-modified-since", date).with_status(304).expect(1).create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let options = FunctionInvokeOptions { if_modified_since: Some(date.to_string()), ..get() };
+        let response = client.invoke("report", Some(options)).await.unwrap();
+        assert!(response.is_not_modified());
+        assert!(matches!(response.into_result(), Err(FunctionsError::NotModified(_))));
+        report.assert();
+    }
+
+    #[tokio::test]
+    async fn test_cache_revalidates_expired_entry_with_etag() {
+        let first = mock("GET", "/report")
+            .match_header("if-none-match", Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", "\"v1\"")
+            .with_header("content-type", "text/plain")
+            .with_body("expensive blob")
+            .expect(1)
+            .create();
+        let revalidated = mock("GET", "/report")
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .expect(1)
+            .create();
+
+        let client = FunctionsClient::builder(mockito::server_url())
+            .cache(CachePolicy::new(Duration::from_millis(50)))
+            .build()
+            .unwrap();
+        client.invoke("report", Some(get())).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let data = client.invoke("report", Some(get())).await.unwrap().into_result().unwrap();
+        assert_eq!(data, ResponseData::Text("expensive blob".into()));
+        // The 304 restarted the TTL.
+        client.invoke("report", Some(get())).await.unwrap();
+        first.assert();
+        revalidated.assert();
+    }
+}
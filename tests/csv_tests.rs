@@ -0,0 +1,104 @@
+#![cfg(feature = "csv")]
+
+mod common;
+
+mod csv_tests {
+    use crate::common;
+    use futures_util::StreamExt;
+    use mockito::mock;
+    use serde::Deserialize;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+    use supabase_function_rs::{CsvOptions, FunctionsClient, FunctionsError, ResponseData};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Row {
+        id: u32,
+        name: String,
+        note: String,
+    }
+
+    const FIXTURE: &str = "id,name,note\r\n1,\"Smith, Ada\",\"line one\nline two\"\r\n2,Grace,\"she said \"\"hi\"\"\"\r\n3,Linus,\"trailing, comma,\"\r\n";
+
+    fn rows() -> Vec<Row> {
+        vec![
+            Row { id: 1, name: "Smith, Ada".into(), note: "line one\nline two".into() },
+            Row { id: 2, name: "Grace".into(), note: "she said \"hi\"".into() },
+            Row { id: 3, name: "Linus".into(), note: "trailing, comma,".into() },
+        ]
+    }
+
+    /// Sends `body` with chunked encoding in 5-byte chunks, so quoted fields and line endings
+    /// are split across reads.
+    fn serve_chunked(body: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            common::read_request(&mut stream);
+            let head = "HTTP/1.1 200 OK\r\nContent-Type: text/csv\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n";
+            stream.write_all(head.as_bytes()).unwrap();
+            for chunk in body.as_bytes().chunks(5) {
+                stream.write_all(format!("{:x}\r\n", chunk.len()).as_bytes()).unwrap();
+                stream.write_all(chunk).unwrap();
+                stream.write_all(b"\r\n").unwrap();
+            }
+            stream.write_all(b"0\r\n\r\n").unwrap();
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_csv_records_with_quoted_fields() {
+        let _m = mock("POST", "/csv-export")
+            .with_status(200)
+            .with_header("content-type", "text/csv; charset=utf-8")
+            .with_body(FIXTURE)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let response = client.invoke("csv-export", None).await.unwrap();
+        assert_eq!(response.data().unwrap().csv_records::<Row>().unwrap(), rows());
+
+        // Without a header row, fields map onto the struct by position.
+        let data = ResponseData::Bytes("7;\"a;b\";\"x\ny\"\n".into());
+        let options = CsvOptions::new().delimiter(b';').has_headers(false);
+        assert_eq!(data.csv_records_with::<Row>(options).unwrap(), [Row { id: 7, name: "a;b".into(), note: "x\ny".into() }]);
+
+        match ResponseData::Text("id,name,note\nseven,Ada,\n".into()).csv_records::<Row>() {
+            Err(FunctionsError::DeserializeError(message)) => assert!(message.starts_with("invalid CSV: "), "{}", message),
+            other => panic!("Expected DeserializeError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_csv_stream_across_chunks() {
+        let body = format!("{}{}", FIXTURE, "4,Edsger,\"last, unterminated line\"");
+        let client = FunctionsClient::new(serve_chunked(body), None, None);
+        let results: Vec<_> = client.invoke_csv_stream::<Row>("csv-export", None, CsvOptions::new()).collect().await;
+        let records: Vec<Row> = results.into_iter().map(Result::unwrap).collect();
+        let mut expected = rows();
+        expected.push(Row { id: 4, name: "Edsger".into(), note: "last, unterminated line".into() });
+        assert_eq!(records, expected);
+    }
+
+    #[tokio::test]
+    async fn test_csv_stream_ends_after_first_error() {
+        let _m = mock("POST", "/csv-ragged")
+            .match_header("accept", "text/csv")
+            .with_status(200)
+            .with_header("content-type", "text/csv")
+            .with_body("id,name,note\n1,Ada,hi\n2,Grace\n3,Linus,hey\n")
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let results: Vec<_> = client.invoke_csv_stream::<Row>("csv-ragged", None, CsvOptions::new()).collect().await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap(), &Row { id: 1, name: "Ada".into(), note: "hi".into() });
+        match &results[1] {
+            Err(FunctionsError::DeserializeError(message)) => assert_eq!(message, "CSV record 1: found 2 fields, expected 3"),
+            other => panic!("Expected DeserializeError, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+mod common;
+
+mod default_options_tests {
+    use crate::common;
+    use std::net::TcpListener;
+    use std::time::Duration;
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionRegion, FunctionsClient, FunctionsError, HttpMethod, InvokeBody, TimeoutKind};
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok";
+
+    fn defaults() -> FunctionInvokeOptions {
+        FunctionInvokeOptions {
+            method: Some(HttpMethod::Get),
+            region: Some(FunctionRegion::EuWest1),
+            timeout: Some(Duration::from_millis(300)),
+            ..FunctionInvokeOptions::default().header("X-Tenant", "acme").query_param("version", "2")
+        }
+    }
+
+    fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+        head.lines().filter_map(|line| line.split_once(':')).find(|(field, _)| field.eq_ignore_ascii_case(name)).map(|(_, value)| value.trim())
+    }
+
+    #[tokio::test]
+    async fn test_defaults_apply_under_per_invoke_options() {
+        let (url, requests) = common::serve_recorded(vec![OK.to_string(), OK.to_string()]);
+        let client = FunctionsClient::builder(url).default_options(defaults()).build().unwrap();
+
+        client.invoke("orders", None).await.unwrap();
+        let options = FunctionInvokeOptions { region: Some(FunctionRegion::UsEast1), ..Default::default() };
+        client.invoke("orders", Some(options)).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        for (head, region) in requests.iter().zip(["eu-west-1", "us-east-1"]) {
+            assert!(head.starts_with("GET /orders?version=2 HTTP/1.1\r\n"), "{}", head);
+            assert_eq!(header(head, "x-tenant"), Some("acme"));
+            assert_eq!(header(head, "x-region"), Some(region));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_timeout_and_header_overrides() {
+        // Accepts connections into the backlog but never answers.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = FunctionsClient::builder(format!("http://{}", listener.local_addr().unwrap())).default_options(defaults()).build().unwrap();
+
+        match client.invoke("orders", None).await {
+            Err(FunctionsError::Timeout { kind: TimeoutKind::Attempt, limit, .. }) => assert_eq!(limit, Some(Duration::from_millis(300))),
+            other => panic!("Expected an attempt timeout, got {:?}", other),
+        }
+
+        let (url, requests) = common::serve_recorded(vec![OK.to_string()]);
+        let client = FunctionsClient::builder(url).default_options(defaults()).build().unwrap();
+        let options = FunctionInvokeOptions::default().header("x-tenant", "globex").query_param("version", "3");
+        client.invoke("orders", Some(options)).await.unwrap();
+
+        let head = &requests.lock().unwrap()[0];
+        assert!(head.starts_with("GET /orders?version=3 HTTP/1.1\r\n"), "{}", head);
+        assert_eq!(header(head, "x-tenant"), Some("globex"));
+        assert_eq!(header(head, "x-region"), Some("eu-west-1"));
+    }
+
+    #[tokio::test]
+    async fn test_invoke_can_turn_off_flags_set_by_default() {
+        let (url, requests) = common::serve_recorded(vec![OK.to_string(), OK.to_string()]);
+        let defaults = FunctionInvokeOptions { detect_content_type: Some(true), keep_raw_body: Some(true), ..defaults() };
+        let client = FunctionsClient::builder(url).default_options(defaults).build().unwrap();
+        let body = || Some(InvokeBody::String(r#"{"id":1}"#.to_string()));
+
+        let inherited = FunctionInvokeOptions { method: Some(HttpMethod::Post), body: body(), ..Default::default() };
+        let response = client.invoke("orders", Some(inherited)).await.unwrap();
+        assert_eq!(response.metadata().unwrap().raw_body.as_deref(), Some(b"ok".as_slice()));
+
+        let overridden = FunctionInvokeOptions {
+            method: Some(HttpMethod::Post),
+            body: body(),
+            detect_content_type: Some(false),
+            keep_raw_body: Some(false),
+            ..Default::default()
+        };
+        let response = client.invoke("orders", Some(overridden)).await.unwrap();
+        assert_eq!(response.metadata().unwrap().raw_body, None);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(header(&requests[0], "content-type"), Some("application/json"));
+        assert_eq!(header(&requests[1], "content-type"), Some("text/plain"));
+    }
+}
@@ -0,0 +1,64 @@
+mod dns_tests {
+    use mockito::{mock, Matcher};
+    use std::net::SocketAddr;
+    use supabase_function_rs::{FunctionsClient, FunctionsResponse};
+
+    fn mock_addr() -> SocketAddr {
+        mockito::server_address()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_override_is_applied() {
+        let hello = mock("POST", "/hello")
+            .match_header("host", Matcher::Regex("^functions.test:".into()))
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let client = FunctionsClient::builder(format!("http://functions.test:{}", mock_addr().port()))
+            .resolve("functions.test", mock_addr())
+            .build()
+            .unwrap();
+        match client.invoke("hello", None).await {
+            Ok(FunctionsResponse::Success { .. }) => {}
+            other => panic!("Expected success, got {:?}", other),
+        }
+        hello.assert();
+    }
+
+    #[tokio::test]
+    async fn test_multiple_overrides() {
+        let port = mock_addr().port();
+        let _old = mock("POST", "/old")
+            .with_status(307)
+            .with_header("location", &format!("http://other.test:{}/new", port))
+            .create();
+        let new = mock("POST", "/new")
+            .match_header("host", format!("other.test:{}", port).as_str())
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let unreachable: SocketAddr = "[::1]:9".parse().unwrap();
+        let client = FunctionsClient::builder(format!("http://functions.test:{}", port))
+            .resolve("functions.test", mock_addr())
+            .resolve_to_addrs("other.test", &[unreachable, mock_addr()])
+            .prefer_ipv4(true)
+            .build()
+            .unwrap();
+        client.invoke("old", None).await.unwrap();
+        new.assert();
+    }
+
+    #[tokio::test]
+    async fn test_prefer_ipv4_with_system_resolver() {
+        let hello = mock("POST", "/hello").with_status(200).expect(1).create();
+
+        let client = FunctionsClient::builder(format!("http://localhost:{}", mock_addr().port()))
+            .prefer_ipv4(true)
+            .build()
+            .unwrap();
+        client.invoke("hello", None).await.unwrap();
+        hello.assert();
+    }
+}
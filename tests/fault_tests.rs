@@ -0,0 +1,70 @@
+mod common;
+
+mod fault_tests {
+    use crate::common;
+    use std::time::Duration;
+    use supabase_function_rs::{
+        ConnectErrorKind, Fault, FaultInjector, FaultRule, FunctionsClient, FunctionsError, MockSleeper, ResponseData, RetryPolicy, TimeoutKind,
+    };
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 10\r\nConnection: close\r\n\r\nall synced";
+
+    #[tokio::test]
+    async fn test_first_calls_fail_and_everything_is_slow() {
+        let (url, requests) = common::serve_recorded(vec![OK.to_string(), OK.to_string()]);
+        let faults = FaultInjector::new()
+            .rule(FaultRule::new(Fault::Status(503)).function("sync").first(2))
+            .rule(FaultRule::new(Fault::Latency(Duration::from_millis(200))));
+        let policy = RetryPolicy::new(3).backoff(Duration::from_millis(100), Duration::from_secs(1));
+        let mut client = FunctionsClient::builder(url).retry_policy(policy).fault_injector(faults).build().unwrap();
+        let sleeper = MockSleeper::new();
+        client.set_sleeper(sleeper.clone());
+
+        let response = client.invoke("sync", None).await.unwrap();
+        assert_eq!(response.data(), Some(&ResponseData::Text("all synced".to_string())));
+        let latency = Duration::from_millis(200);
+        assert_eq!(sleeper.slept(), vec![latency, Duration::from_millis(100), latency, Duration::from_millis(200), latency]);
+
+        client.invoke("report", None).await.unwrap();
+        assert_eq!(sleeper.slept().len(), 6);
+        assert_eq!(requests.lock().unwrap().len(), 2, "the failed attempts never reach the server");
+    }
+
+    #[tokio::test]
+    async fn test_faults_fail_through_the_normal_errors() {
+        let url = common::serve(vec![OK.to_string()]);
+        let faults = FaultInjector::new()
+            .rule(FaultRule::new(Fault::ConnectError(ConnectErrorKind::ConnectionRefused)).function("down"))
+            .rule(FaultRule::new(Fault::Latency(Duration::from_secs(5))).function("slow"))
+            .rule(FaultRule::new(Fault::Truncate(3)).function("cut"));
+        let mut client = FunctionsClient::builder(url).timeout(Duration::from_secs(1)).fault_injector(faults).build().unwrap();
+        client.set_sleeper(MockSleeper::new());
+
+        let error = client.invoke("down", None).await.unwrap_err();
+        assert_eq!(error.connect_kind(), Some(ConnectErrorKind::ConnectionRefused));
+        match client.invoke("slow", None).await {
+            Err(FunctionsError::Timeout { kind: TimeoutKind::Attempt, limit, .. }) => assert_eq!(limit, Some(Duration::from_secs(1))),
+            other => panic!("Expected an attempt timeout, got {:?}", other),
+        }
+        assert!(matches!(client.invoke("cut", None).await, Err(FunctionsError::FetchError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_seeded_probability_repeats() {
+        let draws = |seed| {
+            let faults = FaultInjector::new().seed(seed).rule(FaultRule::new(Fault::Status(500)).probability(0.5));
+            let mut client = FunctionsClient::builder("http://127.0.0.1:9").fault_injector(faults).build().unwrap();
+            client.set_sleeper(MockSleeper::new());
+            async move {
+                let mut failed = Vec::new();
+                for _ in 0..16 {
+                    failed.push(matches!(client.invoke("flaky", None).await, Err(FunctionsError::HttpError { status: 500, .. })));
+                }
+                failed
+            }
+        };
+        let first = draws(7).await;
+        assert_eq!(first, draws(7).await);
+        assert!(first.contains(&true) && first.contains(&false), "{:?}", first);
+    }
+}
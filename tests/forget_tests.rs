@@ -0,0 +1,37 @@
+mod common;
+
+mod forget_tests {
+    use crate::common;
+    use std::time::{Duration, Instant};
+    use supabase_function_rs::{FunctionsClient, FunctionsError, RetryPolicy, StatusCode};
+
+    #[tokio::test]
+    async fn test_body_is_not_read() {
+        // Announces a gigabyte but sends a few bytes, so reading the body would never finish.
+        let url = common::serve(vec![
+            "HTTP/1.1 202 Accepted\r\nContent-Type: application/octet-stream\r\nContent-Length: 1073741824\r\n\r\npartial".to_string(),
+        ]);
+        let client = FunctionsClient::builder(url).timeout(Duration::from_secs(5)).build().unwrap();
+
+        let start = Instant::now();
+        assert_eq!(client.invoke_and_forget("import", None).await.unwrap(), StatusCode::ACCEPTED);
+        assert!(start.elapsed() < Duration::from_secs(1), "{:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_error_statuses_are_retried_and_reported() {
+        let url = common::serve(vec![
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+            "HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n".to_string(),
+            "HTTP/1.1 400 Bad Request\r\nContent-Length: 7\r\nConnection: close\r\n\r\ninvalid".to_string(),
+        ]);
+        let mut client = FunctionsClient::new(url, None, None);
+        client.set_retry_policy(RetryPolicy::new(2).backoff(Duration::from_millis(1), Duration::from_millis(1)));
+
+        assert_eq!(client.invoke_and_forget("import", None).await.unwrap(), StatusCode::NO_CONTENT);
+        match client.invoke_and_forget("import", None).await {
+            Err(FunctionsError::HttpError { status, body, .. }) => assert_eq!((status, &body[..]), (400, &b"invalid"[..])),
+            other => panic!("Expected HttpError, got {:?}", other),
+        }
+    }
+}
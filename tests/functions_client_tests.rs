@@ -3,11 +3,16 @@ mod functions_client_tests {
     use std::fs::File;
     use std::io::{Read, Write};
     use std::path::Path;
-    use mockito::mock;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use mockito::{mock, Matcher};
     use serde_json::json;
+    use serde_json::value::RawValue;
+    #[cfg(feature = "multipart")]
+    use supabase_function_rs::{FormFields, FormValue};
     use supabase_function_rs::{
-        FunctionInvokeOptions, FunctionsClient, FunctionsResponse, HttpMethod, InvokeBody,
-        ResponseData, FunctionRegion,
+        FunctionInvokeOptions, FunctionsClient, FunctionsFetchError, FunctionsHttpError, FunctionsRelayError,
+        FunctionsResponse, HttpMethod, InvokeBody, ResponseData, FunctionRegion, FunctionsError, ConnectErrorKind, GatewayInfo, RateLimitInfo,
+        StatusCode, TimeoutKind, Version, LOCAL_ANON_KEY,
     };
 
     #[tokio::test]
@@ -21,21 +26,17 @@ mod functions_client_tests {
         let url = &mockito::server_url();
         println!("Mock server URL: {}", url);
 
-        let mut client = FunctionsClient::new(url.to_string(), None, None);
-        client.set_auth("test-token".to_string());
+        let client = FunctionsClient::new(url.to_string(), None, None);
+        client.set_auth("test-token".to_string()).unwrap();
 
-        let mut invoke_options = FunctionInvokeOptions::default();
-        invoke_options.method = Some(HttpMethod::Post);
-        let mut json_body = HashMap::new();
-        json_body.insert("request_key".to_string(), json!("request_value"));
-        invoke_options.body = Some(InvokeBody::Json(json_body));
+        let invoke_options = FunctionInvokeOptions::json([("request_key", json!("request_value"))]);
         println!("Invoking function with options: {:?}", invoke_options);
 
         match client.invoke("function-name", Some(invoke_options)).await {
             Ok(response) => {
                 println!("Response: {:?}", response);
                 match response {
-                    FunctionsResponse::Success { data } => match data {
+                    FunctionsResponse::Success { data, .. } => match data {
                         ResponseData::Json(json) => {
                             assert_eq!(json["key"], "value");
                         }
@@ -44,6 +45,7 @@ mod functions_client_tests {
                     FunctionsResponse::Failure { error } => {
                         panic!("Expected success, got failure: {}", error);
                     }
+                    FunctionsResponse::NotModified { .. } => panic!("Expected success, got NotModified"),
                 }
             }
             Err(e) => {
@@ -64,22 +66,20 @@ mod functions_client_tests {
         let url = &mockito::server_url();
         println!("Mock server URL: {}", url);
 
-        let mut client = FunctionsClient::new(url.to_string(), None, Some(FunctionRegion::UsEast1));
-        client.set_auth("test-token".to_string());
+        let client = FunctionsClient::new(url.to_string(), None, Some(FunctionRegion::UsEast1));
+        client.set_auth("test-token".to_string()).unwrap();
 
-        let mut invoke_options = FunctionInvokeOptions::default();
-        invoke_options.method = Some(HttpMethod::Post);
-        let mut json_body = HashMap::new();
-        json_body.insert("request_key".to_string(), json!("request_value"));
-        invoke_options.body = Some(InvokeBody::Json(json_body));
-        invoke_options.region = Some(FunctionRegion::UsEast1);
+        let invoke_options = FunctionInvokeOptions {
+            region: Some(FunctionRegion::UsEast1),
+            ..FunctionInvokeOptions::json([("request_key", json!("request_value"))])
+        };
         println!("Invoking function with options: {:?}", invoke_options);
 
         match client.invoke("function-name", Some(invoke_options)).await {
             Ok(response) => {
                 println!("Response: {:?}", response);
                 match response {
-                    FunctionsResponse::Success { data } => match data {
+                    FunctionsResponse::Success { data, .. } => match data {
                         ResponseData::Json(json) => {
                             assert_eq!(json["key"], "value");
                         }
@@ -88,6 +88,7 @@ mod functions_client_tests {
                     FunctionsResponse::Failure { error } => {
                         panic!("Expected success, got failure: {}", error);
                     }
+                    FunctionsResponse::NotModified { .. } => panic!("Expected success, got NotModified"),
                 }
             }
             Err(e) => {
@@ -96,6 +97,28 @@ mod functions_client_tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_custom_region_header() {
+        let _m = mock("POST", "/edge-region")
+            .match_header("x-edge-region", Matcher::Regex("^(us-east-1|eu-west-1)$".into()))
+            .match_header("x-region", Matcher::Missing)
+            .with_status(200)
+            .expect(2)
+            .create();
+
+        let client = FunctionsClient::builder(mockito::server_url())
+            .region(FunctionRegion::UsEast1)
+            .region_header("X-Edge-Region")
+            .build()
+            .unwrap();
+        client.invoke("edge-region", None).await.unwrap();
+        let options = FunctionInvokeOptions { region: Some(FunctionRegion::EuWest1), ..Default::default() };
+        client.invoke("edge-region", Some(options)).await.unwrap();
+
+        let error = FunctionsClient::builder(mockito::server_url()).region_header("bad header").build().unwrap_err();
+        assert!(matches!(error, FunctionsError::InvalidHeader { ref name, .. } if name == "bad header"), "{:?}", error);
+    }
+
     #[tokio::test]
     async fn test_invoke_with_text_body() {
         let _m = mock("POST", "/function-name")
@@ -107,19 +130,17 @@ mod functions_client_tests {
         let url = &mockito::server_url();
         println!("Mock server URL: {}", url);
 
-        let mut client = FunctionsClient::new(url.to_string(), None, None);
-        client.set_auth("test-token".to_string());
+        let client = FunctionsClient::new(url.to_string(), None, None);
+        client.set_auth("test-token".to_string()).unwrap();
 
-        let mut invoke_options = FunctionInvokeOptions::default();
-        invoke_options.method = Some(HttpMethod::Post);
-        invoke_options.body = Some(InvokeBody::String("request text".to_string()));
+        let invoke_options = FunctionInvokeOptions::text("request text");
         println!("Invoking function with options: {:?}", invoke_options);
 
         match client.invoke("function-name", Some(invoke_options)).await {
             Ok(response) => {
                 println!("Response: {:?}", response);
                 match response {
-                    FunctionsResponse::Success { data } => match data {
+                    FunctionsResponse::Success { data, .. } => match data {
                         ResponseData::Json(json) => {
                             assert_eq!(json["key"], "value");
                         }
@@ -128,6 +149,7 @@ mod functions_client_tests {
                     FunctionsResponse::Failure { error } => {
                         panic!("Expected success, got failure: {}", error);
                     }
+                    FunctionsResponse::NotModified { .. } => panic!("Expected success, got NotModified"),
                 }
             }
             Err(e) => {
@@ -136,6 +158,7 @@ mod functions_client_tests {
         }
     }
 
+    #[cfg(feature = "multipart")]
     #[tokio::test]
     async fn test_invoke_with_form_data() {
         let _m = mock("POST", "/function-name")
@@ -147,22 +170,17 @@ mod functions_client_tests {
         let url = &mockito::server_url();
         println!("Mock server URL: {}", url);
 
-        let mut client = FunctionsClient::new(url.to_string(), None, None);
-        client.set_auth("test-token".to_string());
+        let client = FunctionsClient::new(url.to_string(), None, None);
+        client.set_auth("test-token".to_string()).unwrap();
 
-        let mut invoke_options = FunctionInvokeOptions::default();
-        invoke_options.method = Some(HttpMethod::Post);
-        let mut form_data = HashMap::new();
-        form_data.insert("field1".to_string(), "value1".to_string());
-        form_data.insert("field2".to_string(), "value2".to_string());
-        invoke_options.body = Some(InvokeBody::FormData(form_data));
+        let invoke_options = FunctionInvokeOptions::form([("field1", "value1"), ("field2", "value2")]);
         println!("Invoking function with options: {:?}", invoke_options);
 
         match client.invoke("function-name", Some(invoke_options)).await {
             Ok(response) => {
                 println!("Response: {:?}", response);
                 match response {
-                    FunctionsResponse::Success { data } => match data {
+                    FunctionsResponse::Success { data, .. } => match data {
                         ResponseData::Json(json) => {
                             assert_eq!(json["key"], "value");
                         }
@@ -171,6 +189,7 @@ mod functions_client_tests {
                     FunctionsResponse::Failure { error } => {
                         panic!("Expected success, got failure: {}", error);
                     }
+                    FunctionsResponse::NotModified { .. } => panic!("Expected success, got NotModified"),
                 }
             }
             Err(e) => {
@@ -179,6 +198,69 @@ mod functions_client_tests {
         }
     }
 
+    #[cfg(feature = "multipart")]
+    #[tokio::test]
+    async fn test_form_values_of_each_kind() {
+        let field = |name: &str, value: &str| Matcher::Regex(format!("name=\"{}\"\r\n\r\n{}\r\n--", name, value));
+        let _m = mock("POST", "/typed-form")
+            .match_body(Matcher::AllOf(vec![
+                field("title", "Q3 report"),
+                field("pages", "12"),
+                field("ratio", r"0\.5"),
+                field("draft", "false"),
+                field("note", ""),
+                Matcher::Regex("name=\"file\"; filename=\"q3.csv\"\r\nContent-Type: text/csv\r\n\r\na,b\n1,2\r\n--".into()),
+            ]))
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let options = FunctionInvokeOptions::form([
+            ("title", FormValue::from("Q3 report")),
+            ("pages", FormValue::from(12)),
+            ("ratio", FormValue::from(0.5)),
+            ("draft", FormValue::from(false)),
+            // Sent as an empty field; a missing value is a missing key.
+            ("note", FormValue::from("")),
+            ("file", FormValue::file(b"a,b\n1,2".to_vec(), "q3.csv", "text/csv")),
+        ]);
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        client.invoke("typed-form", Some(options)).await.unwrap();
+        _m.assert();
+
+        // Plain string maps still deserialize, and each kind reads back as itself.
+        let form: HashMap<String, FormValue> = serde_json::from_value(json!({"a": "x", "n": 1.5, "b": true})).unwrap();
+        assert_eq!(form["a"], FormValue::Text("x".into()));
+        assert_eq!(form["n"], FormValue::from(1.5));
+        assert_eq!(form["b"], FormValue::Bool(true));
+    }
+
+    #[cfg(feature = "multipart")]
+    #[tokio::test]
+    async fn test_repeated_form_fields_keep_their_order() {
+        let _m = mock("POST", "/batch-tag")
+            .match_body(Matcher::Regex(
+                "(?s)name=\"tags\"\r\n\r\nurgent\r\n.*name=\"id\"\r\n\r\n7\r\n.*name=\"tags\"\r\n\r\nbilling\r\n".into(),
+            ))
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let options = FunctionInvokeOptions::form([("tags", FormValue::from("urgent")), ("id", 7.into()), ("tags", "billing".into())]);
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        client.invoke("batch-tag", Some(options.clone())).await.unwrap();
+        _m.assert();
+
+        // Duplicates survive a serde round trip, and a plain map of fields still reads.
+        let body = options.body.unwrap();
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(json, json!({"FormData": [["tags", "urgent"], ["id", 7], ["tags", "billing"]]}));
+        assert_eq!(serde_json::from_value::<InvokeBody>(json).unwrap(), body);
+        let body: InvokeBody = serde_json::from_value(json!({"FormData": {"b": "2", "a": "1"}})).unwrap();
+        let expected: FormFields = [("a", "1"), ("b", "2")].into_iter().collect();
+        assert_eq!(body, InvokeBody::FormData(expected));
+    }
+
     #[tokio::test]
     async fn test_invoke_with_file() {
         let _m = mock("POST", "/function-name")
@@ -190,20 +272,19 @@ mod functions_client_tests {
         let url = &mockito::server_url();
         println!("Mock server URL: {}", url);
 
-        let mut client = FunctionsClient::new(url.to_string(), None, None);
-        client.set_auth("test-token".to_string());
+        let client = FunctionsClient::new(url.to_string(), None, None);
+        client.set_auth("test-token".to_string()).unwrap();
 
-        let mut invoke_options = FunctionInvokeOptions::default();
-        invoke_options.method = Some(HttpMethod::Post);
+        let mut invoke_options = FunctionInvokeOptions { method: Some(HttpMethod::Post), ..Default::default() };
 
         // Create a temporary file for testing
         let path = Path::new("test_file.txt");
-        let mut file = File::create(&path).unwrap();
+        let mut file = File::create(path).unwrap();
         writeln!(file, "This is a test file").unwrap();
 
         // Read file contents into Vec<u8>
         let mut file_content = Vec::new();
-        let mut file = File::open(&path).unwrap();
+        let mut file = File::open(path).unwrap();
         file.read_to_end(&mut file_content).unwrap();
 
         invoke_options.body = Some(InvokeBody::File(file_content));
@@ -213,7 +294,7 @@ mod functions_client_tests {
             Ok(response) => {
                 println!("Response: {:?}", response);
                 match response {
-                    FunctionsResponse::Success { data } => match data {
+                    FunctionsResponse::Success { data, .. } => match data {
                         ResponseData::Json(json) => {
                             assert_eq!(json["key"], "value");
                         }
@@ -222,6 +303,7 @@ mod functions_client_tests {
                     FunctionsResponse::Failure { error } => {
                         panic!("Expected success, got failure: {}", error);
                     }
+                    FunctionsResponse::NotModified { .. } => panic!("Expected success, got NotModified"),
                 }
             }
             Err(e) => {
@@ -244,21 +326,18 @@ mod functions_client_tests {
         let url = &mockito::server_url();
         println!("Mock server URL: {}", url);
 
-        let mut client = FunctionsClient::new(url.to_string(), None, None);
-        client.set_auth("test-token".to_string());
-
-        let mut invoke_options = FunctionInvokeOptions::default();
-        invoke_options.method = Some(HttpMethod::Post);
+        let client = FunctionsClient::new(url.to_string(), None, None);
+        client.set_auth("test-token".to_string()).unwrap();
 
         let blob: Vec<u8> = vec![1, 2, 3, 4, 5]; // Example blob data
-        invoke_options.body = Some(InvokeBody::Blob(blob));
+        let invoke_options = FunctionInvokeOptions::bytes(blob);
         println!("Invoking function with options: {:?}", invoke_options);
 
         match client.invoke("function-name", Some(invoke_options)).await {
             Ok(response) => {
                 println!("Response: {:?}", response);
                 match response {
-                    FunctionsResponse::Success { data } => match data {
+                    FunctionsResponse::Success { data, .. } => match data {
                         ResponseData::Json(json) => {
                             assert_eq!(json["key"], "value");
                         }
@@ -267,6 +346,7 @@ mod functions_client_tests {
                     FunctionsResponse::Failure { error } => {
                         panic!("Expected success, got failure: {}", error);
                     }
+                    FunctionsResponse::NotModified { .. } => panic!("Expected success, got NotModified"),
                 }
             }
             Err(e) => {
@@ -286,11 +366,10 @@ mod functions_client_tests {
         let url = &mockito::server_url();
         println!("Mock server URL: {}", url);
 
-        let mut client = FunctionsClient::new(url.to_string(), None, None);
-        client.set_auth("test-token".to_string());
+        let client = FunctionsClient::new(url.to_string(), None, None);
+        client.set_auth("test-token".to_string()).unwrap();
 
-        let mut invoke_options = FunctionInvokeOptions::default();
-        invoke_options.method = Some(HttpMethod::Post);
+        let mut invoke_options = FunctionInvokeOptions { method: Some(HttpMethod::Post), ..Default::default() };
 
         let array_buffer: Vec<u8> = vec![1, 2, 3, 4, 5]; // Example array buffer data
         invoke_options.body = Some(InvokeBody::ArrayBuffer(array_buffer));
@@ -300,7 +379,7 @@ mod functions_client_tests {
             Ok(response) => {
                 println!("Response: {:?}", response);
                 match response {
-                    FunctionsResponse::Success { data } => match data {
+                    FunctionsResponse::Success { data, .. } => match data {
                         ResponseData::Json(json) => {
                             assert_eq!(json["key"], "value");
                         }
@@ -309,6 +388,7 @@ mod functions_client_tests {
                     FunctionsResponse::Failure { error } => {
                         panic!("Expected success, got failure: {}", error);
                     }
+                    FunctionsResponse::NotModified { .. } => panic!("Expected success, got NotModified"),
                 }
             }
             Err(e) => {
@@ -316,4 +396,1102 @@ mod functions_client_tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_invalid_header_name_is_reported() {
+        let mut headers = HashMap::new();
+        headers.insert("Bad Header".to_string(), "value".to_string());
+        let client = FunctionsClient::new("http://localhost".to_string(), Some(headers), None);
+
+        match client.invoke("function-name", None).await {
+            Err(FunctionsError::InvalidHeader { name, reason }) => {
+                assert_eq!(name, "Bad Header");
+                assert_eq!(reason, "invalid header name");
+            }
+            other => panic!("Expected InvalidHeader error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalid_header_value_is_reported() {
+        let mut headers = HashMap::new();
+        headers.insert("X-Custom".to_string(), "abc\ndef".to_string());
+        let client = FunctionsClient::new("http://localhost".to_string(), Some(headers), None);
+
+        match client.invoke("function-name", None).await {
+            Err(error) => {
+                assert_eq!(error.to_string(), r#"InvalidHeader: X-Custom: invalid header value "abc\ndef""#);
+                match error {
+                    FunctionsError::InvalidHeader { name, .. } => assert_eq!(name, "X-Custom"),
+                    other => panic!("Expected InvalidHeader error, got {:?}", other),
+                }
+            }
+            Ok(response) => panic!("Expected error, got {:?}", response),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalid_header_value_preview_is_truncated() {
+        let mut headers = HashMap::new();
+        headers.insert("X-Custom".to_string(), format!("{}\r\n", "a".repeat(100)));
+        let client = FunctionsClient::new("http://localhost".to_string(), Some(headers), None);
+
+        match client.invoke("function-name", None).await {
+            Err(FunctionsError::InvalidHeader { reason, .. }) => {
+                assert_eq!(reason, format!("invalid header value \"{}...\"", "a".repeat(32)));
+            }
+            other => panic!("Expected InvalidHeader error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_error_includes_status_and_body_snippet() {
+        let _m = mock("POST", "/function-name")
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body("{\n  \"error\": \"boom\"\n}")
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        match client.invoke("function-name", None).await {
+            Err(error) => {
+                assert_eq!(
+                    error.to_string(),
+                    r#"HttpError: function-name returned 500 Internal Server Error: { "error": "boom" }"#
+                );
+                match error {
+                    FunctionsError::HttpError { function_name, status, body, metadata } => {
+                        assert_eq!(function_name, "function-name");
+                        assert_eq!(status, 500);
+                        assert_eq!(&body[..], b"{\n  \"error\": \"boom\"\n}");
+                        assert_eq!(metadata.content_type.as_deref(), Some("application/json"));
+                    }
+                    other => panic!("Expected HttpError, got {:?}", other),
+                }
+            }
+            Ok(response) => panic!("Expected error, got {:?}", response),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_error_truncates_long_body() {
+        let _m = mock("POST", "/function-name")
+            .with_status(502)
+            .with_body("x".repeat(300))
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        let error = client.invoke("function-name", None).await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("HttpError: function-name returned 502 Bad Gateway: {}...", "x".repeat(256))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_error_renders_binary_body_as_placeholder() {
+        let _m = mock("POST", "/function-name")
+            .with_status(400)
+            .with_header("content-type", "image/png")
+            .with_body(vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00])
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        let error = client.invoke("function-name", None).await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "HttpError: function-name returned 400 Bad Request: <10 bytes of binary data>"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_error_without_body() {
+        let _m = mock("POST", "/function-name")
+            .with_status(404)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        let error = client.invoke("function-name", None).await.unwrap_err();
+        assert_eq!(error.to_string(), "HttpError: function-name returned 404 Not Found");
+    }
+
+    #[tokio::test]
+    async fn test_content_type_detection() {
+        #[allow(unused_mut)]
+        let mut cases: Vec<(InvokeBody, bool, Option<&str>, Matcher)> = vec![
+            (InvokeBody::String(r#"{"a": 1}"#.to_string()), true, None, Matcher::Exact("application/json".into())),
+            (InvokeBody::String("[1, 2]".to_string()), true, None, Matcher::Exact("application/json".into())),
+            (InvokeBody::String(r#"{"a": 1}"#.to_string()), false, None, Matcher::Exact("text/plain".into())),
+            (
+                InvokeBody::String(r#"{"a": 1}"#.to_string()),
+                true,
+                Some("text/plain; charset=utf-8"),
+                Matcher::Exact("text/plain; charset=utf-8".into()),
+            ),
+            (InvokeBody::String("42".to_string()), true, None, Matcher::Exact("text/plain".into())),
+            (InvokeBody::String("{not json".to_string()), true, None, Matcher::Exact("text/plain".into())),
+            (InvokeBody::Blob(vec![1, 2, 3]), true, None, Matcher::Exact("application/octet-stream".into())),
+        ];
+        #[cfg(feature = "multipart")]
+        {
+            let mut form_data = HashMap::new();
+            form_data.insert("field1".to_string(), "value1".to_string());
+            cases.push((InvokeBody::FormData(form_data.into()), true, None, Matcher::Regex("^multipart/form-data; boundary=".into())));
+        }
+
+        for (body, detect, explicit, expected) in cases {
+            let m = mock("POST", "/function-name")
+                .match_header("content-type", expected)
+                .with_status(200)
+                .with_body("ok")
+                .expect(1)
+                .create();
+
+            let headers = explicit.map(|content_type| {
+                let mut headers = HashMap::new();
+                headers.insert("Content-Type".to_string(), content_type.to_string());
+                headers
+            });
+            let client = FunctionsClient::new(mockito::server_url(), headers, None);
+
+            let invoke_options = FunctionInvokeOptions { body: Some(body.clone()), detect_content_type: Some(detect), ..Default::default() };
+
+            let result = client.invoke("function-name", Some(invoke_options)).await;
+            assert!(result.is_ok(), "body {:?} (detect: {}) failed: {:?}", body, detect, result);
+            m.assert();
+        }
+    }
+
+    fn all_body_variants() -> Vec<InvokeBody> {
+        let mut json_body = HashMap::new();
+        json_body.insert("request_key".to_string(), json!("request_value"));
+
+        #[allow(unused_mut)]
+        let mut variants = vec![
+            InvokeBody::File(vec![1, 2, 3]),
+            InvokeBody::Blob(vec![1, 2, 3]),
+            InvokeBody::ArrayBuffer(vec![1, 2, 3]),
+            InvokeBody::Json(json_body),
+            InvokeBody::String("request text".to_string()),
+        ];
+        #[cfg(feature = "multipart")]
+        {
+            let mut form_data = HashMap::new();
+            form_data.insert("field1".to_string(), "value1".to_string());
+            variants.insert(3, InvokeBody::FormData(form_data.into()));
+        }
+        variants
+    }
+
+    #[tokio::test]
+    async fn test_per_invoke_content_type_is_preserved() {
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        for body in all_body_variants() {
+            let m = mock("POST", "/function-name")
+                .match_header("content-type", "application/x-custom")
+                .with_status(200)
+                .with_body("ok")
+                .expect(1)
+                .create();
+
+            let mut headers = HashMap::new();
+            headers.insert("content-type".to_string(), "application/x-custom".to_string());
+            let invoke_options = FunctionInvokeOptions { headers: Some(headers), body: Some(body.clone()), ..Default::default() };
+
+            let result = client.invoke("function-name", Some(invoke_options)).await;
+            assert!(result.is_ok(), "body {:?} failed: {:?}", body, result);
+            m.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_content_type_is_preserved() {
+        let mut headers = HashMap::new();
+        headers.insert("Content-Type".to_string(), "application/json; charset=utf-8".to_string());
+        let client = FunctionsClient::new(mockito::server_url(), Some(headers), None);
+
+        for body in all_body_variants() {
+            let m = mock("POST", "/function-name")
+                .match_header("content-type", "application/json; charset=utf-8")
+                .with_status(200)
+                .with_body("ok")
+                .expect(1)
+                .create();
+
+            let invoke_options = FunctionInvokeOptions { body: Some(body.clone()), ..Default::default() };
+
+            let result = client.invoke("function-name", Some(invoke_options)).await;
+            assert!(result.is_ok(), "body {:?} failed: {:?}", body, result);
+            m.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_content_type_applied_when_absent() {
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        #[allow(unused_mut)]
+        let mut expected = vec![
+            Matcher::Exact("application/octet-stream".into()),
+            Matcher::Exact("application/octet-stream".into()),
+            Matcher::Exact("application/octet-stream".into()),
+            Matcher::Exact("application/json".into()),
+            Matcher::Exact("text/plain".into()),
+        ];
+        #[cfg(feature = "multipart")]
+        expected.insert(3, Matcher::Regex("^multipart/form-data; boundary=".into()));
+
+        for (body, content_type) in all_body_variants().into_iter().zip(expected) {
+            let m = mock("POST", "/function-name")
+                .match_header("content-type", content_type)
+                .with_status(200)
+                .with_body("ok")
+                .expect(1)
+                .create();
+
+            let invoke_options = FunctionInvokeOptions { body: Some(body.clone()), ..Default::default() };
+
+            let result = client.invoke("function-name", Some(invoke_options)).await;
+            assert!(result.is_ok(), "body {:?} failed: {:?}", body, result);
+            m.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_from_project_url_variants() {
+        let base = mockito::server_url();
+        let project_urls = vec![
+            base.clone(),
+            format!("{}/", base),
+            format!("{}/functions/v1", base),
+            format!("{}/functions/v1/", base),
+        ];
+
+        for project_url in project_urls {
+            let m = mock("POST", "/functions/v1/function-name")
+                .match_header("apikey", "anon-key")
+                .match_header("authorization", "Bearer anon-key")
+                .with_status(200)
+                .with_body("ok")
+                .expect(1)
+                .create();
+
+            let client = FunctionsClient::from_project_url(&project_url, "anon-key").unwrap();
+            let result = client.invoke("function-name", None).await;
+            assert!(result.is_ok(), "{} failed: {:?}", project_url, result);
+            m.assert();
+        }
+    }
+
+    #[test]
+    fn test_from_project_url_rejects_invalid_urls() {
+        for project_url in ["not a url", "ftp://example.com", "https://example.com/?a=1"] {
+            match FunctionsClient::from_project_url(project_url, "anon-key") {
+                Err(FunctionsError::InvalidUrl(_)) => {}
+                other => panic!("Expected InvalidUrl for {}, got {:?}", project_url, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_project_ref() {
+        assert!(FunctionsClient::from_project_ref("abcd1234", "anon-key").is_ok());
+        match FunctionsClient::from_project_ref("abcd.evil.com/x", "anon-key") {
+            Err(FunctionsError::InvalidUrl(_)) => {}
+            other => panic!("Expected InvalidUrl, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_preset() {
+        assert_eq!(FunctionsClient::local().url().as_str(), "http://localhost:54321/functions/v1");
+
+        let port = mockito::server_address().port();
+        let m = mock("POST", "/functions/v1/function-name")
+            .match_header("apikey", LOCAL_ANON_KEY)
+            .match_header("authorization", format!("Bearer {}", LOCAL_ANON_KEY).as_str())
+            .with_status(200)
+            .with_body("ok")
+            .expect(1)
+            .create();
+
+        let client = FunctionsClient::local_with_port(port);
+        assert_eq!(client.url().as_str(), format!("http://localhost:{}/functions/v1", port));
+        let result = client.invoke("function-name", None).await;
+        assert!(result.is_ok(), "{:?}", result);
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_response_metadata() {
+        let _m = mock("POST", "/function-name")
+            .with_status(200)
+            .with_header("content-type", "application/json; charset=utf-8")
+            .with_body(r#"{"key": "value"}"#)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        match client.invoke("function-name", None).await {
+            Ok(FunctionsResponse::Success { metadata, .. }) => {
+                assert_eq!(metadata.status, 200);
+                assert!(metadata.elapsed > Duration::ZERO);
+                assert_eq!(metadata.content_type.as_deref(), Some("application/json; charset=utf-8"));
+                assert_eq!(metadata.content_length, Some(16));
+                assert_eq!(metadata.headers["content-type"], "application/json; charset=utf-8");
+            }
+            other => panic!("Expected success, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gateway_headers() {
+        let _gateway = mock("POST", "/gateway")
+            .with_status(200)
+            .with_header("sb-gateway-version", "1")
+            .with_header("x-sb-edge-region", "eu-central-1")
+            .with_header("sb-request-id", "019a2b3c")
+            .with_header("x-sb-cache", "MISS")
+            .create();
+        let _plain = mock("POST", "/plain").with_status(200).create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        let gateway = client.invoke("gateway", None).await.unwrap().metadata().unwrap().gateway();
+        assert_eq!(gateway.gateway_version.as_deref(), Some("1"));
+        assert_eq!(gateway.edge_region.as_deref(), Some("eu-central-1"));
+        assert_eq!(gateway.request_id.as_deref(), Some("019a2b3c"));
+        assert_eq!(gateway.project_ref, None);
+        assert_eq!(gateway.other.get("x-sb-cache").map(String::as_str), Some("MISS"));
+
+        let plain = client.invoke("plain", None).await.unwrap().metadata().unwrap().gateway();
+        assert_eq!(plain, GatewayInfo::default());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_headers() {
+        let _legacy = mock("POST", "/rate-legacy")
+            .with_header("x-ratelimit-limit", "100")
+            .with_header("x-ratelimit-remaining", "7")
+            .with_header("x-ratelimit-reset", "30")
+            .create();
+        let _draft = mock("POST", "/rate-draft")
+            .with_header("ratelimit-limit", "100, 100;w=60")
+            .with_header("ratelimit-remaining", "0")
+            .create();
+        let epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 120;
+        let _limited = mock("POST", "/rate-limited")
+            .with_status(429)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", &epoch.to_string())
+            .create();
+        let _plain = mock("POST", "/rate-plain").with_header("x-ratelimit-limit", "unlimited").create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let legacy = client.invoke("rate-legacy", None).await.unwrap().metadata().unwrap().rate_limit();
+        assert_eq!(legacy, Some(RateLimitInfo { limit: Some(100), remaining: Some(7), reset: Some(Duration::from_secs(30)) }));
+
+        let draft = client.invoke("rate-draft", None).await.unwrap().metadata().unwrap().rate_limit();
+        assert_eq!(draft, Some(RateLimitInfo { limit: Some(100), remaining: Some(0), reset: None }));
+
+        let error = client.invoke("rate-limited", None).await.unwrap_err();
+        let info = error.rate_limit().unwrap();
+        assert_eq!(info.remaining, Some(0));
+        let reset = info.reset.unwrap();
+        assert!(reset > Duration::from_secs(110) && reset <= Duration::from_secs(120), "{:?}", reset);
+
+        let plain = client.invoke("rate-plain", None).await.unwrap().metadata().unwrap().rate_limit();
+        assert_eq!(plain, None);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_reset_is_measured_from_arrival() {
+        let epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 60;
+        let _epoch = mock("POST", "/rate-epoch").with_header("x-ratelimit-reset", &epoch.to_string()).create();
+        let _delta = mock("POST", "/rate-delta").with_header("x-ratelimit-reset", "60").create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let from_epoch = client.invoke("rate-epoch", None).await.unwrap();
+        let from_delta = client.invoke("rate-delta", None).await.unwrap();
+        let reset = |response: &FunctionsResponse| response.metadata().unwrap().rate_limit().unwrap().reset.unwrap();
+        let (epoch_before, delta_before) = (reset(&from_epoch), reset(&from_delta));
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert_eq!(reset(&from_epoch), epoch_before);
+        assert_eq!(reset(&from_delta), delta_before);
+        assert!(epoch_before > Duration::from_secs(58) && epoch_before <= Duration::from_secs(60), "{:?}", epoch_before);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_json_keeps_body() {
+        let html = "<html><body><h1>502 Bad Gateway</h1></body></html>";
+        let _m = mock("POST", "/function-name")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(html)
+            .expect(3)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        match client.invoke("function-name", None).await {
+            Err(FunctionsError::InvalidJson { error, body }) => {
+                assert!(!error.is_empty());
+                assert_eq!(&body[..], html.as_bytes());
+            }
+            other => panic!("Expected InvalidJson, got {:?}", other),
+        }
+
+        let client = FunctionsClient::builder(mockito::server_url()).invalid_json_body_limit(6).build().unwrap();
+        match client.invoke("function-name", None).await {
+            Err(error @ FunctionsError::InvalidJson { .. }) => {
+                assert!(error.to_string().starts_with("InvalidJson: "), "{}", error);
+                assert!(matches!(error, FunctionsError::InvalidJson { ref body, .. } if &body[..] == b"<html>"));
+            }
+            other => panic!("Expected InvalidJson, got {:?}", other),
+        }
+
+        let client = FunctionsClient::builder(mockito::server_url()).invalid_json_as_text(true).build().unwrap();
+        match client.invoke("function-name", None).await {
+            Ok(FunctionsResponse::Success { data: ResponseData::Text(text), .. }) => assert_eq!(text, html),
+            other => panic!("Expected text fallback, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalid_utf8_text() {
+        let _m = mock("POST", "/function-name")
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_body(b"caf\xe9 \xff!".as_slice())
+            .expect(2)
+            .create();
+        let _valid = mock("POST", "/valid").with_status(200).with_header("content-type", "text/plain").with_body("café").create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        match client.invoke("function-name", None).await {
+            Ok(FunctionsResponse::Success { data: ResponseData::Text(text), metadata }) => {
+                assert_eq!(text, "caf\u{fffd} \u{fffd}!");
+                assert!(metadata.lossy);
+            }
+            other => panic!("Expected lossy text, got {:?}", other),
+        }
+        let valid = client.invoke("valid", None).await.unwrap();
+        assert!(!valid.metadata().unwrap().lossy);
+
+        let client = FunctionsClient::builder(mockito::server_url()).strict_utf8(true).build().unwrap();
+        match client.invoke("function-name", None).await {
+            Err(FunctionsError::DeserializeError(message)) => assert!(message.contains("UTF-8"), "{}", message),
+            other => panic!("Expected DeserializeError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_missing_content_type_yields_bytes() {
+        let _binary = mock("POST", "/binary").with_status(200).with_body(b"\x89PNG\r\n\x1a\n\xff".as_slice()).create();
+        let _textual = mock("POST", "/textual").with_status(200).with_body("hello").create();
+        let _empty = mock("POST", "/empty-type").with_status(200).with_header("content-type", "").with_body("hello").create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        for (name, body) in [("binary", b"\x89PNG\r\n\x1a\n\xff".as_slice()), ("textual", b"hello".as_slice())] {
+            match client.invoke(name, None).await {
+                Ok(FunctionsResponse::Success { data: ResponseData::Bytes(bytes), metadata }) => {
+                    assert_eq!(bytes.as_ref(), body);
+                    assert_eq!(metadata.content_type, None);
+                    assert!(!metadata.lossy);
+                }
+                other => panic!("Expected bytes for {}, got {:?}", name, other),
+            }
+        }
+
+        // A header without a usable media type still defaults to text.
+        match client.invoke("empty-type", None).await {
+            Ok(FunctionsResponse::Success { data: ResponseData::Text(text), .. }) => assert_eq!(text, "hello"),
+            other => panic!("Expected text, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_keep_raw_body() {
+        let json_body = "{ \"b\": 1.50,\n  \"a\": [ ] }";
+        let form_body = "--XyZ\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\none\r\n--XyZ--\r\n";
+        let _json = mock("POST", "/json").with_status(200).with_header("content-type", "application/json").with_body(json_body).create();
+        let _text = mock("POST", "/text").with_status(200).with_header("content-type", "text/plain").with_body(" spaced\r\n").create();
+        let _form = mock("POST", "/form")
+            .with_status(200)
+            .with_header("content-type", "multipart/form-data; boundary=XyZ")
+            .with_body(form_body)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let keep = || Some(FunctionInvokeOptions { keep_raw_body: Some(true), ..Default::default() });
+        for (name, body) in [("json", json_body), ("text", " spaced\r\n"), ("form", form_body)] {
+            let response = client.invoke(name, keep()).await.unwrap();
+            assert_eq!(response.metadata().unwrap().raw_body.as_deref(), Some(body.as_bytes()), "{}", name);
+            match (name, response.data().unwrap()) {
+                ("json", ResponseData::Json(json)) => assert_eq!(json, &json!({"a": [], "b": 1.5})),
+                ("text", ResponseData::Text(text)) => assert_eq!(text, " spaced\r\n"),
+                ("form", ResponseData::FormData(parts)) => assert_eq!(parts.find("a").unwrap().text().unwrap(), "one"),
+                (name, other) => panic!("Unexpected data for {}: {:?}", name, other),
+            }
+        }
+
+        let response = client.invoke("json", None).await.unwrap();
+        assert_eq!(response.metadata().unwrap().raw_body, None);
+    }
+
+    #[tokio::test]
+    async fn test_keep_raw_body_over_max_response_size() {
+        let body = "x".repeat(64);
+        let _sized = mock("POST", "/sized").with_status(200).with_header("content-type", "text/plain").with_body(&body).create();
+        let _chunked = mock("POST", "/chunked")
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_body_from_fn(|w| {
+                for _ in 0..8 {
+                    w.write_all(b"xxxxxxxx")?;
+                }
+                Ok(())
+            })
+            .create();
+
+        let client = FunctionsClient::builder(mockito::server_url()).max_response_size(32).build().unwrap();
+        let keep = || Some(FunctionInvokeOptions { keep_raw_body: Some(true), ..Default::default() });
+        for name in ["sized", "chunked"] {
+            match client.invoke(name, keep()).await {
+                Err(FunctionsError::ResponseTooLarge { limit }) => assert_eq!(limit, 32),
+                other => panic!("Expected ResponseTooLarge for {}, got {:?}", name, other),
+            }
+            let response = client.invoke(name, None).await.unwrap();
+            assert_eq!(response.data(), Some(&ResponseData::Text(body.clone())), "{}", name);
+        }
+
+        let client = FunctionsClient::builder(mockito::server_url()).max_response_size(64).build().unwrap();
+        let response = client.invoke("sized", keep()).await.unwrap();
+        assert_eq!(response.metadata().unwrap().raw_body.as_deref(), Some(body.as_bytes()));
+    }
+
+    #[tokio::test]
+    async fn test_invoke_for_raw_value() {
+        let body = "{ \"z\": 1.50, \"a\": [1e2, 7] }";
+        let _json = mock("POST", "/json").with_status(200).with_header("content-type", "application/json").with_body(body).create();
+        let _broken = mock("POST", "/broken").with_status(200).with_header("content-type", "application/json").with_body("{\"a\": ").create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let raw = client.invoke_for::<Box<RawValue>>("json", None).await.unwrap();
+        assert_eq!(raw.get(), body);
+        let typed = client.invoke_for::<HashMap<String, serde_json::Value>>("json", None).await.unwrap();
+        assert_eq!(typed["a"], json!([100.0, 7]));
+
+        match client.invoke_for::<Box<RawValue>>("broken", None).await {
+            Err(FunctionsError::InvalidJson { body, .. }) => assert_eq!(body.as_ref(), b"{\"a\": "),
+            other => panic!("Expected InvalidJson, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invoke_for_keeps_the_callers_response_type() {
+        let _m = mock("POST", "/latin1-json")
+            .with_status(200)
+            .with_header("content-type", "application/json; charset=iso-8859-1")
+            .with_body(b"\"caf\xe9\"".as_slice())
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        assert!(matches!(client.invoke_for::<String>("latin1-json", None).await, Err(FunctionsError::InvalidJson { .. })));
+        let options = FunctionInvokeOptions { response_type: Some("text/plain".to_string()), ..Default::default() };
+        assert_eq!(client.invoke_for::<String>("latin1-json", Some(options)).await.unwrap(), "café");
+    }
+
+    #[tokio::test]
+    async fn test_connection_refused_is_classified() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let client = FunctionsClient::new(format!("http://127.0.0.1:{}", port), None, None);
+
+        let error = client.invoke("function-name", None).await.unwrap_err();
+        assert_eq!(error.connect_kind(), Some(ConnectErrorKind::ConnectionRefused), "{:?}", error);
+        assert!(error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_connect_timeout_is_classified() {
+        // A listener with a full accept queue drops further SYNs, so connecting hangs like it
+        // would against a non-routable address.
+        let socket = tokio::net::TcpSocket::new_v4().unwrap();
+        socket.bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let listener = socket.listen(0).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let _queued = std::net::TcpStream::connect(addr).unwrap();
+
+        let client = FunctionsClient::builder(format!("http://{}", addr))
+            .connect_timeout(Duration::from_millis(200))
+            .timeout(Duration::from_secs(30))
+            .build()
+            .unwrap();
+        let started = std::time::Instant::now();
+        let error = client.invoke("function-name", None).await.unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(5), "{:?}", started.elapsed());
+        assert_eq!(error.connect_kind(), Some(ConnectErrorKind::Timeout), "{:?}", error);
+        assert!(matches!(
+            error,
+            FunctionsError::Timeout { kind: TimeoutKind::Connect, limit: Some(limit), .. } if limit == Duration::from_millis(200)
+        ));
+        assert!(error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_connection_behavior_options() {
+        let _m = mock("POST", "/function-name").with_status(200).with_header("content-type", "text/plain").with_body("ok").create();
+
+        let keepalive = FunctionsClient::builder(mockito::server_url())
+            .tcp_keepalive(Some(Duration::from_secs(30)))
+            .pool_idle_timeout(Some(Duration::from_secs(60)))
+            .pool_max_idle_per_host(4)
+            .build()
+            .unwrap();
+        let no_pooling = FunctionsClient::builder(mockito::server_url())
+            .tcp_keepalive(None)
+            .pool_idle_timeout(None)
+            .pool_max_idle_per_host(0)
+            .build()
+            .unwrap();
+        for client in [keepalive, no_pooling] {
+            for _ in 0..2 {
+                assert_eq!(client.invoke("function-name", None).await.unwrap().into_result().unwrap(), ResponseData::Text("ok".into()));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http1_only() {
+        let _m = mock("POST", "/function-name").with_status(200).with_header("content-type", "text/plain").with_body("ok").create();
+
+        let client = FunctionsClient::builder(mockito::server_url()).http1_only(true).build().unwrap();
+        let response = client.invoke("function-name", None).await.unwrap();
+        assert_eq!(response.metadata().unwrap().version, Version::HTTP_11);
+
+        match FunctionsClient::builder(mockito::server_url()).http1_only(true).http2_prior_knowledge(true).build() {
+            Err(FunctionsError::BuildError(message)) => assert!(message.contains("http1_only"), "{}", message),
+            other => panic!("Expected BuildError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dns_failure_is_classified() {
+        let client = FunctionsClient::new("http://functions.invalid".to_string(), None, None);
+
+        let error = client.invoke("function-name", None).await.unwrap_err();
+        assert_eq!(error.connect_kind(), Some(ConnectErrorKind::DnsResolution), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn test_multipart_part_without_headers() {
+        let body = "--XyZ\r\n\r\nbare\r\n--XyZ\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\none\r\n--XyZ--\r\n";
+        let _m = mock("POST", "/headerless")
+            .with_status(200)
+            .with_header("content-type", "multipart/form-data; boundary=XyZ")
+            .with_body(body)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let parts = match client.invoke("headerless", None).await {
+            Ok(FunctionsResponse::Success { data: ResponseData::FormData(parts), .. }) => parts,
+            other => panic!("Expected multipart response, got {:?}", other),
+        };
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].name, None);
+        assert_eq!(parts[0].content_type, None);
+        assert_eq!(parts[0].text().unwrap(), "bare");
+        assert_eq!(parts.find("a").unwrap().text().unwrap(), "one");
+    }
+
+    #[tokio::test]
+    async fn test_multipart_response_with_binary_part() {
+        let image: &[u8] = &[0xff, 0xd8, 0x00, 0x80, 0xfe];
+        let mut body = Vec::new();
+        body.extend_from_slice(b"--XyZ\r\nContent-Disposition: form-data; name=\"meta\"\r\nContent-Type: application/json\r\n\r\n");
+        body.extend_from_slice(br#"{"width": 2}"#);
+        body.extend_from_slice(b"\r\n--XyZ\r\nContent-Disposition: form-data; name=\"image\"; filename=\"a.jpg\"\r\nContent-Type: image/jpeg\r\n\r\n");
+        body.extend_from_slice(image);
+        body.extend_from_slice(b"\r\n--XyZ\r\nContent-Disposition: form-data; name=\"meta\"\r\n\r\nsecond\r\n--XyZ--\r\n");
+
+        let _m = mock("POST", "/function-name")
+            .with_status(200)
+            .with_header("content-type", "multipart/form-data; boundary=\"XyZ\"")
+            .with_body(body)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        let parts = match client.invoke("function-name", None).await {
+            Ok(FunctionsResponse::Success { data: ResponseData::FormData(parts), .. }) => parts,
+            other => panic!("Expected multipart response, got {:?}", other),
+        };
+
+        assert_eq!(parts.len(), 3);
+        let names: Vec<_> = parts.iter().map(|part| part.name.as_deref().unwrap()).collect();
+        assert_eq!(names, vec!["meta", "image", "meta"]);
+
+        let meta = parts.find("meta").unwrap();
+        assert_eq!(meta.content_type.as_deref(), Some("application/json"));
+        assert_eq!(meta.json::<serde_json::Value>().unwrap(), json!({"width": 2}));
+
+        let image_part = parts.find("image").unwrap();
+        assert_eq!(image_part.filename.as_deref(), Some("a.jpg"));
+        assert_eq!(image_part.content_type.as_deref(), Some("image/jpeg"));
+        assert_eq!(&image_part.data[..], image);
+        assert!(image_part.text().is_err());
+
+        assert_eq!(parts[2].text().unwrap(), "second");
+    }
+
+    #[tokio::test]
+    async fn test_accept_header_and_parse_hint() {
+        let _m = mock("POST", "/function-name")
+            .match_header("accept", "text/csv")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("id,name\n1,functions\n")
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let options = FunctionInvokeOptions::default().accept("text/csv");
+
+        match client.invoke("function-name", Some(options)).await {
+            Ok(FunctionsResponse::Success { data: ResponseData::Text(text), .. }) => {
+                assert_eq!(text, "id,name\n1,functions\n");
+            }
+            other => panic!("Expected text response, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_accept_binary_hint_reads_bytes() {
+        let _m = mock("POST", "/function-name")
+            .match_header("accept", "application/pdf")
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_body(&[0x25, 0x50, 0x44, 0x46, 0xff][..])
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let options = FunctionInvokeOptions::default().accept("application/pdf");
+
+        match client.invoke("function-name", Some(options)).await {
+            Ok(FunctionsResponse::Success { data: ResponseData::Bytes(bytes), .. }) => {
+                assert_eq!(&bytes[..], &[0x25, 0x50, 0x44, 0x46, 0xff]);
+            }
+            other => panic!("Expected bytes response, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_accept_list_is_sent_raw_without_hint() {
+        let accept = "application/json, text/csv;q=0.5";
+        let _m = mock("POST", "/function-name")
+            .match_header("accept", accept)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"key": "value"}"#)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let options = FunctionInvokeOptions::default().accept(accept);
+        assert_eq!(options.response_type, None);
+
+        match client.invoke("function-name", Some(options)).await {
+            Ok(FunctionsResponse::Success { data: ResponseData::Json(json), .. }) => {
+                assert_eq!(json, json!({"key": "value"}));
+            }
+            other => panic!("Expected JSON response, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_function_url_is_built_from_parsed_base() {
+        let _plain = mock("POST", "/function-name").with_status(200).with_header("content-type", "text/plain").with_body("plain").create();
+        let _spaced = mock("POST", "/hello%20world").with_status(200).with_header("content-type", "text/plain").with_body("spaced").create();
+
+        let client = FunctionsClient::new(format!("{}/", mockito::server_url()), None, None);
+        assert_eq!(client.url().as_str(), format!("{}/", mockito::server_url()));
+
+        for (name, path, expected) in [("function-name", "/function-name", "plain"), ("hello world", "/hello%20world", "spaced")] {
+            match client.invoke(name, None).await {
+                Ok(FunctionsResponse::Success { data: ResponseData::Text(text), metadata }) => {
+                    assert_eq!(text, expected);
+                    assert_eq!(metadata.url.unwrap().path(), path);
+                }
+                other => panic!("Expected success for {}, got {:?}", name, other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_function_names_are_validated_and_encoded() {
+        let _m = mock("POST", "/a%3Fb").with_status(200).with_header("content-type", "text/plain").with_body("one segment").create();
+        let _nested = mock("POST", "/reports/daily%20sum").with_status(200).with_header("content-type", "text/plain").with_body("nested").create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let prepared = client.build_request("a?b", None).await.unwrap();
+        assert_eq!(prepared.url.path(), "/a%3Fb");
+        assert_eq!(prepared.url.query(), None);
+        assert_eq!(client.invoke("a?b", None).await.unwrap().data(), Some(&ResponseData::Text("one segment".into())));
+
+        for (name, reason) in [
+            ("", "the name is empty"),
+            ("hello\nworld", "the name contains a control character"),
+            ("reports/daily", "the name contains '/'; enable `allow_sub_paths` to call a sub-path"),
+        ] {
+            match client.invoke(name, None).await {
+                Err(FunctionsError::InvalidFunctionName { name: reported, reason: message }) => {
+                    assert_eq!(reported, name.escape_default().to_string());
+                    assert_eq!(message, reason);
+                }
+                other => panic!("Expected InvalidFunctionName for {:?}, got {:?}", name, other),
+            }
+        }
+
+        let client = FunctionsClient::builder(mockito::server_url()).allow_sub_paths(true).build().unwrap();
+        assert_eq!(client.invoke("reports/daily sum", None).await.unwrap().data(), Some(&ResponseData::Text("nested".into())));
+        assert!(matches!(client.invoke("reports//daily", None).await, Err(FunctionsError::InvalidFunctionName { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_non_2xx_as_data_when_error_for_status_is_off() {
+        let _conflict = mock("POST", "/reserve-seat")
+            .with_status(409)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"reason": "taken"}"#)
+            .create();
+        let _relay = mock("POST", "/relay-down").with_status(502).with_header("x-relay-error", "true").create();
+
+        let client = FunctionsClient::builder(mockito::server_url()).error_for_status(false).build().unwrap();
+        match client.invoke("reserve-seat", None).await {
+            Ok(FunctionsResponse::Success { data: ResponseData::Json(json), metadata }) => {
+                assert_eq!(json, json!({"reason": "taken"}));
+                assert_eq!(metadata.status, 409);
+                assert_eq!(metadata.media_type().as_deref(), Some("application/json"));
+            }
+            other => panic!("Expected the 409 as data, got {:?}", other),
+        }
+        assert!(matches!(client.invoke("relay-down", None).await, Err(FunctionsError::RelayError(_))));
+
+        // On by default, and an invoke can turn it back on.
+        let strict = FunctionInvokeOptions { error_for_status: Some(true), ..Default::default() };
+        for (client, options) in [(FunctionsClient::new(mockito::server_url(), None, None), None), (client, Some(strict))] {
+            match client.invoke("reserve-seat", options).await {
+                Err(FunctionsError::HttpError { status: 409, body, .. }) => assert_eq!(body, r#"{"reason": "taken"}"#),
+                other => panic!("Expected HttpError, got {:?}", other),
+            }
+        }
+
+        let lenient = FunctionInvokeOptions { error_for_status: Some(false), ..Default::default() };
+        let response = FunctionsClient::new(mockito::server_url(), None, None).invoke("reserve-seat", Some(lenient)).await.unwrap();
+        assert_eq!(response.metadata().unwrap().status, 409);
+    }
+
+    #[tokio::test]
+    async fn test_error_types_carry_response_context() {
+        let _http = mock("POST", "/context-http")
+            .with_status(422)
+            .with_header("content-type", "application/json")
+            .with_header("x-request-id", "req-1")
+            .with_body(r#"{"error": "name is required"}"#)
+            .create();
+        let _relay = mock("POST", "/context-relay").with_status(502).with_header("x-relay-error", "true").with_body("boot failed").create();
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        let error = client.invoke("context-http", None).await.unwrap_err();
+        match FunctionsHttpError::try_from(error) {
+            Ok(http) => {
+                #[derive(serde::Deserialize)]
+                struct Body {
+                    error: String,
+                }
+                assert_eq!(http.function_name, "context-http");
+                assert_eq!(http.context.status, 422);
+                assert_eq!(http.context.headers["x-request-id"], "req-1");
+                assert_eq!(http.context.json::<Body>().unwrap().error, "name is required");
+                assert_eq!(http.context.url.as_ref().unwrap().path(), "/context-http");
+                // Converting back gives the same enum variant.
+                assert!(matches!(FunctionsError::from(http), FunctionsError::HttpError { status: 422, .. }));
+            }
+            Err(other) => panic!("Expected FunctionsHttpError, got {:?}", other),
+        }
+
+        let error = client.invoke("context-relay", None).await.unwrap_err();
+        let error = FunctionsHttpError::try_from(error).unwrap_err();
+        let relay = FunctionsRelayError::try_from(error).unwrap();
+        let context = relay.context.unwrap();
+        assert_eq!((context.status, context.text()), (502, "boot failed".to_string()));
+
+        let error = FunctionsClient::new("http://127.0.0.1:1".into(), None, None).invoke("down", None).await.unwrap_err();
+        let fetch = FunctionsFetchError::try_from(error).unwrap();
+        assert_eq!(fetch.connect_kind, Some(ConnectErrorKind::ConnectionRefused));
+    }
+
+    #[tokio::test]
+    async fn test_success_statuses_replace_2xx_check() {
+        let _missing = mock("POST", "/lookup-missing")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"found": false}"#)
+            .create();
+        let _created = mock("POST", "/lookup-created").with_status(201).with_header("content-type", "text/plain").with_body("created").create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let lookup = || FunctionInvokeOptions::default().success_statuses([200, 404]);
+        match client.invoke("lookup-missing", Some(lookup())).await {
+            Ok(FunctionsResponse::Success { data: ResponseData::Json(json), metadata }) => {
+                assert_eq!(json, json!({"found": false}));
+                assert_eq!(metadata.status, 404);
+            }
+            other => panic!("Expected the 404 as success, got {:?}", other),
+        }
+        match client.invoke("lookup-created", Some(lookup())).await {
+            Err(FunctionsError::HttpError { status: 201, body, .. }) => assert_eq!(body, "created"),
+            other => panic!("Expected HttpError for 201, got {:?}", other),
+        }
+
+        let options = FunctionInvokeOptions::default().success_when(|status| status == StatusCode::CREATED);
+        assert_eq!(client.invoke("lookup-created", Some(options)).await.unwrap().metadata().unwrap().status, 201);
+        assert!(matches!(client.invoke("lookup-missing", None).await, Err(FunctionsError::HttpError { status: 404, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_sub_path_after_function_name() {
+        let _route = mock("GET", "/api/users/42").with_status(200).with_header("content-type", "text/plain").with_body("user").create();
+        let _spaced = mock("GET", "/api/files/my%20report/a%2Fb").with_status(200).with_header("content-type", "text/plain").with_body("file").create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let get = || FunctionInvokeOptions { method: Some(HttpMethod::Get), ..Default::default() };
+        let cases = [
+            (get().path("users/42"), "/api/users/42", "user"),
+            (get().path("/files/my report/").path_segment("a/b"), "/api/files/my%20report/a%2Fb", "file"),
+        ];
+        for (options, path, expected) in cases {
+            let prepared = client.build_request("api", Some(options.clone())).await.unwrap();
+            assert_eq!(prepared.url.path(), path);
+            let response = client.invoke("api", Some(options)).await.unwrap();
+            assert_eq!(response.data(), Some(&ResponseData::Text(expected.into())));
+            assert_eq!(response.metadata().unwrap().url.as_ref().unwrap().path(), path);
+        }
+    }
+
+    #[test]
+    fn test_invalid_base_url_is_rejected_at_construction() {
+        for url in ["not a url", "ftp://example.com/functions/v1"] {
+            match FunctionsClient::try_new(url.to_string(), None, None) {
+                Err(FunctionsError::InvalidUrl(_)) => {}
+                other => panic!("Expected InvalidUrl for {}, got {:?}", url, other),
+            }
+        }
+        let client = FunctionsClient::try_new("https://example.com/functions/v1/".to_string(), None, None).unwrap();
+        assert_eq!(client.url().as_str(), "https://example.com/functions/v1");
+        assert_eq!(client.base_url(), "https://example.com/functions/v1");
+    }
+
+    #[tokio::test]
+    async fn test_raw_request_through_http_client() {
+        let _m = mock("GET", "/storage/v1/bucket")
+            .match_header("apikey", Matcher::Missing)
+            .with_status(200)
+            .with_body("[]")
+            .create();
+
+        let client = FunctionsClient::from_project_url(&mockito::server_url(), "anon-key").unwrap();
+        assert_eq!(client.base_url(), format!("{}/functions/v1", mockito::server_url()));
+        let response = client.http_client().get(format!("{}/storage/v1/bucket", mockito::server_url())).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.text().await.unwrap(), "[]");
+    }
+
+    #[tokio::test]
+    async fn test_custom_methods() {
+        let _m = mock("OPTIONS", "/function-name").with_status(200).with_header("content-type", "text/plain").with_body("ok").create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        let options = FunctionInvokeOptions { method: Some(HttpMethod::Custom("OPTIONS".into())), ..Default::default() };
+        match client.invoke("function-name", Some(options)).await {
+            Ok(FunctionsResponse::Success { data: ResponseData::Text(text), .. }) => assert_eq!(text, "ok"),
+            other => panic!("Expected success, got {:?}", other),
+        }
+
+        let options = FunctionInvokeOptions { method: Some(HttpMethod::Custom("BAD METHOD".into())), ..Default::default() };
+        match client.invoke("function-name", Some(options)).await {
+            Err(FunctionsError::InvalidMethod(method)) => assert_eq!(method, "BAD METHOD"),
+            other => panic!("Expected InvalidMethod, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_method_precedence() {
+        let get = mock("GET", "/function-name").with_status(200).expect(1).create();
+        let post = mock("POST", "/function-name").with_status(200).expect(2).create();
+        let post_options = || Some(FunctionInvokeOptions { method: Some(HttpMethod::Post), ..Default::default() });
+
+        let client = FunctionsClient::builder(mockito::server_url())
+            .default_method(HttpMethod::Get)
+            .build()
+            .unwrap();
+        client.invoke("function-name", None).await.unwrap();
+        client.invoke("function-name", post_options()).await.unwrap();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        client.invoke("function-name", None).await.unwrap();
+
+        get.assert();
+        post.assert();
+    }
+
+    #[tokio::test]
+    async fn test_html_response() {
+        let document = |charset_meta: &str| {
+            let mut body = format!("<!DOCTYPE html>\n<html><head>{}<title>Caf", charset_meta).into_bytes();
+            body.push(0xE9);
+            body.extend_from_slice(b"</title></head><body><p>Hello</p></body></html>");
+            body
+        };
+        let _header = mock("GET", "/html-header")
+            .with_status(200)
+            .with_header("content-type", "Text/HTML; charset=ISO-8859-1")
+            .with_body(document(""))
+            .create();
+        let _meta = mock("GET", "/html-meta")
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_body(document(r#"<meta http-equiv="Content-Type" content="text/html; charset=windows-1252">"#))
+            .create();
+        let _plain = mock("GET", "/plain-text").with_status(200).with_header("content-type", "text/plain").with_body("hi").create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let get = || Some(FunctionInvokeOptions { method: Some(HttpMethod::Get), ..Default::default() });
+
+        let response = client.invoke("html-header", get()).await.unwrap();
+        let metadata = response.metadata().unwrap();
+        assert!(metadata.is_html());
+        assert_eq!(metadata.media_type().as_deref(), Some("text/html"));
+        assert_eq!(metadata.charset().as_deref(), Some("iso-8859-1"));
+        match response.data() {
+            Some(ResponseData::Text(html)) => assert!(html.contains("<title>Café</title>"), "{}", html),
+            other => panic!("Expected HTML text, got {:?}", other),
+        }
+
+        let response = client.invoke("html-meta", get()).await.unwrap();
+        assert!(response.metadata().unwrap().is_html());
+        assert_eq!(response.metadata().unwrap().charset(), None);
+        match response.data() {
+            Some(ResponseData::Text(html)) => assert!(html.contains("<title>Café</title>"), "{}", html),
+            other => panic!("Expected HTML text, got {:?}", other),
+        }
+
+        let response = client.invoke("plain-text", get()).await.unwrap();
+        assert!(!response.metadata().unwrap().is_html());
+        assert_eq!(response.data(), Some(&ResponseData::Text("hi".into())));
+    }
 }
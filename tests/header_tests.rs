@@ -0,0 +1,89 @@
+mod common;
+
+mod header_tests {
+    use crate::common;
+    use std::collections::HashMap;
+    use serde_json::json;
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionRegion, FunctionsClient, HeaderMode};
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok";
+
+    /// Every value sent for `name`, compared case-insensitively.
+    fn values(head: &str, name: &str) -> Vec<String> {
+        head.lines()
+            .filter_map(|line| line.split_once(':'))
+            .filter(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_mixed_case_duplicates_send_one_header() {
+        let (url, requests) = common::serve_recorded(vec![OK.to_string(), OK.to_string()]);
+        let headers = HashMap::from([
+            ("authorization".to_string(), "Bearer from-map".to_string()),
+            ("X-Tenant".to_string(), "upper".to_string()),
+            ("x-tenant".to_string(), "lower".to_string()),
+        ]);
+        let client = FunctionsClient::builder(url.clone())
+            .headers(headers)
+            .header("X-Client-Info", "first")
+            .header("x-client-info", "second")
+            .build()
+            .unwrap();
+        client.set_auth("from-set-auth").unwrap();
+
+        let options = FunctionInvokeOptions::default().header("X-Trace", "1").header("x-trace", "2").header("X-CLIENT-INFO", "per-invoke");
+        client.invoke("headers", Some(options)).await.unwrap();
+        client.invoke("headers", None).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        let (with_options, plain) = (&requests[0], &requests[1]);
+        assert_eq!(values(with_options, "authorization"), ["Bearer from-set-auth"]);
+        assert_eq!(values(with_options, "x-tenant"), ["lower"]);
+        assert_eq!(values(with_options, "x-trace"), ["2"]);
+        assert_eq!(values(with_options, "x-client-info"), ["per-invoke"]);
+        assert_eq!(values(plain, "x-client-info"), ["second"]);
+    }
+
+    #[tokio::test]
+    async fn test_replace_mode_sends_only_per_invoke_headers() {
+        let (url, requests) = common::serve_recorded(vec![OK.to_string()]);
+        let client = FunctionsClient::builder(url)
+            .header("X-Client-Info", "supabase-function-rs")
+            .region(FunctionRegion::EuWest1)
+            .build()
+            .unwrap();
+        client.set_auth("client-token").unwrap();
+
+        let options = FunctionInvokeOptions {
+            header_mode: HeaderMode::Replace,
+            ..FunctionInvokeOptions::json([("minimal", json!(true))]).header("X-Repro", "1").header("Accept", "text/plain")
+        };
+        client.invoke("headers", Some(options)).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        let mut names: Vec<_> = requests[0].lines().skip(1).filter_map(|line| line.split_once(':')).map(|(name, _)| name.to_ascii_lowercase()).collect();
+        names.sort();
+        assert_eq!(names, ["accept", "content-length", "host", "x-repro"]);
+    }
+
+    #[tokio::test]
+    async fn test_replace_mode_keeps_conditional_headers() {
+        let (url, requests) = common::serve_recorded(vec![OK.to_string()]);
+        let client = FunctionsClient::builder(url).header("X-Client-Info", "supabase-function-rs").build().unwrap();
+
+        let options = FunctionInvokeOptions {
+            header_mode: HeaderMode::Replace,
+            if_none_match: Some("\"v1\"".to_string()),
+            if_modified_since: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+            ..Default::default()
+        };
+        client.invoke("headers", Some(options)).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(values(&requests[0], "if-none-match"), ["\"v1\""]);
+        assert_eq!(values(&requests[0], "if-modified-since"), ["Wed, 21 Oct 2015 07:28:00 GMT"]);
+        assert!(values(&requests[0], "x-client-info").is_empty());
+    }
+}
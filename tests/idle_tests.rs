@@ -0,0 +1,58 @@
+mod common;
+
+mod idle_tests {
+    use crate::common;
+    use futures_util::StreamExt;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient, FunctionsError};
+
+    const IDLE: Duration = Duration::from_millis(300);
+
+    /// Sends the head and a 5-byte chunk, then holds the connection open without a byte more.
+    fn serve_stalling() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                thread::spawn(move || {
+                    common::read_request(&mut stream);
+                    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nTransfer-Encoding: chunked\r\n\r\n";
+                    let _ = stream.write_all(format!("{}5\r\nhello\r\n", head).as_bytes());
+                    thread::sleep(Duration::from_secs(10));
+                });
+            }
+        });
+        url
+    }
+
+    fn assert_stalled(error: FunctionsError, start: Instant) {
+        assert_eq!(error, FunctionsError::StalledStream { idle: IDLE, received: 5 });
+        assert!(start.elapsed() >= IDLE, "{:?}", start.elapsed());
+        assert!(start.elapsed() < IDLE * 5, "{:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_buffered_body_fails_when_stalled() {
+        let client = FunctionsClient::builder(serve_stalling()).idle_timeout(IDLE).build().unwrap();
+
+        let start = Instant::now();
+        let error = client.invoke("stalling", None).await.unwrap_err();
+        assert_stalled(error, start);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_body_fails_when_stalled() {
+        let client = FunctionsClient::new(serve_stalling(), None, None);
+        let options = FunctionInvokeOptions { idle_timeout: Some(IDLE), ..Default::default() };
+
+        let start = Instant::now();
+        let mut chunks = client.invoke_stream("stalling", Some(options));
+        assert_eq!(chunks.next().await.unwrap().unwrap(), "hello");
+        assert_stalled(chunks.next().await.unwrap().unwrap_err(), start);
+        assert!(chunks.next().await.is_none());
+    }
+}
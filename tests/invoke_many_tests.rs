@@ -0,0 +1,96 @@
+mod common;
+
+mod invoke_many_tests {
+    use crate::common;
+    use mockito::mock;
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient, FunctionsError, ResponseData};
+
+    /// Answers every request after a delay with the value of its `x-record` header,
+    /// tracking the highest number of requests handled at once.
+    fn serve_slow_echo(max_in_flight: Arc<AtomicUsize>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let in_flight = in_flight.clone();
+                let max_in_flight = max_in_flight.clone();
+                thread::spawn(move || {
+                    let head = common::read_request(&mut stream);
+                    let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(current, Ordering::SeqCst);
+                    let record = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("x-record: "))
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string();
+                    thread::sleep(Duration::from_millis(30));
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    let _ = stream.write_all(
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            record.len(),
+                            record
+                        )
+                        .as_bytes(),
+                    );
+                });
+            }
+        });
+        url
+    }
+
+    fn record(index: usize) -> FunctionInvokeOptions {
+        FunctionInvokeOptions {
+            headers: Some(HashMap::from([("x-record".to_string(), index.to_string())])),
+            ..FunctionInvokeOptions::text(format!("record {}", index))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_results_are_ordered_and_concurrency_bounded() {
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let client = FunctionsClient::new(serve_slow_echo(max_in_flight.clone()), None, None);
+
+        let mut progress = Vec::new();
+        let results = client
+            .invoke_many_with_progress("backfill", (0..10).map(record), 2, |done| progress.push(done))
+            .await;
+
+        let records: Vec<_> = results
+            .into_iter()
+            .map(|result| match result.unwrap().into_result().unwrap() {
+                ResponseData::Text(text) => text,
+                other => panic!("Expected text, got {:?}", other),
+            })
+            .collect();
+        let expected: Vec<_> = (0..10).map(|i| i.to_string()).collect();
+        assert_eq!(records, expected);
+        assert_eq!(progress, (1..=10).collect::<Vec<_>>());
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failures_keep_their_position() {
+        let _ok = mock("POST", "/backfill").match_body("good").with_status(200).with_body("ok").create();
+        let _bad = mock("POST", "/backfill").match_body("bad").with_status(422).create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let bodies = ["good", "bad", "good"].map(FunctionInvokeOptions::text);
+        let results = client.invoke_many("backfill", bodies, 0).await;
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].as_ref().unwrap().is_success());
+        assert!(matches!(results[1], Err(FunctionsError::HttpError { status: 422, .. })));
+        assert!(results[2].as_ref().unwrap().is_success());
+    }
+}
@@ -0,0 +1,70 @@
+mod invoker_tests {
+    use futures_util::StreamExt;
+    use mockito::mock;
+    use serde::Deserialize;
+    use std::sync::Arc;
+    use supabase_function_rs::{
+        FunctionInvokeOptions, FunctionsClient, FunctionsError, FunctionsInvoker, FunctionsInvokerExt, FunctionsResponse, HttpMethod, ResponseData,
+    };
+
+    struct Plugin {
+        invoker: Box<dyn FunctionsInvoker + Send + Sync>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Greeting {
+        message: String,
+    }
+
+    fn get() -> Option<FunctionInvokeOptions> {
+        Some(FunctionInvokeOptions { method: Some(HttpMethod::Get), ..Default::default() })
+    }
+
+    #[tokio::test]
+    async fn test_invoke_through_boxed_invoker() {
+        let _m = mock("GET", "/invoker-greet")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "hi"}"#)
+            .expect(3)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let plugin = Plugin { invoker: Box::new(Arc::new(client.clone())) };
+
+        match plugin.invoker.invoke("invoker-greet", get()).await.unwrap() {
+            FunctionsResponse::Success { data: ResponseData::Json(json), .. } => assert_eq!(json["message"], "hi"),
+            other => panic!("Expected JSON, got {:?}", other),
+        }
+        let greeting: Greeting = plugin.invoker.invoke_for("invoker-greet", get()).await.unwrap();
+        assert_eq!(greeting, Greeting { message: "hi".into() });
+
+        let borrowed: &dyn FunctionsInvoker = &&client;
+        let chunks: Vec<_> = borrowed.invoke_stream("invoker-greet", get()).map(Result::unwrap).collect().await;
+        assert_eq!(chunks.concat(), br#"{"message": "hi"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_invoke_for_keeps_the_clients_invalid_json_limit() {
+        let _m = mock("GET", "/invoker-broken").with_status(200).with_header("content-type", "application/json").with_body("{\"message\": ").create();
+
+        let client = FunctionsClient::builder(mockito::server_url()).invalid_json_body_limit(4).build().unwrap();
+        let invokers: [Box<dyn FunctionsInvoker + Send + Sync>; 2] = [Box::new(client.clone()), Box::new(Arc::new(client))];
+        for invoker in invokers {
+            assert_eq!(invoker.invalid_json_body_limit(), 4);
+            match invoker.invoke_for::<Greeting>("invoker-broken", get()).await {
+                Err(FunctionsError::InvalidJson { body, .. }) => assert_eq!(body.as_ref(), b"{\"me"),
+                other => panic!("Expected InvalidJson, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invoke_stream_reports_http_error() {
+        let _m = mock("GET", "/invoker-missing").with_status(404).create();
+
+        let invoker: Box<dyn FunctionsInvoker + Send + Sync> = Box::new(FunctionsClient::new(mockito::server_url(), None, None));
+        let items: Vec<_> = invoker.invoke_stream("invoker-missing", get()).collect().await;
+        assert!(matches!(items[..], [Err(FunctionsError::HttpError { status: 404, .. })]), "{:?}", items);
+    }
+}
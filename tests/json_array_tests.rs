@@ -0,0 +1,103 @@
+mod common;
+
+mod json_array_tests {
+    use crate::common;
+    use futures_util::StreamExt;
+    use mockito::mock;
+    use serde::Deserialize;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+    use supabase_function_rs::{FunctionsClient, FunctionsError};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        id: u32,
+        name: String,
+        tags: Vec<String>,
+    }
+
+    /// Sends `body` with chunked encoding in 7-byte chunks, so elements, strings and escapes
+    /// are split across reads.
+    fn serve_chunked(body: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            common::read_request(&mut stream);
+            let head = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n";
+            stream.write_all(head.as_bytes()).unwrap();
+            for (i, chunk) in body.as_bytes().chunks(7).enumerate() {
+                stream.write_all(format!("{:x}\r\n", chunk.len()).as_bytes()).unwrap();
+                stream.write_all(chunk).unwrap();
+                stream.write_all(b"\r\n").unwrap();
+                if i % 500 == 0 {
+                    stream.flush().unwrap();
+                    thread::sleep(Duration::from_millis(1));
+                }
+            }
+            stream.write_all(b"0\r\n\r\n").unwrap();
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_streams_large_chunk_split_array() {
+        let count = 20_000;
+        let elements: Vec<_> = (0..count)
+            .map(|id| format!(r#"{{"id": {}, "name": "item [{}], {{x}}", "tags": ["a", "b\"]\\"]}}"#, id, id))
+            .collect();
+        let url = serve_chunked(format!(" [\n{}\n]\n", elements.join(",\n  ")));
+
+        let client = FunctionsClient::new(url, None, None);
+        let mut stream = client.invoke_json_array_stream::<Item>("function-name", None);
+        let mut received = 0;
+        while let Some(item) = stream.next().await {
+            let item = item.unwrap();
+            assert_eq!(item.id, received);
+            assert_eq!(item.name, format!("item [{}], {{x}}", received));
+            assert_eq!(item.tags, vec!["a".to_string(), "b\"]\\".to_string()]);
+            received += 1;
+        }
+        assert_eq!(received, count);
+    }
+
+    #[tokio::test]
+    async fn test_object_response_errors_immediately() {
+        let _m = mock("POST", "/function-name")
+            .match_header("accept", "application/json")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"items": [1, 2]}"#)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let results: Vec<_> = client.invoke_json_array_stream::<u32>("function-name", None).collect().await;
+        match results.as_slice() {
+            [Err(FunctionsError::DeserializeError(message))] => {
+                assert_eq!(message, "expected a top-level JSON array, found an object")
+            }
+            other => panic!("Expected a single DeserializeError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_truncated_array_errors_after_complete_elements() {
+        let _m = mock("POST", "/function-name")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("[1, [2], 3")
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let results: Vec<_> = client.invoke_json_array_stream::<serde_json::Value>("function-name", None).collect().await;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &serde_json::json!(1));
+        assert_eq!(results[1].as_ref().unwrap(), &serde_json::json!([2]));
+        match &results[2] {
+            Err(FunctionsError::DeserializeError(message)) => assert!(message.contains("closing `]`"), "{}", message),
+            other => panic!("Expected DeserializeError, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+mod log_tests {
+    use log::{Level, Log, Metadata, Record};
+    use mockito::mock;
+    use std::sync::{Mutex, Once};
+    use std::time::Duration;
+    use supabase_function_rs::{FunctionRegion, FunctionsClient, MockSleeper, RetryPolicy};
+
+    /// Keeps every record from the crate as `(level, target, message)`. `log` allows one
+    /// logger per process, so tests share it and pick out their own function's records.
+    struct Capture(Mutex<Vec<(Level, String, String)>>);
+
+    static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+
+    impl Log for Capture {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.target().starts_with("supabase_function_rs")
+        }
+
+        fn log(&self, record: &Record<'_>) {
+            if self.enabled(record.metadata()) {
+                self.0.lock().unwrap().push((record.level(), record.target().to_string(), record.args().to_string()));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn records_for(function_name: &str) -> Vec<(Level, String, String)> {
+        CAPTURE.0.lock().unwrap().iter().filter(|(_, _, message)| message.contains(function_name)).cloned().collect()
+    }
+
+    fn install() {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            log::set_logger(&CAPTURE).unwrap();
+            log::set_max_level(log::LevelFilter::Debug);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_request_response_and_retry_records() {
+        install();
+        let _failing = mock("POST", "/logged-retry").with_status(503).with_body("secret body").expect(1).create();
+        let _ok = mock("POST", "/logged-retry").with_status(200).expect(1).create();
+
+        let sleeper = MockSleeper::new();
+        let mut client = FunctionsClient::builder(mockito::server_url())
+            .header("Authorization", "Bearer secret-token")
+            .region(FunctionRegion::EuWest1)
+            .retry_policy(RetryPolicy::new(3).backoff(Duration::from_millis(100), Duration::from_secs(1)))
+            .build()
+            .unwrap();
+        client.set_sleeper(sleeper);
+        client.invoke("logged-retry", None).await.unwrap();
+
+        let records = records_for("logged-retry");
+        let levels: Vec<_> = records.iter().map(|(level, _, _)| *level).collect();
+        assert_eq!(levels, [Level::Debug, Level::Debug, Level::Warn, Level::Warn, Level::Debug, Level::Debug]);
+        assert!(records.iter().all(|(_, target, _)| target == "supabase_function_rs::client"));
+        assert_eq!(records[0].2, "invoking POST logged-retry in region eu-west-1");
+        assert!(records[1].2.starts_with("logged-retry responded 503 in "), "{}", records[1].2);
+        assert_eq!(records[2].2, "invoking logged-retry failed: status 503");
+        assert_eq!(records[3].2, "retrying logged-retry after attempt 1 in 100ms: status 503");
+        assert!(records[5].2.starts_with("logged-retry responded 200 in "), "{}", records[5].2);
+
+        let logged = format!("{:?}", records);
+        assert!(!logged.contains("secret"), "{}", logged);
+    }
+}
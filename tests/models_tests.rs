@@ -0,0 +1,195 @@
+mod models_tests {
+    use mockito::mock;
+    use serde_json::json;
+    use std::collections::HashMap;
+    #[cfg(feature = "multipart")]
+    use supabase_function_rs::FormValue;
+    use supabase_function_rs::{
+        FunctionInvokeOptions, FunctionRegion, FunctionsClient, FunctionsError, FunctionsResponse, HttpMethod, InvokeBody,
+        ResponseData, ResponseMetadata,
+    };
+
+    #[test]
+    fn test_http_method_round_trip() {
+        let methods = [
+            (HttpMethod::Post, "POST"),
+            (HttpMethod::Get, "GET"),
+            (HttpMethod::Put, "PUT"),
+            (HttpMethod::Patch, "PATCH"),
+            (HttpMethod::Delete, "DELETE"),
+            (HttpMethod::Custom("OPTIONS".into()), "OPTIONS"),
+        ];
+        for (method, name) in methods {
+            let serialized = serde_json::to_value(&method).unwrap();
+            assert_eq!(serialized, json!(name));
+            let deserialized: HttpMethod = serde_json::from_value(serialized).unwrap();
+            assert_eq!(deserialized, method);
+        }
+    }
+
+    #[test]
+    fn test_http_method_is_case_insensitive() {
+        for name in ["get", "Get", "gEt"] {
+            let method: HttpMethod = serde_json::from_value(json!(name)).unwrap();
+            assert_eq!(method, HttpMethod::Get, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_invalid_http_method_lists_valid_ones() {
+        for name in ["fetch", "BAD METHOD", ""] {
+            let error = serde_json::from_value::<HttpMethod>(json!(name)).unwrap_err().to_string();
+            assert!(error.contains("expected one of POST, GET, PUT, PATCH, DELETE"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_invoke_options_from_config() {
+        let options: FunctionInvokeOptions = serde_json::from_value(json!({
+            "method": "get",
+            "region": "UsEast1",
+            "headers": {"x-tenant": "acme"}
+        }))
+        .unwrap();
+
+        assert!(matches!(options.method, Some(HttpMethod::Get)));
+        assert_eq!(options.headers.unwrap()["x-tenant"], "acme");
+        assert!(options.body.is_none());
+
+        let serialized = serde_json::to_value(FunctionInvokeOptions::text("hi")).unwrap();
+        assert_eq!(serialized["method"], "POST");
+        assert_eq!(serialized["body"], json!({"String": "hi"}));
+    }
+
+    #[test]
+    fn test_invoke_options_equality() {
+        let mut body = HashMap::new();
+        body.insert("key".to_string(), json!({"nested": [1, 2.5, "three"]}));
+        let expected = FunctionInvokeOptions {
+            method: Some(HttpMethod::Post),
+            region: Some(FunctionRegion::EuWest1),
+            body: Some(InvokeBody::Json(body)),
+            ..Default::default()
+        };
+
+        let actual = FunctionInvokeOptions {
+            region: Some(FunctionRegion::EuWest1),
+            ..FunctionInvokeOptions::json([("key", json!({"nested": [1, 2.5, "three"]}))])
+        };
+        assert_eq!(actual, expected);
+        assert_ne!(actual, FunctionInvokeOptions::text("key"));
+    }
+
+    #[tokio::test]
+    async fn test_response_equality() {
+        let _m = mock("POST", "/function-name")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"key": "value"}"#)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let response = client.invoke("function-name", None).await.unwrap();
+
+        let metadata = match response {
+            FunctionsResponse::Success { ref metadata, .. } => metadata.clone(),
+            ref other => panic!("Expected success, got {:?}", other),
+        };
+        assert_eq!(response, FunctionsResponse::Success { data: ResponseData::Json(json!({"key": "value"})), metadata });
+    }
+
+    #[test]
+    fn test_region_default_and_hash() {
+        assert_eq!(FunctionRegion::default(), FunctionRegion::Any);
+
+        let mut calls: HashMap<FunctionRegion, u32> = HashMap::new();
+        for region in [FunctionRegion::UsEast1, FunctionRegion::EuWest1, FunctionRegion::UsEast1] {
+            *calls.entry(region).or_default() += 1;
+        }
+        assert_eq!(calls[&FunctionRegion::UsEast1], 2);
+        assert_eq!(calls[&FunctionRegion::EuWest1], 1);
+
+        let region = FunctionRegion::ApSouth1;
+        let copied = region;
+        assert_eq!(region.as_str(), "ap-south-1");
+        assert_eq!(copied.to_string(), "ap-south-1");
+    }
+
+    #[test]
+    fn test_response_helpers() {
+        let success = FunctionsResponse::Success {
+            data: ResponseData::Text("ok".into()),
+            metadata: ResponseMetadata { status: 200, ..Default::default() },
+        };
+        assert!(success.is_success());
+        assert_eq!(success.data(), Some(&ResponseData::Text("ok".into())));
+        assert_eq!(success.metadata().map(|m| m.status), Some(200));
+        assert_eq!(success.err(), None);
+        assert_eq!(success.into_result(), Ok(ResponseData::Text("ok".into())));
+
+        let failure = FunctionsResponse::Failure { error: FunctionsError::RelayError("relay".into()) };
+        assert!(!failure.is_success());
+        assert_eq!(failure.data(), None);
+        assert_eq!(failure.metadata(), None);
+        assert_eq!(failure.err(), Some(&FunctionsError::RelayError("relay".into())));
+        assert_eq!(failure.into_result(), Err(FunctionsError::RelayError("relay".into())));
+    }
+
+    fn curl_client() -> FunctionsClient {
+        FunctionsClient::builder("https://example.supabase.co/functions/v1")
+            .header("Authorization", "Bearer secret-token")
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_to_curl_escapes_text_bodies() {
+        let client = curl_client();
+        let options = FunctionInvokeOptions::text("it's \"quoted\" – ünïcode\nsecond line\\");
+        let prepared = client.build_request("hello world", Some(options)).await.unwrap();
+        assert_eq!(
+            prepared.to_curl(true),
+            r#"curl -X 'POST' -H 'authorization: [REDACTED]' -H 'content-type: text/plain' --data-binary $'it\'s "quoted" – ünïcode\nsecond line\\' 'https://example.supabase.co/functions/v1/hello%20world'"#
+        );
+        assert!(prepared.to_curl(false).contains("-H 'authorization: Bearer secret-token'"));
+
+        let options = FunctionInvokeOptions::json([("name", json!("O'Brien"))]);
+        let prepared = client.build_request("hello", Some(options)).await.unwrap();
+        assert_eq!(
+            prepared.to_curl(true),
+            r#"curl -X 'POST' -H 'authorization: [REDACTED]' -H 'content-type: application/json' --data-binary '{"name":"O'\''Brien"}' 'https://example.supabase.co/functions/v1/hello'"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_to_curl_binary_body() {
+        let client = curl_client();
+        let prepared = client.build_request("upload", Some(FunctionInvokeOptions::bytes(vec![0, 159, 255]))).await.unwrap();
+        assert_eq!(
+            prepared.to_curl(true),
+            "curl -X 'POST' -H 'authorization: [REDACTED]' -H 'content-type: application/octet-stream' --data-binary @- 'https://example.supabase.co/functions/v1/upload' # pipe the 3-byte binary body to stdin"
+        );
+    }
+
+    #[cfg(feature = "multipart")]
+    #[tokio::test]
+    async fn test_to_curl_form_bodies() {
+        let client = curl_client();
+        let options = FunctionInvokeOptions::form([("note", "@/etc/passwd"), ("greeting", "hi\tthere")]);
+        let prepared = client.build_request("submit", Some(options)).await.unwrap();
+        assert_eq!(
+            prepared.to_curl(true),
+            "curl -X 'POST' -H 'authorization: [REDACTED]' --form-string 'note=@/etc/passwd' --form-string $'greeting=hi\\tthere' 'https://example.supabase.co/functions/v1/submit'"
+        );
+
+        let options = FunctionInvokeOptions::form([
+            ("pages", FormValue::from(12)),
+            ("scan", FormValue::file(vec![0, 159], "scan.png", "image/png")),
+        ]);
+        let prepared = client.build_request("submit", Some(options)).await.unwrap();
+        assert_eq!(
+            prepared.to_curl(true),
+            "curl -X 'POST' -H 'authorization: [REDACTED]' --form-string 'pages=12' --form 'scan=@scan.png;type=image/png' 'https://example.supabase.co/functions/v1/submit'"
+        );
+    }
+}
@@ -0,0 +1,29 @@
+#![cfg(not(feature = "multipart"))]
+
+mod no_multipart_tests {
+    use mockito::mock;
+    use serde_json::json;
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient, HttpMethod, InvokeBody, ResponseData};
+
+    #[test]
+    fn test_form_body_is_not_available() {
+        assert!(serde_json::from_value::<InvokeBody>(json!({"FormData": [["field", "value"]]})).is_err());
+        assert!(serde_json::from_value::<InvokeBody>(json!({"String": "still here"})).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_multipart_responses_still_parse() {
+        let _m = mock("GET", "/no-multipart-parts")
+            .with_status(200)
+            .with_header("content-type", "multipart/form-data; boundary=XyZ")
+            .with_body("--XyZ\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\none\r\n--XyZ--\r\n")
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let options = FunctionInvokeOptions { method: Some(HttpMethod::Get), ..Default::default() };
+        match client.invoke("no-multipart-parts", Some(options)).await.unwrap().data() {
+            Some(ResponseData::FormData(parts)) => assert_eq!(parts.find("a").unwrap().text().unwrap(), "one"),
+            other => panic!("Expected parts, got {:?}", other),
+        }
+    }
+}
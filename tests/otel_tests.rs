@@ -0,0 +1,82 @@
+#![cfg(feature = "opentelemetry")]
+
+mod common;
+
+mod otel_tests {
+    use crate::common;
+    use mockito::mock;
+    use opentelemetry::trace::{SpanKind, Status};
+    use opentelemetry::{global, Key, Value};
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use supabase_function_rs::{FunctionsClient, RetryPolicy};
+
+    fn attributes(span: &SpanData) -> HashMap<String, Value> {
+        span.attributes.iter().map(|kv| (kv.key.to_string(), kv.value.clone())).collect()
+    }
+
+    // The invocations share one test because the tracer provider is process-global.
+    #[tokio::test]
+    async fn test_invoke_spans_follow_http_client_conventions() {
+        let exporter = InMemorySpanExporter::default();
+        global::set_tracer_provider(SdkTracerProvider::builder().with_simple_exporter(exporter.clone()).build());
+
+        let (url, requests) = common::serve_recorded(vec![
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok".to_string(),
+        ]);
+        let address = url.trim_start_matches("http://").to_string();
+        let client = FunctionsClient::builder(url.replace("http://", "http://user:secret@"))
+            .propagate_trace_context(true)
+            .build()
+            .unwrap();
+        client.invoke("ok", None).await.unwrap();
+
+        let _failing = mock("POST", "/failing").with_status(503).expect(2).create();
+        let mut client = FunctionsClient::new(mockito::server_url(), None, None);
+        client.set_retry_policy(RetryPolicy::new(2).backoff(Duration::from_millis(1), Duration::from_millis(1)));
+        client.invoke("failing", None).await.unwrap_err();
+
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        FunctionsClient::new(format!("http://127.0.0.1:{}", port), None, None).invoke("refused", None).await.unwrap_err();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 3);
+
+        let ok = &spans[0];
+        assert_eq!(ok.name, "POST");
+        assert_eq!(ok.span_kind, SpanKind::Client);
+        assert_eq!(ok.status, Status::Unset);
+        let (host, port) = address.split_once(':').unwrap();
+        let expected: HashMap<String, Value> = [
+            ("http.request.method", Value::from("POST")),
+            ("url.full", Value::from(format!("http://{}/ok", address))),
+            ("server.address", Value::from(host.to_string())),
+            ("server.port", Value::from(port.parse::<i64>().unwrap())),
+            ("http.response.status_code", Value::from(200)),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+        assert_eq!(attributes(ok), expected);
+        // The propagated trace context names the invoke span as the parent.
+        let traceparent = format!("00-{}-{}-01", ok.span_context.trace_id(), ok.span_context.span_id());
+        assert!(requests.lock().unwrap()[0].contains(&traceparent));
+
+        let failed = &spans[1];
+        let failed_attributes = attributes(failed);
+        assert!(matches!(failed.status, Status::Error { ref description } if description.contains("503")));
+        assert_eq!(failed_attributes["url.full"], Value::from(format!("{}/failing", mockito::server_url())));
+        assert_eq!(failed_attributes["http.response.status_code"], Value::from(503));
+        assert_eq!(failed_attributes["error.type"], Value::from("503"));
+        assert_eq!(failed_attributes["http.request.resend_count"], Value::from(1));
+        let events: Vec<_> = failed.events.iter().collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name, "retry");
+        assert!(events[0].attributes.iter().any(|kv| kv.key == Key::from("http.response.status_code") && kv.value == Value::from(503)));
+
+        let refused = attributes(&spans[2]);
+        assert_eq!(refused["error.type"], Value::from("connect_error"));
+        assert!(!refused.contains_key("http.response.status_code"));
+    }
+}
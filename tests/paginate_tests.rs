@@ -0,0 +1,55 @@
+mod common;
+
+mod paginate_tests {
+    use crate::common;
+    use futures_util::StreamExt;
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient, FunctionsError, HttpMethod, Pagination};
+
+    fn page(body: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    }
+
+    fn by_cursor(options: &mut FunctionInvokeOptions, cursor: &str) {
+        options.query.push(("cursor".to_string(), cursor.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_pages_stream_out_in_order() {
+        let (url, requests) = common::serve_recorded(vec![
+            page(r#"{"items":[1,2],"next_cursor":"b"}"#),
+            page(r#"{"items":[3],"next_cursor":"c"}"#),
+            page(r#"{"items":[4,5],"next_cursor":null}"#),
+        ]);
+        let client = FunctionsClient::new(url, None, None);
+        let query = vec![("limit".to_string(), "2".to_string())];
+        let options = FunctionInvokeOptions { method: Some(HttpMethod::Get), query, ..Default::default() };
+
+        let items: Vec<Result<u32, FunctionsError>> = client.invoke_paginated("list", Some(options), "next_cursor", by_cursor).collect().await;
+        assert_eq!(items.into_iter().collect::<Result<Vec<_>, _>>().unwrap(), vec![1, 2, 3, 4, 5]);
+
+        let requests = requests.lock().unwrap();
+        let lines: Vec<_> = requests.iter().map(|head| head.lines().next().unwrap()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "GET /list?limit=2 HTTP/1.1",
+                "GET /list?limit=2&cursor=b HTTP/1.1",
+                "GET /list?limit=2&cursor=c HTTP/1.1",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_endless_cursor_stops_at_max_pages() {
+        let url = common::serve(vec![page(r#"{"rows":["a"],"next":7}"#), page(r#"{"rows":["b"],"next":7}"#)]);
+        let client = FunctionsClient::new(url, None, None);
+        let pagination = Pagination::new("next").items_field("rows").max_pages(2);
+
+        let items: Vec<Result<String, FunctionsError>> = client.invoke_paginated("list", None, pagination, by_cursor).collect().await;
+        assert_eq!(items, vec![Ok("a".to_string()), Ok("b".to_string()), Err(FunctionsError::PageLimit { max_pages: 2 })]);
+    }
+}
@@ -0,0 +1,45 @@
+mod poll_tests {
+    use mockito::mock;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::time::Duration;
+    use supabase_function_rs::{FunctionsClient, FunctionsError, MockSleeper, PollInterval};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Job {
+        state: String,
+    }
+
+    #[tokio::test]
+    async fn test_polls_until_the_predicate_matches() {
+        let pending = mock("POST", "/poll-job").with_header("content-type", "application/json").with_body(r#"{"state":"pending"}"#).expect(2).create();
+        let done = mock("POST", "/poll-job").with_header("content-type", "application/json").with_body(r#"{"state":"done"}"#).expect(1).create();
+
+        let sleeper = MockSleeper::new();
+        let mut client = FunctionsClient::new(mockito::server_url(), None, None);
+        client.set_sleeper(sleeper.clone());
+        let interval = PollInterval::backoff(Duration::from_millis(100), Duration::from_secs(1));
+        let job: Job = client.poll_until("poll-job", None, interval, Duration::from_secs(60), |job: &Job| job.state == "done").await.unwrap();
+
+        assert_eq!(job, Job { state: "done".to_string() });
+        assert_eq!(sleeper.slept(), vec![Duration::from_millis(100), Duration::from_millis(200)]);
+        pending.assert();
+        done.assert();
+    }
+
+    #[tokio::test]
+    async fn test_timeout_carries_the_last_value() {
+        let _m = mock("POST", "/poll-stuck").with_header("content-type", "application/json").with_body(r#"{"state":"pending"}"#).create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let result = client.poll_until("poll-stuck", None, Duration::from_millis(20), Duration::from_millis(200), |job: &Job| job.state == "done").await;
+        match result {
+            Err(FunctionsError::PollTimeout { elapsed, polls, last }) => {
+                assert!(elapsed >= Duration::from_millis(200), "{:?}", elapsed);
+                assert!(polls >= 2, "{}", polls);
+                assert_eq!(last, Some(json!({"state": "pending"})));
+            }
+            other => panic!("Expected PollTimeout, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+mod common;
+
+mod progress_tests {
+    use crate::common;
+    use futures_util::StreamExt;
+    use mockito::mock;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient, HttpMethod, ResponseData};
+
+    type Reports = Arc<Mutex<Vec<(u64, Option<u64>)>>>;
+
+    fn get_with_progress() -> (FunctionInvokeOptions, Reports) {
+        let reports = Reports::default();
+        let recorded = reports.clone();
+        let options = FunctionInvokeOptions { method: Some(HttpMethod::Get), ..Default::default() }
+            .on_download_progress(move |received, total| recorded.lock().unwrap().push((received, total)));
+        (options, reports)
+    }
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok";
+
+    #[tokio::test]
+    async fn test_upload_progress_counts_up_to_total() {
+        let (url, requests) = common::serve_recorded(vec![OK.to_string()]);
+        let body = vec![7u8; 300 * 1024 + 5];
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let recorded = reports.clone();
+        let options = FunctionInvokeOptions::bytes(body.clone())
+            .on_upload_progress(move |sent, total| recorded.lock().unwrap().push((sent, total)));
+
+        let client = FunctionsClient::new(url, None, None);
+        client.invoke("upload", Some(options)).await.unwrap();
+
+        let reports = reports.lock().unwrap();
+        let total = body.len() as u64;
+        assert!(reports.len() > 1, "{:?}", reports);
+        assert!(reports.windows(2).all(|pair| pair[0].0 < pair[1].0), "{:?}", reports);
+        assert!(reports.iter().all(|&(_, reported)| reported == Some(total)), "{:?}", reports);
+        assert_eq!(reports.last(), Some(&(total, Some(total))));
+
+        // The body keeps its length instead of being sent chunked.
+        let head = requests.lock().unwrap()[0].to_ascii_lowercase();
+        assert!(head.contains(&format!("content-length: {}", total)), "{}", head);
+        assert!(!head.contains("transfer-encoding"), "{}", head);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_callback_does_not_abort_upload() {
+        let url = common::serve(vec![OK.to_string()]);
+        let options = FunctionInvokeOptions::bytes(vec![1u8; 100_000]).on_upload_progress(|_, _| panic!("progress bar gone"));
+
+        let client = FunctionsClient::new(url, None, None);
+        client.invoke("upload", Some(options)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_progress_reaches_content_length() {
+        let body = vec![3u8; 500_000];
+        let _m = mock("GET", "/download-weights")
+            .with_status(200)
+            .with_header("content-type", "application/octet-stream")
+            .with_body(&body)
+            .create();
+
+        let (options, reports) = get_with_progress();
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let response = client.invoke("download-weights", Some(options)).await.unwrap();
+        assert_eq!(response.data(), Some(&ResponseData::Bytes(body.clone().into())));
+
+        let reports = reports.lock().unwrap();
+        let total = body.len() as u64;
+        assert!(reports.windows(2).all(|pair| pair[0].0 < pair[1].0), "{:?}", reports);
+        assert!(reports.iter().all(|&(_, reported)| reported == Some(total)), "{:?}", reports);
+        assert_eq!(reports.last(), Some(&(total, Some(total))));
+    }
+
+    #[tokio::test]
+    async fn test_download_progress_keeps_total_with_idle_timeout() {
+        let body = vec![5u8; 200_000];
+        let _m = mock("GET", "/download-idle").with_status(200).with_header("content-type", "application/octet-stream").with_body(&body).create();
+
+        let (options, reports) = get_with_progress();
+        let options = FunctionInvokeOptions { idle_timeout: Some(Duration::from_secs(5)), ..options };
+        let client = FunctionsClient::builder(mockito::server_url()).idle_timeout(Duration::from_secs(5)).build().unwrap();
+        client.invoke("download-idle", Some(options)).await.unwrap();
+
+        let total = body.len() as u64;
+        assert_eq!(reports.lock().unwrap().last(), Some(&(total, Some(total))));
+    }
+
+    #[tokio::test]
+    async fn test_download_progress_of_chunked_stream_has_no_total() {
+        let body = "[1,2,3]";
+        let chunked = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n3\r\n[1,\r\n4\r\n2,3]\r\n0\r\n\r\n";
+        let url = common::serve(vec![chunked.to_string()]);
+
+        let (options, reports) = get_with_progress();
+        let client = FunctionsClient::new(url, None, None);
+        let items: Vec<u32> = client.invoke_json_array_stream::<u32>("export", Some(options)).map(Result::unwrap).collect().await;
+        assert_eq!(items, [1, 2, 3]);
+        assert_eq!(reports.lock().unwrap().last(), Some(&(body.len() as u64, None)));
+        assert!(reports.lock().unwrap().iter().all(|&(_, total)| total.is_none()));
+    }
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "prost")]
+
+mod protobuf_tests {
+    use mockito::mock;
+    use prost::Message;
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient, FunctionsError, InvokeBody, ResponseData};
+
+    // What prost-build generates for `message Ping { uint32 id = 1; string name = 2; repeated string tags = 3; }`.
+    #[derive(Clone, PartialEq, Message)]
+    struct Ping {
+        #[prost(uint32, tag = "1")]
+        id: u32,
+        #[prost(string, tag = "2")]
+        name: String,
+        #[prost(string, repeated, tag = "3")]
+        tags: Vec<String>,
+    }
+
+    fn ping() -> Ping {
+        Ping { id: 7, name: "ada".into(), tags: vec!["a".into(), "b".into()] }
+    }
+
+    #[tokio::test]
+    async fn test_protobuf_round_trip() {
+        let encoded = ping().encode_to_vec();
+        let _m = mock("POST", "/protobuf-echo")
+            .match_header("content-type", "application/x-protobuf")
+            .match_body(String::from_utf8(encoded.clone()).unwrap().as_str())
+            .with_status(200)
+            .with_header("content-type", "application/x-protobuf")
+            .with_body(&encoded)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let options = FunctionInvokeOptions { body: Some(InvokeBody::protobuf(&ping())), ..Default::default() };
+        let response = client.invoke("protobuf-echo", Some(options)).await.unwrap();
+        assert_eq!(response.data().unwrap().protobuf_into::<Ping>().unwrap(), ping());
+    }
+
+    #[test]
+    fn test_protobuf_decode_errors() {
+        // A field tag whose varint value is cut off.
+        match ResponseData::Bytes(vec![0x08].into()).protobuf_into::<Ping>() {
+            Err(FunctionsError::DeserializeError(message)) => {
+                assert!(message.starts_with("invalid protobuf message: "), "{}", message)
+            }
+            other => panic!("Expected DeserializeError, got {:?}", other),
+        }
+        match ResponseData::Text("not protobuf".into()).protobuf_into::<Ping>() {
+            Err(FunctionsError::DeserializeError(message)) => {
+                assert_eq!(message, "expected a protobuf message, got Text(\"not protobuf\")")
+            }
+            other => panic!("Expected DeserializeError, got {:?}", other),
+        }
+    }
+}
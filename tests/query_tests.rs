@@ -0,0 +1,73 @@
+mod query_tests {
+    use mockito::{mock, Matcher};
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+    use supabase_function_rs::{ArrayStyle, FunctionInvokeOptions, FunctionsClient, FunctionsError, HttpMethod};
+
+    fn get() -> FunctionInvokeOptions {
+        FunctionInvokeOptions { method: Some(HttpMethod::Get), ..Default::default() }
+    }
+
+    #[derive(Serialize)]
+    struct Ids {
+        ids: Vec<u32>,
+    }
+
+    #[tokio::test]
+    async fn test_array_styles_on_the_wire() {
+        let params = || get().query_param("ids", "1").query_param("q", "a b&c").query_param("ids", "2,3").query_param("tags[x]", "[y]");
+        let one = || get().query_from(&Ids { ids: vec![4] }).unwrap().query_param("q", "x");
+        let cases = [
+            (params(), ArrayStyle::Repeat, "ids=1&ids=2%2C3&q=a+b%26c&tags[x]=%5By%5D"),
+            (params(), ArrayStyle::Brackets, "ids[]=1&ids[]=2%2C3&q=a+b%26c&tags[x]=%5By%5D"),
+            (params(), ArrayStyle::CommaSeparated, "ids=1,2%2C3&q=a+b%26c&tags[x]=%5By%5D"),
+            (one(), ArrayStyle::Repeat, "ids=4&q=x"),
+            (one(), ArrayStyle::Brackets, "ids[]=4&q=x"),
+            (one(), ArrayStyle::CommaSeparated, "ids=4&q=x"),
+        ];
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        for (options, style, expected) in cases {
+            let m = mock("GET", "/query-styles")
+                .match_query(Matcher::Exact(expected.to_string()))
+                .with_status(200)
+                .expect(1)
+                .create();
+            let prepared = client.build_request("query-styles", Some(options.clone().array_style(style))).await.unwrap();
+            assert_eq!(prepared.url.query(), Some(expected), "{:?}", style);
+            client.invoke("query-styles", Some(options.array_style(style))).await.unwrap();
+            m.assert();
+        }
+
+        let prepared = client.build_request("query-styles", Some(get().query_from(&Ids { ids: Vec::new() }).unwrap())).await.unwrap();
+        assert_eq!(prepared.url.query(), None);
+    }
+
+    #[tokio::test]
+    async fn test_query_from_struct() {
+        #[derive(Serialize)]
+        struct Search {
+            term: &'static str,
+            ids: Vec<u32>,
+            archived: bool,
+            page: Option<u32>,
+            filter: BTreeMap<&'static str, &'static str>,
+        }
+        let search = Search { term: "café", ids: vec![4, 5], archived: false, page: None, filter: [("status", "open")].into() };
+
+        let _m = mock("GET", "/query-struct")
+            .match_query(Matcher::Exact("archived=false&filter[status]=open&ids[]=4&ids[]=5&term=caf%C3%A9".into()))
+            .with_status(200)
+            .expect(1)
+            .create();
+        let options = get().query_from(&search).unwrap().array_style(ArrayStyle::Brackets);
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        client.invoke("query-struct", Some(options)).await.unwrap();
+        _m.assert();
+
+        match get().query_from(&vec![1, 2]) {
+            Err(FunctionsError::BuildError(message)) => assert_eq!(message, "query must be a struct or map, got [1,2]"),
+            other => panic!("Expected BuildError, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+mod common;
+
+mod redirect_tests {
+    use crate::common;
+    use mockito::{mock, Matcher};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use supabase_function_rs::{
+        FunctionInvokeOptions, FunctionsClient, FunctionsError, FunctionsResponse, RedirectPolicy,
+    };
+
+    fn text_body(text: &str) -> Option<FunctionInvokeOptions> {
+        Some(FunctionInvokeOptions::text(text))
+    }
+
+    #[tokio::test]
+    async fn test_follows_redirect_chain() {
+        let url = mockito::server_url();
+        let _old = mock("POST", "/old-name").with_status(307).with_header("location", "/mid").create();
+        let _mid = mock("POST", "/mid")
+            .with_status(307)
+            .with_header("location", &format!("{}/function-name", url))
+            .create();
+        let target = mock("POST", "/function-name")
+            .match_header("authorization", "Bearer test-token")
+            .match_body("payload")
+            .with_status(200)
+            .with_body("ok")
+            .expect(1)
+            .create();
+
+        let client = FunctionsClient::new(url, None, None);
+        client.set_auth("test-token".to_string()).unwrap();
+
+        match client.invoke("old-name", text_body("payload")).await {
+            Ok(FunctionsResponse::Success { .. }) => {}
+            other => panic!("Expected success, got {:?}", other),
+        }
+        target.assert();
+    }
+
+    #[tokio::test]
+    async fn test_redirects_disabled() {
+        let _old = mock("POST", "/old-name").with_status(307).with_header("location", "/mid").create();
+        let mid = mock("POST", "/mid").expect(0).create();
+
+        let client = FunctionsClient::builder(mockito::server_url())
+            .redirect_policy(RedirectPolicy::None)
+            .build()
+            .unwrap();
+
+        match client.invoke("old-name", None).await {
+            Err(FunctionsError::HttpError { status, metadata, .. }) => {
+                assert_eq!(status, 307);
+                assert_eq!(metadata.location(), Some("/mid"));
+            }
+            other => panic!("Expected HttpError, got {:?}", other),
+        }
+        mid.assert();
+    }
+
+    #[tokio::test]
+    async fn test_redirect_limit() {
+        let _old = mock("POST", "/old-name").with_status(307).with_header("location", "/mid").create();
+        let _mid = mock("POST", "/mid").with_status(307).with_header("location", "/function-name").create();
+        let target = mock("POST", "/function-name").expect(0).create();
+
+        let client = FunctionsClient::builder(mockito::server_url())
+            .redirect_policy(RedirectPolicy::Limited(1))
+            .build()
+            .unwrap();
+
+        match client.invoke("old-name", None).await {
+            Err(FunctionsError::HttpError { status, metadata, .. }) => {
+                assert_eq!(status, 307);
+                assert_eq!(metadata.location(), Some("/function-name"));
+            }
+            other => panic!("Expected HttpError, got {:?}", other),
+        }
+        target.assert();
+    }
+
+    #[tokio::test]
+    async fn test_custom_redirect_predicate() {
+        let _old = mock("POST", "/old-name").with_status(308).with_header("location", "/mid").create();
+        let _mid = mock("POST", "/mid").with_status(307).with_header("location", "/function-name").create();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let seen = calls.clone();
+        let client = FunctionsClient::builder(mockito::server_url())
+            .redirect_policy(RedirectPolicy::custom(move |attempt| {
+                seen.fetch_add(1, Ordering::SeqCst);
+                attempt.status == 308
+            }))
+            .build()
+            .unwrap();
+
+        match client.invoke("old-name", None).await {
+            Err(FunctionsError::HttpError { status, .. }) => assert_eq!(status, 307),
+            other => panic!("Expected HttpError, got {:?}", other),
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_see_other_switches_to_get() {
+        let _old = mock("POST", "/old-name").with_status(303).with_header("location", "/function-name").create();
+        let target = mock("GET", "/function-name")
+            .match_header("content-type", Matcher::Missing)
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        assert!(client.invoke("old-name", text_body("payload")).await.is_ok());
+        target.assert();
+    }
+
+    fn redirect_to_mockito_port() -> String {
+        let location = format!("{}/function-name", mockito::server_url());
+        common::serve(vec![format!(
+            "HTTP/1.1 307 Temporary Redirect\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            location
+        )])
+    }
+
+    #[tokio::test]
+    async fn test_auth_dropped_on_port_change_by_default() {
+        let target = mock("POST", "/function-name")
+            .match_header("authorization", Matcher::Missing)
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let client = FunctionsClient::new(redirect_to_mockito_port(), None, None);
+        client.set_auth("test-token".to_string()).unwrap();
+
+        assert!(client.invoke("function-name", None).await.is_ok());
+        target.assert();
+    }
+
+    #[tokio::test]
+    async fn test_auth_preserved_on_same_host_redirect() {
+        let target = mock("POST", "/function-name")
+            .match_header("authorization", "Bearer test-token")
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let client = FunctionsClient::builder(redirect_to_mockito_port())
+            .header("Authorization", "Bearer test-token")
+            .preserve_auth_on_same_host_redirect(true)
+            .build()
+            .unwrap();
+
+        assert!(client.invoke("function-name", None).await.is_ok());
+        target.assert();
+    }
+
+    #[tokio::test]
+    async fn test_final_url_after_redirect() {
+        let _old = mock("POST", "/old-name").with_status(307).with_header("location", "/function-name").create();
+        let _target = mock("POST", "/function-name").with_status(200).with_body("ok").create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        match client.invoke("old-name", None).await {
+            Ok(FunctionsResponse::Success { metadata, .. }) => {
+                let final_url = metadata.url.expect("final url");
+                assert_eq!(final_url.as_str(), format!("{}/function-name", mockito::server_url()));
+                assert_ne!(final_url.path(), "/old-name");
+            }
+            other => panic!("Expected success, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_final_url_on_http_error() {
+        let _old = mock("POST", "/old-name").with_status(307).with_header("location", "/function-name").create();
+        let _target = mock("POST", "/function-name").with_status(500).create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+
+        match client.invoke("old-name", None).await {
+            Err(FunctionsError::HttpError { metadata, .. }) => {
+                assert_eq!(metadata.url.expect("final url").path(), "/function-name");
+            }
+            other => panic!("Expected HttpError, got {:?}", other),
+        }
+    }
+}
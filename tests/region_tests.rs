@@ -0,0 +1,28 @@
+mod region_tests {
+    use mockito::{mock, Matcher};
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionRegion, FunctionsClient};
+
+    #[tokio::test]
+    async fn test_client_region_is_sent_when_the_invoke_sets_none() {
+        let m = mock("POST", "/client-region").match_header("x-region", "eu-west-1").with_status(200).expect(1).create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, Some(FunctionRegion::EuWest1));
+        client.invoke("client-region", None).await.unwrap();
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_invoke_region_overrides_the_client_region() {
+        let m = mock("POST", "/invoke-region").match_header("x-region", "us-east-1").with_status(200).expect(1).create();
+        let any = mock("POST", "/any-region").match_header("x-region", Matcher::Missing).with_status(200).expect(1).create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, Some(FunctionRegion::EuWest1));
+        let options = FunctionInvokeOptions { region: Some(FunctionRegion::UsEast1), ..Default::default() };
+        client.invoke("invoke-region", Some(options)).await.unwrap();
+
+        let options = FunctionInvokeOptions { region: Some(FunctionRegion::Any), ..Default::default() };
+        client.invoke("any-region", Some(options)).await.unwrap();
+        m.assert();
+        any.assert();
+    }
+}
@@ -0,0 +1,395 @@
+mod retry_tests {
+    use mockito::mock;
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use supabase_function_rs::{
+        AttemptFailure, AttemptOutcome, ConnectErrorKind, FunctionInvokeOptions, FunctionsClient, FunctionsError, FunctionsResponse, RetryDecision,
+        RetryEvent, RetryPolicy, MockSleeper, TimeoutKind,
+    };
+
+    fn client_with(policy: RetryPolicy, sleeper: &MockSleeper) -> FunctionsClient {
+        let mut client = FunctionsClient::new(mockito::server_url(), None, None);
+        client.set_retry_policy(policy);
+        client.set_sleeper(sleeper.clone());
+        client
+    }
+
+    #[tokio::test]
+    async fn test_retries_with_exponential_backoff() {
+        let m = mock("POST", "/function-name").with_status(503).expect(4).create();
+
+        let sleeper = MockSleeper::new();
+        let policy = RetryPolicy::new(4).backoff(Duration::from_millis(100), Duration::from_secs(1));
+        let client = client_with(policy, &sleeper);
+
+        match client.invoke("function-name", None).await {
+            Err(FunctionsError::RetriesExhausted { attempts, last_error, .. }) => {
+                assert_eq!(attempts, 4);
+                assert!(matches!(*last_error, FunctionsError::HttpError { status: 503, .. }));
+            }
+            other => panic!("Expected RetriesExhausted, got {:?}", other),
+        }
+        assert_eq!(
+            sleeper.slept(),
+            vec![Duration::from_millis(100), Duration::from_millis(200), Duration::from_millis(400)]
+        );
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_retry_succeeds_after_failure() {
+        let failure = mock("POST", "/function-name").with_status(502).expect(1).create();
+        let success = mock("POST", "/function-name").with_status(200).with_body("ok").expect(1).create();
+
+        let sleeper = MockSleeper::new();
+        let client = client_with(RetryPolicy::new(3), &sleeper);
+
+        match client.invoke("function-name", None).await {
+            Ok(FunctionsResponse::Success { .. }) => {}
+            other => panic!("Expected success, got {:?}", other),
+        }
+        assert_eq!(sleeper.slept().len(), 1);
+        failure.assert();
+        success.assert();
+    }
+
+    #[tokio::test]
+    async fn test_non_retryable_status_is_not_retried() {
+        let m = mock("POST", "/function-name").with_status(400).expect(1).create();
+
+        let sleeper = MockSleeper::new();
+        let client = client_with(RetryPolicy::new(3), &sleeper);
+
+        match client.invoke("function-name", None).await {
+            Err(FunctionsError::HttpError { status, .. }) => assert_eq!(status, 400),
+            other => panic!("Expected HttpError, got {:?}", other),
+        }
+        assert!(sleeper.slept().is_empty());
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_configured_status_is_retried() {
+        for policy in [RetryPolicy::new(3).retry_on_statuses([522, 599]), RetryPolicy::new(3).gateway_errors()] {
+            let failure = mock("POST", "/function-name").with_status(522).expect(1).create();
+            let success = mock("POST", "/function-name").with_status(200).with_body("ok").expect(1).create();
+
+            let sleeper = MockSleeper::new();
+            let client = client_with(policy, &sleeper);
+            assert!(client.invoke("function-name", None).await.unwrap().is_success());
+            assert_eq!(sleeper.slept().len(), 1);
+            failure.assert();
+            success.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unlisted_status_is_not_retried() {
+        let m = mock("POST", "/function-name").with_status(522).expect(1).create();
+
+        let sleeper = MockSleeper::new();
+        let client = client_with(RetryPolicy::new(3), &sleeper);
+
+        match client.invoke("function-name", None).await {
+            Err(FunctionsError::HttpError { status, .. }) => assert_eq!(status, 522),
+            other => panic!("Expected HttpError, got {:?}", other),
+        }
+        assert!(sleeper.slept().is_empty());
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_are_retried_only_when_listed() {
+        let not_retried = mock("POST", "/function-name").with_status(429).expect(1).create();
+        let sleeper = MockSleeper::new();
+        let client = client_with(RetryPolicy::new(3).server_errors(), &sleeper);
+        assert!(matches!(
+            client.invoke("function-name", None).await,
+            Err(FunctionsError::HttpError { status: 429, .. })
+        ));
+        not_retried.assert();
+
+        let retried = mock("POST", "/function-name").with_status(429).expect(3).create();
+        let client = client_with(RetryPolicy::new(3).retry_on_statuses([429]), &sleeper);
+        match client.invoke("function-name", None).await {
+            Err(FunctionsError::RetriesExhausted { attempts, .. }) => assert_eq!(attempts, 3),
+            other => panic!("Expected RetriesExhausted, got {:?}", other),
+        }
+        retried.assert();
+    }
+
+    fn body_says_retry(outcome: &AttemptOutcome) -> RetryDecision {
+        let body: serde_json::Value = serde_json::from_slice(outcome.body).unwrap_or_default();
+        match (outcome.status, body["retryable"].as_bool()) {
+            (Some(409), Some(true)) => RetryDecision::RetryAfter(Duration::from_millis(50)),
+            _ if outcome.retryable => RetryDecision::Retry,
+            _ => RetryDecision::Stop,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_predicate_retries_on_body() {
+        let conflict = mock("POST", "/function-name")
+            .with_status(409)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"retryable": true}"#)
+            .expect(1)
+            .create();
+        let success = mock("POST", "/function-name").with_status(200).with_body("ok").expect(1).create();
+
+        let sleeper = MockSleeper::new();
+        let client = client_with(RetryPolicy::new(3).retry_if(body_says_retry), &sleeper);
+
+        assert!(client.invoke("function-name", None).await.unwrap().is_success());
+        assert_eq!(sleeper.slept(), vec![Duration::from_millis(50)]);
+        conflict.assert();
+        success.assert();
+    }
+
+    #[tokio::test]
+    async fn test_predicate_stop_keeps_body() {
+        let m = mock("POST", "/function-name").with_status(503).with_body("maintenance until 10:00").expect(1).create();
+
+        let sleeper = MockSleeper::new();
+        let policy = RetryPolicy::new(3).retry_if(|outcome: &AttemptOutcome| {
+            assert!(outcome.retryable);
+            RetryDecision::Stop
+        });
+        let client = client_with(policy, &sleeper);
+
+        match client.invoke("function-name", None).await {
+            Err(FunctionsError::HttpError { status, body, .. }) => {
+                assert_eq!(status, 503);
+                assert_eq!(&body[..], b"maintenance until 10:00");
+            }
+            other => panic!("Expected HttpError, got {:?}", other),
+        }
+        assert!(sleeper.slept().is_empty());
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_predicate_sees_transport_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorded = seen.clone();
+        let policy = RetryPolicy::new(2).retry_if(move |outcome: &AttemptOutcome| {
+            recorded.lock().unwrap().push((outcome.status, outcome.error.is_some(), outcome.retryable));
+            RetryDecision::Stop
+        });
+        let mut client = FunctionsClient::new(url, None, None);
+        client.set_retry_policy(policy);
+
+        assert!(client.invoke("function-name", None).await.is_err());
+        assert_eq!(*seen.lock().unwrap(), vec![(None, true, true)]);
+    }
+
+    #[tokio::test]
+    async fn test_on_retry_is_called_before_each_sleep() {
+        let failure = mock("POST", "/function-name").with_status(503).expect(1).create();
+        let reset = mock("POST", "/function-name").with_status(502).expect(1).create();
+        let success = mock("POST", "/function-name").with_status(200).with_body("ok").expect(1).create();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let policy = RetryPolicy::new(3)
+            .backoff(Duration::from_millis(100), Duration::from_secs(1))
+            .on_retry(move |event: &RetryEvent| {
+                recorded.lock().unwrap().push((event.function_name.to_string(), event.attempt, event.status, event.delay));
+            });
+        let sleeper = MockSleeper::new();
+        let client = client_with(policy, &sleeper);
+
+        assert!(client.invoke("function-name", None).await.unwrap().is_success());
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ("function-name".to_string(), 1, Some(503), Duration::from_millis(100)),
+                ("function-name".to_string(), 2, Some(502), Duration::from_millis(200)),
+            ]
+        );
+        failure.assert();
+        reset.assert();
+        success.assert();
+    }
+
+    #[tokio::test]
+    async fn test_panicking_on_retry_does_not_fail_invoke() {
+        let failure = mock("POST", "/function-name").with_status(503).expect(1).create();
+        let success = mock("POST", "/function-name").with_status(200).with_body("ok").expect(1).create();
+
+        let policy = RetryPolicy::new(3).on_retry(|_: &RetryEvent| panic!("dashboard unavailable"));
+        let client = client_with(policy, &MockSleeper::new());
+
+        assert!(client.invoke("function-name", None).await.unwrap().is_success());
+        failure.assert();
+        success.assert();
+    }
+
+    #[tokio::test]
+    async fn test_max_elapsed_stops_retrying() {
+        let m = mock("POST", "/function-name")
+            .with_status(503)
+            .with_header("retry-after", "10")
+            .expect(2)
+            .create();
+
+        let sleeper = MockSleeper::new();
+        let policy = RetryPolicy::new(10).max_elapsed(Duration::from_secs(15));
+        let client = client_with(policy, &sleeper);
+
+        match client.invoke("function-name", None).await {
+            Err(FunctionsError::RetriesExhausted { attempts, elapsed, .. }) => {
+                assert_eq!(attempts, 2);
+                assert!(elapsed >= Duration::from_secs(10) && elapsed < Duration::from_secs(15));
+            }
+            other => panic!("Expected RetriesExhausted, got {:?}", other),
+        }
+        assert_eq!(sleeper.slept(), vec![Duration::from_secs(10)]);
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_max_elapsed_bounds_attempt_timeout() {
+        // Accepts connections but never answers them.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let sleeper = MockSleeper::new();
+        let mut client = FunctionsClient::new(url, None, None);
+        client.set_timeout(Duration::from_secs(10));
+        client.set_retry_policy(RetryPolicy::new(5).max_elapsed(Duration::from_millis(300)));
+        client.set_sleeper(sleeper.clone());
+
+        let start = Instant::now();
+        match client.invoke("function-name", None).await {
+            Err(FunctionsError::RetriesExhausted { attempts, last_error, .. }) => {
+                assert_eq!(attempts, 1);
+                match *last_error {
+                    FunctionsError::Timeout { kind, limit, elapsed, attempt, max_attempts } => {
+                        assert_eq!(kind, TimeoutKind::Deadline);
+                        assert_eq!(limit, Some(Duration::from_millis(300)));
+                        assert!(elapsed >= Duration::from_millis(300), "{:?}", elapsed);
+                        assert_eq!((attempt, max_attempts), (1, 5));
+                    }
+                    other => panic!("Expected Timeout, got {:?}", other),
+                }
+            }
+            other => panic!("Expected RetriesExhausted, got {:?}", other),
+        }
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(sleeper.slept().is_empty());
+    }
+
+    // Accepts connections but never answers them.
+    fn silent_server() -> (TcpListener, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        (listener, url)
+    }
+
+    #[tokio::test]
+    async fn test_attempt_timeout_reports_limit_and_elapsed() {
+        let (_listener, url) = silent_server();
+        let client = FunctionsClient::new(url, None, None);
+        let options = FunctionInvokeOptions { timeout: Some(Duration::from_millis(200)), ..Default::default() };
+
+        let error = client.invoke("function-name", Some(options)).await.unwrap_err();
+        match error {
+            FunctionsError::Timeout { kind, limit, elapsed, attempt, max_attempts } => {
+                assert_eq!(kind, TimeoutKind::Attempt);
+                assert_eq!(limit, Some(Duration::from_millis(200)));
+                assert!(elapsed >= Duration::from_millis(200) && elapsed < Duration::from_secs(2), "{:?}", elapsed);
+                assert_eq!((attempt, max_attempts), (1, 1));
+            }
+            ref other => panic!("Expected Timeout, got {:?}", other),
+        }
+        let message = error.to_string();
+        assert!(message.starts_with("Timeout: gave up after 2"), "{}", message);
+        assert!(message.ends_with("ms (attempt timeout 200ms, attempt 1/1)"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_reports_attempt_number() {
+        let (_listener, url) = silent_server();
+        let sleeper = MockSleeper::new();
+        let mut client = FunctionsClient::new(url, None, None);
+        client.set_timeout(Duration::from_millis(100));
+        client.set_retry_policy(RetryPolicy::new(3));
+        client.set_sleeper(sleeper.clone());
+
+        match client.invoke("function-name", None).await {
+            Err(FunctionsError::RetriesExhausted { attempts: 3, last_error, .. }) => match *last_error {
+                FunctionsError::Timeout { kind, limit, elapsed, attempt, max_attempts } => {
+                    assert_eq!(kind, TimeoutKind::Attempt);
+                    assert_eq!(limit, Some(Duration::from_millis(100)));
+                    assert!(elapsed >= Duration::from_millis(100) && elapsed < Duration::from_secs(2), "{:?}", elapsed);
+                    assert_eq!((attempt, max_attempts), (3, 3));
+                }
+                other => panic!("Expected Timeout, got {:?}", other),
+            },
+            other => panic!("Expected RetriesExhausted, got {:?}", other),
+        }
+    }
+
+    async fn read_head(stream: &mut tokio::net::TcpStream) {
+        use tokio::io::AsyncReadExt;
+        let mut head = Vec::new();
+        let mut buf = [0; 1024];
+        while !head.ends_with(b"\r\n\r\n") {
+            let n = stream.read(&mut buf).await.unwrap();
+            assert!(n > 0);
+            head.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_error_summarizes_each_attempt() {
+        use tokio::io::AsyncWriteExt;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut unavailable, _) = listener.accept().await.unwrap();
+            read_head(&mut unavailable).await;
+            let response = "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+            unavailable.write_all(response.as_bytes()).await.unwrap();
+
+            let (mut reset, _) = listener.accept().await.unwrap();
+            read_head(&mut reset).await;
+            reset.set_zero_linger().unwrap();
+            drop(reset);
+
+            let (mut silent, _) = listener.accept().await.unwrap();
+            read_head(&mut silent).await;
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        let sleeper = MockSleeper::new();
+        let mut client = FunctionsClient::new(url, None, None);
+        client.set_timeout(Duration::from_millis(200));
+        client.set_retry_policy(RetryPolicy::new(3));
+        client.set_sleeper(sleeper.clone());
+
+        let error = client.invoke("function-name", None).await.unwrap_err();
+        assert!(error.to_string().starts_with("RetriesExhausted: gave up after 3 attempt(s)"), "{}", error);
+        match error {
+            FunctionsError::RetriesExhausted { history, .. } => {
+                let failures: Vec<_> = history.iter().map(|summary| (summary.attempt, summary.failure.clone())).collect();
+                assert_eq!(
+                    failures,
+                    [
+                        (1, AttemptFailure::Status(503)),
+                        (2, AttemptFailure::Connect(ConnectErrorKind::ConnectionReset)),
+                        (3, AttemptFailure::Timeout(TimeoutKind::Attempt)),
+                    ]
+                );
+                assert!(history[2].elapsed >= Duration::from_millis(200), "{:?}", history[2].elapsed);
+            }
+            other => panic!("Expected RetriesExhausted, got {:?}", other),
+        }
+    }
+}
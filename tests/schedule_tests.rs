@@ -0,0 +1,48 @@
+#![cfg(feature = "tokio")]
+
+mod schedule_tests {
+    use mockito::mock;
+    use std::time::{Duration, Instant};
+    use supabase_function_rs::{FunctionsClient, FunctionsResponse};
+
+    #[tokio::test]
+    async fn test_invoke_after_fires_after_delay() {
+        let m = mock("POST", "/scheduled-fire").with_status(200).with_body("sent").expect(1).create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let start = Instant::now();
+        let scheduled = client.invoke_after(Duration::from_millis(100), "scheduled-fire", None);
+        assert!(!scheduled.has_fired());
+
+        match scheduled.await {
+            Ok(FunctionsResponse::Success { .. }) => {}
+            other => panic!("Expected success, got {:?}", other),
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_cancel_or_drop_before_delay_sends_nothing() {
+        let m = mock("POST", "/scheduled-cancel").with_status(200).expect(0).create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let cancelled = client.invoke_after(Duration::from_millis(100), "scheduled-cancel", None);
+        assert!(cancelled.cancel());
+        drop(client.invoke_after(Duration::from_millis(100), "scheduled-cancel", None));
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_detached_invocation_still_fires() {
+        let m = mock("POST", "/scheduled-detach").with_status(200).expect(1).create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        client.invoke_after(Duration::from_millis(50), "scheduled-detach", None).detach();
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        m.assert();
+    }
+}
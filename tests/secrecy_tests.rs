@@ -0,0 +1,46 @@
+#![cfg(feature = "secrecy")]
+
+mod secrecy_tests {
+    use mockito::mock;
+    use secrecy::SecretString;
+    use supabase_function_rs::{FunctionsClient, FunctionsResponse, Secret};
+
+    #[tokio::test]
+    async fn test_credentials_are_sent_but_not_printed() {
+        let _m = mock("POST", "/function-name")
+            .match_header("authorization", "Bearer secret-token")
+            .match_header("apikey", "secret-key")
+            .with_status(200)
+            .with_body("ok")
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        client.set_auth(SecretString::from("secret-token")).unwrap();
+        let key: Secret = SecretString::from("secret-key");
+        client.set_api_key(key);
+
+        let debug = format!("{:?}", client);
+        assert!(!debug.contains("secret-token"), "{}", debug);
+        assert!(!debug.contains("secret-key"), "{}", debug);
+
+        match client.invoke("function-name", None).await {
+            Ok(FunctionsResponse::Success { .. }) => {}
+            other => panic!("Expected success, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_credentials_from_headers_are_not_printed() {
+        let client = FunctionsClient::builder("https://example.com/functions/v1")
+            .header("authorization", "Bearer secret-token")
+            .header("apikey", "secret-key")
+            .header("x-custom", "visible")
+            .build()
+            .unwrap();
+
+        let debug = format!("{:?}", client);
+        assert!(!debug.contains("secret-token"), "{}", debug);
+        assert!(!debug.contains("secret-key"), "{}", debug);
+        assert!(debug.contains("visible"));
+    }
+}
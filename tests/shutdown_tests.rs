@@ -0,0 +1,64 @@
+mod common;
+
+mod shutdown_tests {
+    use crate::common;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+    use supabase_function_rs::{FunctionsClient, FunctionsError, ShutdownReport};
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok";
+
+    /// Answers every request after a delay taken from its path, e.g. `/delay-200` after 200ms.
+    fn serve_delayed() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                thread::spawn(move || {
+                    let head = common::read_request(&mut stream);
+                    let millis = head.split(['-', ' ']).nth(2).and_then(|ms| ms.parse().ok()).unwrap_or(0);
+                    thread::sleep(Duration::from_millis(millis));
+                    let _ = stream.write_all(OK.as_bytes());
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_in_flight_and_rejects_new_invocations() {
+        let client = FunctionsClient::new(serve_delayed(), None, None);
+
+        let quick = tokio::spawn({
+            let client = client.clone();
+            async move { client.invoke("delay-100", None).await }
+        });
+        let straggler = tokio::spawn({
+            let client = client.clone();
+            async move { client.invoke("delay-5000", None).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let report = client.shutdown(Duration::from_millis(1000)).await;
+        assert_eq!(report, ShutdownReport { completed: 1, abandoned: 1 });
+        assert!(quick.await.unwrap().is_ok());
+        assert!(!straggler.is_finished());
+
+        assert!(matches!(client.invoke("delay-0", None).await, Err(FunctionsError::ShuttingDown)));
+        assert!(matches!(client.clone().invoke("delay-0", None).await, Err(FunctionsError::ShuttingDown)));
+        straggler.abort();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_with_nothing_in_flight_returns_at_once() {
+        let client = FunctionsClient::new(serve_delayed(), None, None);
+        assert!(client.invoke("delay-0", None).await.is_ok());
+
+        let report = tokio::time::timeout(Duration::from_secs(1), client.shutdown(Duration::from_secs(60))).await.unwrap();
+        assert_eq!(report, ShutdownReport::default());
+        assert!(matches!(client.invoke("delay-0", None).await, Err(FunctionsError::ShuttingDown)));
+    }
+}
@@ -0,0 +1,112 @@
+mod signing_tests {
+    use hmac::{Hmac, Mac};
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+    use sha2::Sha256;
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient, FunctionsError, HttpMethod, SignableRequest};
+
+    const SECRET: &[u8] = b"signing-secret";
+    const TIMESTAMP: &str = "1700000000";
+
+    fn signature(timestamp: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET).unwrap();
+        mac.update(timestamp.as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn signed_client() -> FunctionsClient {
+        let mut client = FunctionsClient::new(mockito::server_url(), None, None);
+        client.set_request_signer(|request: &SignableRequest| {
+            assert_eq!(request.method, "POST");
+            assert_eq!(request.path, "/verify");
+            Ok(vec![
+                ("x-timestamp".to_string(), TIMESTAMP.to_string()),
+                ("x-signature".to_string(), signature(TIMESTAMP, request.body)),
+            ])
+        });
+        client
+    }
+
+    async fn assert_signed(options: FunctionInvokeOptions, body: &[u8]) {
+        let verify = mock("POST", "/verify")
+            .match_header("x-timestamp", TIMESTAMP)
+            .match_header("x-signature", signature(TIMESTAMP, body).as_str())
+            .match_body(body.to_vec())
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        signed_client().invoke("verify", Some(options)).await.unwrap();
+        verify.assert();
+    }
+
+    #[tokio::test]
+    async fn test_signs_json_body() {
+        assert_signed(FunctionInvokeOptions::json([("amount", json!(42))]), br#"{"amount":42}"#).await;
+    }
+
+    #[tokio::test]
+    async fn test_signs_text_body() {
+        assert_signed(FunctionInvokeOptions::text("hello"), b"hello").await;
+    }
+
+    #[tokio::test]
+    async fn test_signs_binary_body() {
+        assert_signed(FunctionInvokeOptions::bytes(vec![0, 159, 146, 150, 255]), &[0, 159, 146, 150, 255]).await;
+    }
+
+    #[tokio::test]
+    async fn test_signs_empty_body() {
+        let options = FunctionInvokeOptions { method: Some(HttpMethod::Post), ..Default::default() };
+        assert_signed(options, b"").await;
+    }
+
+    #[cfg(feature = "multipart")]
+    #[tokio::test]
+    async fn test_multipart_body_is_rejected() {
+        let verify = mock("POST", "/verify").expect(0).create();
+
+        let result = signed_client().invoke("verify", Some(FunctionInvokeOptions::form([("field", "value")]))).await;
+        assert!(matches!(result, Err(FunctionsError::BuildError(ref message)) if message.contains("multipart")));
+        verify.assert();
+    }
+
+    #[tokio::test]
+    async fn test_signer_error_aborts_request() {
+        let verify = mock("POST", "/verify").expect(0).create();
+
+        let mut client = FunctionsClient::new(mockito::server_url(), None, None);
+        client.set_request_signer(|_: &SignableRequest| Err(FunctionsError::BuildError("key unavailable".into())));
+        let result = client.invoke("verify", Some(FunctionInvokeOptions::text("hello"))).await;
+        assert_eq!(result, Err(FunctionsError::BuildError("key unavailable".into())));
+        verify.assert();
+    }
+
+    #[tokio::test]
+    async fn test_build_request_matches_what_invoke_sends() {
+        let client = signed_client();
+        client.set_auth("secret-token").unwrap();
+        let mut options = FunctionInvokeOptions::json([("amount", json!(42))]);
+        options.headers = Some([("x-custom".to_string(), "custom".to_string())].into());
+
+        let prepared = client.build_request("verify", Some(options.clone())).await.unwrap();
+        assert_eq!(prepared.method, "POST");
+        assert_eq!(prepared.url.as_str(), format!("{}/verify", mockito::server_url()));
+        assert_eq!(prepared.body.as_deref(), Some(&br#"{"amount":42}"#[..]));
+        let headers = prepared.headers();
+        assert!(headers.contains(&("authorization".to_string(), "[REDACTED]".to_string())));
+        assert!(headers.contains(&("x-custom".to_string(), "custom".to_string())));
+        assert!(headers.contains(&("x-signature".to_string(), signature(TIMESTAMP, br#"{"amount":42}"#))));
+        assert!(!format!("{:?}", prepared).contains("secret-token"));
+
+        let expected = prepared.unredacted_headers().iter().fold(
+            mock("POST", "/verify").match_body(prepared.body.clone().unwrap().to_vec()),
+            |mock, (name, value)| mock.match_header(name.as_str(), Matcher::Exact(value.to_str().unwrap().to_string())),
+        );
+        let verify = expected.with_status(200).expect(1).create();
+        client.invoke("verify", Some(options)).await.unwrap();
+        verify.assert();
+    }
+}
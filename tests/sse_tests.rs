@@ -0,0 +1,163 @@
+mod common;
+
+mod sse_tests {
+    use crate::common;
+    use futures_util::StreamExt;
+    use mockito::mock;
+    use std::time::Duration;
+    use supabase_function_rs::{FunctionsClient, FunctionsError, MockSleeper, SseEvent, SseReconnect, StreamTextOptions};
+
+    fn event_stream(body: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n{}",
+            body
+        )
+    }
+
+    fn event(id: Option<&str>, data: &str) -> SseEvent {
+        SseEvent { id: id.map(str::to_string), event: None, data: data.to_string() }
+    }
+
+    fn last_event_id(head: &str) -> Option<&str> {
+        head.lines()
+            .find_map(|line| line.split_once(':').filter(|(name, _)| name.eq_ignore_ascii_case("last-event-id")))
+            .map(|(_, value)| value.trim())
+    }
+
+    #[tokio::test]
+    async fn test_parses_event_stream() {
+        let _m = mock("POST", "/function-name")
+            .match_header("accept", "text/event-stream")
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(": comment\r\nevent: update\r\nid: 1\r\ndata: first\r\ndata: second\r\n\r\ndata:no space\n\nid: 2\n\n")
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let events: Vec<_> = client.invoke_sse("function-name", None).await.unwrap().collect().await;
+        let events: Vec<_> = events.into_iter().map(Result::unwrap).collect();
+
+        assert_eq!(
+            events,
+            vec![
+                SseEvent { id: Some("1".into()), event: Some("update".into()), data: "first\nsecond".into() },
+                event(None, "no space"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_with_last_event_id() {
+        let (url, requests) = common::serve_recorded(vec![
+            event_stream("retry: 50\nid: 1\ndata: a\n\ndata: no id\n\ndata: cut off"),
+            event_stream("id: 2\ndata: b\n\n"),
+        ]);
+
+        let sleeper = MockSleeper::new();
+        let mut client = FunctionsClient::new(url, None, None);
+        client.set_sleeper(sleeper.clone());
+
+        let stream = client.invoke_sse("function-name", None).await.unwrap().reconnect(SseReconnect::new(1));
+        let events: Vec<_> = stream.map(Result::unwrap).collect().await;
+
+        assert_eq!(events, vec![event(Some("1"), "a"), event(None, "no id"), event(Some("2"), "b")]);
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(last_event_id(&requests[0]), None);
+        assert_eq!(last_event_id(&requests[1]), Some("1"));
+        assert_eq!(sleeper.slept(), vec![Duration::from_millis(50)]);
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_after_mid_stream_error() {
+        let (url, requests) = common::serve_recorded(vec![
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: 1000\r\nConnection: close\r\n\r\nid: 7\ndata: a\n\n".to_string(),
+            event_stream("data: b\n\n"),
+        ]);
+
+        let sleeper = MockSleeper::new();
+        let mut client = FunctionsClient::new(url, None, None);
+        client.set_sleeper(sleeper.clone());
+
+        let reconnect = SseReconnect::new(3).default_retry(Duration::from_millis(5));
+        let stream = client.invoke_sse("function-name", None).await.unwrap().reconnect(reconnect);
+        let events: Vec<_> = stream.take(2).map(Result::unwrap).collect().await;
+
+        assert_eq!(events, vec![event(Some("7"), "a"), event(None, "b")]);
+        assert_eq!(last_event_id(&requests.lock().unwrap()[1]), Some("7"));
+        assert_eq!(sleeper.slept()[0], Duration::from_millis(5));
+    }
+
+    #[tokio::test]
+    async fn test_mid_stream_error_without_reconnect() {
+        let url = common::serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: 1000\r\nConnection: close\r\n\r\ndata: a\n\n".to_string(),
+        ]);
+
+        let client = FunctionsClient::new(url, None, None);
+        let mut stream = client.invoke_sse("function-name", None).await.unwrap();
+
+        assert_eq!(stream.next().await.unwrap().unwrap(), event(None, "a"));
+        assert!(matches!(stream.next().await, Some(Err(FunctionsError::FetchError(_)))));
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stops_after_max_reconnects() {
+        let (url, requests) = common::serve_recorded(vec![
+            event_stream("data: a\n\n"),
+            event_stream("data: b\n\n"),
+            event_stream("data: c\n\n"),
+        ]);
+
+        let mut client = FunctionsClient::new(url, None, None);
+        client.set_sleeper(MockSleeper::new());
+
+        let stream = client.invoke_sse("function-name", None).await.unwrap().reconnect(SseReconnect::new(1));
+        let events: Vec<_> = stream.map(Result::unwrap).collect().await;
+
+        assert_eq!(events, vec![event(None, "a"), event(None, "b")]);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stream_text_collects_deltas_until_done() {
+        let body = "data: {\"delta\": \"Hel\"}\n\ndata: {\"delta\": \"lo, \"}\n\ndata: {\"delta\": \"world\"}\n\ndata: [DONE]\n\ndata: {\"delta\": \"ignored\"}\n\n";
+        let _m = mock("POST", "/stream-text")
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(body)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let mut chunks = Vec::new();
+        let text = client
+            .invoke_stream_text("stream-text", None, StreamTextOptions::new(), |chunk| chunks.push(chunk.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(chunks, ["Hel", "lo, ", "world"]);
+        assert_eq!(text, "Hello, world");
+    }
+
+    #[tokio::test]
+    async fn test_stream_text_pointer_and_malformed_events() {
+        let body = "data: {\"choices\": [{\"delta\": {\"content\": \"a\"}}]}\n\ndata: not json\n\ndata: {\"choices\": [{\"delta\": {}}]}\n\ndata: {\"choices\": [{\"delta\": {\"content\": \"b\"}}]}\n\n";
+        let _m = mock("POST", "/stream-text-openai")
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(body)
+            .expect(2)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let openai = StreamTextOptions::new().pointer("/choices/0/delta/content");
+        let skipping = openai.clone().skip_malformed(true);
+        let text = client.invoke_stream_text("stream-text-openai", None, skipping, |_| {}).await.unwrap();
+        assert_eq!(text, "ab");
+
+        let mut chunks = Vec::new();
+        let result = client.invoke_stream_text("stream-text-openai", None, openai, |chunk| chunks.push(chunk.to_string())).await;
+        assert!(matches!(result, Err(FunctionsError::InvalidJson { .. })), "{:?}", result);
+        assert_eq!(chunks, ["a"]);
+    }
+}
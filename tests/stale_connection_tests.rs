@@ -0,0 +1,57 @@
+mod common;
+
+mod stale_connection_tests {
+    use crate::common;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient, FunctionsError, HttpMethod, ResponseData};
+
+    /// Closes the first connection after reading its request, like a server that dropped an
+    /// idle pooled connection, and answers `ok` on the rest. Counts the requests read.
+    fn serve_closing_first() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counted = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                common::read_request(&mut stream);
+                if counted.fetch_add(1, Ordering::SeqCst) > 0 {
+                    let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok");
+                }
+            }
+        });
+        (url, requests)
+    }
+
+    fn get() -> Option<FunctionInvokeOptions> {
+        Some(FunctionInvokeOptions { method: Some(HttpMethod::Get), ..Default::default() })
+    }
+
+    #[tokio::test]
+    async fn test_get_is_resent_once_on_a_closed_connection() {
+        let (url, requests) = serve_closing_first();
+        let client = FunctionsClient::new(url, None, None);
+
+        let response = client.invoke("status", get()).await.unwrap();
+        assert_eq!(response.data(), Some(&ResponseData::Text("ok".to_string())));
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_post_and_opted_out_get_are_not_resent() {
+        let (url, requests) = serve_closing_first();
+        let client = FunctionsClient::new(url, None, None);
+        assert!(matches!(client.invoke("submit", None).await, Err(FunctionsError::FetchError(_) | FunctionsError::ConnectError { .. })));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let (url, requests) = serve_closing_first();
+        let client = FunctionsClient::builder(url).retry_stale_connections(false).build().unwrap();
+        assert!(client.invoke("status", get()).await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,62 @@
+mod common;
+
+mod tls_tests {
+    use crate::common;
+    use native_tls::{Identity, TlsAcceptor};
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+    use supabase_function_rs::{Certificate, ConnectErrorKind, FunctionsClient};
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok";
+
+    /// Serves one HTTPS connection with a fresh self-signed certificate for `localhost`,
+    /// returning the base URL and the certificate PEM.
+    fn serve_tls() -> (String, String) {
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_pem = certified.cert.pem();
+        let identity = Identity::from_pkcs8(cert_pem.as_bytes(), certified.key_pair.serialize_pem().as_bytes()).unwrap();
+        let acceptor = TlsAcceptor::new(identity).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("https://localhost:{}", listener.local_addr().unwrap().port());
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            if let Ok(mut stream) = acceptor.accept(stream) {
+                common::read_request(&mut stream);
+                let _ = stream.write_all(OK.as_bytes());
+                let _ = stream.shutdown();
+            }
+        });
+        (url, cert_pem)
+    }
+
+    #[tokio::test]
+    async fn test_self_signed_certificate_is_rejected_by_default() {
+        let (url, _) = serve_tls();
+        let client = FunctionsClient::builder(url).build().unwrap();
+        match client.invoke("hello", None).await {
+            Err(e) if e.connect_kind() == Some(ConnectErrorKind::TlsHandshake) => {}
+            other => panic!("Expected a TLS failure, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_added_root_certificate_is_trusted() {
+        let (url, cert_pem) = serve_tls();
+        let client = FunctionsClient::builder(url)
+            .add_root_certificate(Certificate::from_pem(cert_pem.as_bytes()).unwrap())
+            .build()
+            .unwrap();
+        let response = client.invoke("hello", None).await;
+        assert!(response.is_ok(), "{:?}", response);
+    }
+
+    #[tokio::test]
+    async fn test_danger_accept_invalid_certs() {
+        let (url, _) = serve_tls();
+        let client = FunctionsClient::builder(url).danger_accept_invalid_certs(true).build().unwrap();
+        let response = client.invoke("hello", None).await;
+        assert!(response.is_ok(), "{:?}", response);
+    }
+}
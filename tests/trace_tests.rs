@@ -0,0 +1,58 @@
+#![cfg(feature = "opentelemetry")]
+
+mod trace_tests {
+    use mockito::{mock, Matcher};
+    use opentelemetry::trace::{FutureExt, SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+    use opentelemetry::Context;
+    use std::collections::HashMap;
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient};
+
+    fn traced_client() -> FunctionsClient {
+        FunctionsClient::builder(mockito::server_url()).propagate_trace_context(true).build().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_current_span_is_propagated() {
+        let _m = mock("POST", "/traced")
+            .match_header("traceparent", "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+            .match_header("tracestate", "vendor=value")
+            .with_status(200)
+            .create();
+
+        let span_context = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::from_key_value([("vendor", "value")]).unwrap(),
+        );
+        let context = Context::current().with_remote_span_context(span_context);
+        traced_client().invoke("traced", None).with_context(context).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_new_trace_is_started_without_a_span() {
+        let _m = mock("POST", "/untraced")
+            .match_header("traceparent", Matcher::Regex("^00-[0-9a-f]{32}-[0-9a-f]{16}-01$".into()))
+            .match_header("tracestate", Matcher::Missing)
+            .with_status(200)
+            .create();
+        let _plain = mock("POST", "/untraced").match_header("traceparent", Matcher::Missing).with_status(500).create();
+
+        traced_client().invoke("untraced", None).await.unwrap();
+        // Off by default.
+        assert!(FunctionsClient::new(mockito::server_url(), None, None).invoke("untraced", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_explicit_traceparent_is_preserved() {
+        let explicit = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-00";
+        let _m = mock("POST", "/explicit").match_header("traceparent", explicit).with_status(200).create();
+
+        let options = FunctionInvokeOptions {
+            headers: Some(HashMap::from([("Traceparent".to_string(), explicit.to_string())])),
+            ..Default::default()
+        };
+        traced_client().invoke("explicit", Some(options)).await.unwrap();
+    }
+}
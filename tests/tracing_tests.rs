@@ -0,0 +1,141 @@
+#![cfg(feature = "tracing")]
+
+mod tracing_tests {
+    use mockito::mock;
+    use std::collections::BTreeMap;
+    use std::fmt;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use supabase_function_rs::{FunctionsClient, RetryPolicy, Sleeper};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Captured {
+        message: String,
+        span: Option<String>,
+        fields: BTreeMap<String, String>,
+    }
+
+    /// Records the crate's events with their fields and the span they happened in.
+    #[derive(Default)]
+    struct Capture {
+        next_id: AtomicU64,
+        spans: Mutex<BTreeMap<u64, String>>,
+        stack: Mutex<Vec<u64>>,
+        events: Arc<Mutex<Vec<Captured>>>,
+    }
+
+    #[derive(Default)]
+    struct Fields(BTreeMap<String, String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{:?}", value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    impl Subscriber for Capture {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.target().starts_with("supabase_function_rs")
+        }
+
+        fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+            let mut fields = Fields::default();
+            attributes.record(&mut fields);
+            let name = format!("{}{:?}", attributes.metadata().name(), fields.0);
+            self.spans.lock().unwrap().insert(id, name);
+            Id::from_u64(id)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            let message = fields.0.remove("message").unwrap_or_default();
+            let span = self.stack.lock().unwrap().last().map(|id| self.spans.lock().unwrap()[id].clone());
+            self.events.lock().unwrap().push(Captured { message, span, fields: fields.0 });
+        }
+
+        fn enter(&self, span: &Id) {
+            self.stack.lock().unwrap().push(span.into_u64());
+        }
+
+        fn exit(&self, _: &Id) {
+            self.stack.lock().unwrap().pop();
+        }
+    }
+
+    #[derive(Debug)]
+    struct NoSleep;
+
+    impl Sleeper for NoSleep {
+        fn sleep(&self, _: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+            Box::pin(async {})
+        }
+    }
+
+    fn fields(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    #[tokio::test]
+    async fn test_retry_then_success_emits_one_retry_event() {
+        let _failing = mock("POST", "/traced-retry").with_status(503).with_body("secret body").expect(1).create();
+        let _ok = mock("POST", "/traced-retry").with_status(200).expect(1).create();
+
+        let subscriber = Capture::default();
+        let events = subscriber.events.clone();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let mut client = FunctionsClient::builder(mockito::server_url()).header("Authorization", "Bearer secret-token").build().unwrap();
+        client.set_retry_policy(RetryPolicy::new(3).backoff(Duration::from_millis(100), Duration::from_secs(1)));
+        client.set_sleeper(NoSleep);
+        client.invoke("traced-retry", None).await.unwrap();
+
+        let span = Some(r#"invoke{"function": "traced-retry"}"#.to_string());
+        let events = events.lock().unwrap().clone();
+        assert_eq!(
+            events,
+            [
+                Captured {
+                    message: "retry".into(),
+                    span: span.clone(),
+                    fields: fields(&[("attempt", "1"), ("code", "http_5xx"), ("delay_ms", "100"), ("function", "traced-retry"), ("reason", "status 503")]),
+                },
+                Captured {
+                    message: "invoke finished".into(),
+                    span,
+                    fields: fields(&[("attempts", "2"), ("function", "traced-retry"), ("outcome", "success")]),
+                },
+            ]
+        );
+        let logged = format!("{:?}", events);
+        assert!(!logged.contains("secret"), "{}", logged);
+    }
+
+    #[tokio::test]
+    async fn test_no_events_without_retries() {
+        let _failing = mock("POST", "/traced-no-retry").with_status(503).expect(1).create();
+
+        let subscriber = Capture::default();
+        let events = subscriber.events.clone();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        client.invoke("traced-no-retry", None).await.unwrap_err();
+        assert_eq!(*events.lock().unwrap(), []);
+    }
+}
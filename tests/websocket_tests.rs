@@ -0,0 +1,72 @@
+#![cfg(feature = "websocket")]
+
+mod common;
+
+mod websocket_tests {
+    use crate::common;
+    use futures_util::{SinkExt, StreamExt};
+    use std::sync::{Arc, Mutex};
+    use supabase_function_rs::{FunctionRegion, FunctionsClient, FunctionsError, WsMessage};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+
+    /// Echoes every message on one connection, recording the path and headers of its handshake.
+    // The handshake callback's error type is tungstenite's.
+    #[allow(clippy::result_large_err)]
+    async fn serve_echo() -> (String, Arc<Mutex<Option<Request>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handshake = Arc::new(Mutex::new(None));
+        let recorded = handshake.clone();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let record = |request: &Request, response: Response| {
+                *recorded.lock().unwrap() = Some(request.clone());
+                Ok(response)
+            };
+            let mut socket = tokio_tungstenite::accept_hdr_async(stream, record).await.unwrap();
+            while let Some(Ok(message)) = socket.next().await {
+                if message.is_text() || message.is_binary() {
+                    socket.send(message).await.unwrap();
+                }
+            }
+        });
+        (url, handshake)
+    }
+
+    #[tokio::test]
+    async fn test_websocket_round_trip_with_client_headers() {
+        let (url, handshake) = serve_echo().await;
+        let client = FunctionsClient::new(url, None, Some(FunctionRegion::EuWest1));
+        client.set_auth("ws-token").unwrap();
+
+        let socket = client.connect_websocket("chat", None).await.unwrap();
+        let (mut outgoing, mut incoming) = socket.split();
+        outgoing.send(WsMessage::text("hello")).await.unwrap();
+        outgoing.send(WsMessage::binary(vec![1, 2, 3])).await.unwrap();
+        assert_eq!(incoming.next().await.unwrap().unwrap(), WsMessage::text("hello"));
+        assert_eq!(incoming.next().await.unwrap().unwrap(), WsMessage::binary(vec![1, 2, 3]));
+
+        let request = handshake.lock().unwrap().take().unwrap();
+        assert_eq!(request.uri().path(), "/chat");
+        assert_eq!(request.headers()["authorization"], "Bearer ws-token");
+        assert_eq!(request.headers()["x-region"], "eu-west-1");
+    }
+
+    #[tokio::test]
+    async fn test_rejected_handshake_is_http_error() {
+        let url = common::serve(vec![
+            "HTTP/1.1 401 Unauthorized\r\nContent-Type: application/json\r\nContent-Length: 19\r\nConnection: close\r\n\r\n{\"msg\":\"bad token\"}".to_string(),
+        ]);
+        let client = FunctionsClient::new(url, None, None);
+
+        match client.connect_websocket("chat", None).await {
+            Err(FunctionsError::HttpError { function_name, status, body, metadata }) => {
+                assert_eq!((function_name.as_str(), status), ("chat", 401));
+                assert_eq!(body, r#"{"msg":"bad token"}"#);
+                assert_eq!(metadata.content_type.as_deref(), Some("application/json"));
+            }
+            other => panic!("Expected HttpError, got {:?}", other),
+        }
+    }
+}
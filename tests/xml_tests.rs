@@ -0,0 +1,67 @@
+#![cfg(feature = "xml")]
+
+mod xml_tests {
+    use mockito::mock;
+    use serde::{Deserialize, Serialize};
+    use supabase_function_rs::{FunctionInvokeOptions, FunctionsClient, FunctionsError, ResponseData};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Order {
+        id: u32,
+        customer: String,
+        item: Vec<String>,
+    }
+
+    const ORDER_XML: &str = "<Order><id>7</id><customer>Ada &amp; Co</customer><item>tea</item><item>scones</item></Order>";
+
+    fn order() -> Order {
+        Order { id: 7, customer: "Ada & Co".into(), item: vec!["tea".into(), "scones".into()] }
+    }
+
+    #[tokio::test]
+    async fn test_xml_round_trip() {
+        let _m = mock("POST", "/xml-echo")
+            .match_header("content-type", "application/xml")
+            .match_body(ORDER_XML)
+            .with_status(200)
+            .with_header("content-type", "application/soap+xml; charset=utf-8")
+            .with_body(ORDER_XML)
+            .create();
+
+        let client = FunctionsClient::new(mockito::server_url(), None, None);
+        let options = FunctionInvokeOptions::xml_from(&order()).unwrap();
+        let response = client.invoke("xml-echo", Some(options)).await.unwrap();
+        let data = response.data().unwrap();
+        assert_eq!(data, &ResponseData::Text(ORDER_XML.to_string()));
+        assert_eq!(data.xml_into::<Order>().unwrap(), order());
+
+        // An `Accept` of an XML type parses the response as text too, whatever its label.
+        let _labelled = mock("GET", "/xml-labelled")
+            .with_status(200)
+            .with_header("content-type", "application/octet-stream")
+            .with_body(ORDER_XML)
+            .create();
+        let options = FunctionInvokeOptions { method: Some(supabase_function_rs::HttpMethod::Get), ..Default::default() }
+            .accept("text/xml");
+        let response = client.invoke("xml-labelled", Some(options)).await.unwrap();
+        assert_eq!(response.data().unwrap().xml_into::<Order>().unwrap(), order());
+    }
+
+    #[test]
+    fn test_malformed_xml_error_quotes_snippet() {
+        let data = ResponseData::Text("<Order><id>7</id><customer>Ada</client><item>tea</item></Order>".into());
+        match data.xml_into::<Order>() {
+            Err(FunctionsError::DeserializeError(message)) => {
+                assert!(message.starts_with("invalid XML: "), "{}", message);
+                assert!(message.contains("</client>"), "{}", message);
+            }
+            other => panic!("Expected DeserializeError, got {:?}", other),
+        }
+
+        let data = ResponseData::Text("<Order><id>seven</id></Order>".into());
+        match data.xml_into::<Order>() {
+            Err(FunctionsError::DeserializeError(message)) => assert!(message.ends_with("near `<Order><id>seven</id></Order>`"), "{}", message),
+            other => panic!("Expected DeserializeError, got {:?}", other),
+        }
+    }
+}